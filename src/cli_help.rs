@@ -0,0 +1,362 @@
+use crate::reth_node::CliOption;
+
+/// Section used for options listed before any clap help heading
+const DEFAULT_SECTION: &str = "Options";
+
+/// Clap's built-in flags, which make no sense as launch parameters
+const BUILTIN_FLAGS: &[&str] = &["--help", "--version"];
+
+/// Parse the long help output of a clap command (e.g. `reth node --help`) into options.
+/// Handles section headings, short flags, multi-line help, inline short help and the
+/// `[default: …]`, `[env: …]`, `[possible values: …]` and `[aliases: …]` annotations.
+pub fn parse_help(text: &str) -> Vec<CliOption> {
+    let mut options = Vec::new();
+    let mut section = DEFAULT_SECTION.to_string();
+    let mut current: Option<(OptionHeader, Vec<String>)> = None;
+
+    for line in text.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+
+        // Section headings are unindented lines such as "Networking:"
+        if indent == 0 && trimmed.ends_with(':') && !trimmed.is_empty() {
+            if let Some((header, body)) = current.take() {
+                options.extend(build_option(header, &body, &section));
+            }
+            section = trimmed.trim_end_matches(':').to_string();
+            continue;
+        }
+
+        // Option lines are indented less than their help text, which clap indents by 10
+        if indent > 0 && indent < 10 && trimmed.starts_with('-') {
+            if let Some((header, body)) = current.take() {
+                options.extend(build_option(header, &body, &section));
+            }
+            let (spec, inline_help) = match trimmed.find("  ") {
+                Some(pos) => (&trimmed[..pos], trimmed[pos..].trim()),
+                None => (trimmed, ""),
+            };
+            let body = if inline_help.is_empty() { Vec::new() } else { vec![inline_help.to_string()] };
+            current = Some((parse_option_spec(spec), body));
+            continue;
+        }
+
+        if let Some((_, body)) = current.as_mut() {
+            if indent == 0 && !trimmed.is_empty() {
+                // Unindented text ends the option list (e.g. a trailing note)
+                if let Some((header, body)) = current.take() {
+                    options.extend(build_option(header, &body, &section));
+                }
+            } else {
+                body.push(trimmed.to_string());
+            }
+        }
+    }
+
+    if let Some((header, body)) = current.take() {
+        options.extend(build_option(header, &body, &section));
+    }
+
+    options
+}
+
+/// The flag part of an option line, e.g. `-d, --disable-discovery` or `--http.api <HTTP_API>`
+struct OptionHeader {
+    long: Option<String>,
+    short: Option<String>,
+    value_name: Option<String>,
+    repeatable: bool,
+}
+
+fn parse_option_spec(spec: &str) -> OptionHeader {
+    let mut header = OptionHeader { long: None, short: None, value_name: None, repeatable: false };
+
+    for token in spec.split_whitespace() {
+        let token = token.trim_end_matches(',');
+        if token.ends_with("...") {
+            header.repeatable = true;
+        }
+        let token = token.trim_end_matches("...");
+
+        if let Some(rest) = token.strip_prefix("--") {
+            // Optional values are written as --flag[=<VALUE>]
+            let (name, value) = match rest.split_once("[=") {
+                Some((name, value)) => (name, Some(value.trim_end_matches(']'))),
+                None => match rest.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (rest, None),
+                },
+            };
+            header.long = Some(format!("--{}", name));
+            if let Some(value) = value {
+                header.value_name = Some(value.trim_matches(|c| c == '<' || c == '>').to_string());
+            }
+        } else if token.starts_with('-') && token.len() == 2 {
+            header.short = Some(token.to_string());
+        } else if token.starts_with('<') || token.starts_with("[<") {
+            header.value_name = Some(token.trim_matches(|c| c == '<' || c == '>' || c == '[' || c == ']').to_string());
+        }
+    }
+
+    header
+}
+
+/// Remove a `[label: …]` annotation from the help text and return its contents.
+/// Brackets inside the value are balanced, so `[default: [::]:30303]` keeps the whole address.
+fn take_annotation(help: &mut String, label: &str) -> Option<String> {
+    let marker = format!("[{}: ", label);
+    let start = help.find(&marker)?;
+    let mut depth = 0usize;
+    let end = start + help[start..].char_indices().find_map(|(index, c)| {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+        None
+    })?;
+    let value = help[start + marker.len()..end].trim().to_string();
+    help.replace_range(start..=end, "");
+    Some(value)
+}
+
+/// Pull a "Possible values:" block with "- name: description" entries out of the help text
+fn take_possible_values_block(help: &mut String) -> Option<Vec<String>> {
+    let start = help.find("Possible values:")?;
+    let mut values = Vec::new();
+    let mut end = start + "Possible values:".len();
+    for line in help[end..].split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.is_empty() && values.is_empty() {
+            end += line.len();
+            continue;
+        }
+        let Some(entry) = trimmed.strip_prefix("- ") else {
+            break;
+        };
+        let name = entry.split(':').next().unwrap_or(entry).trim();
+        values.push(name.to_string());
+        end += line.len();
+    }
+    if values.is_empty() {
+        return None;
+    }
+    help.replace_range(start..end, "");
+    Some(values)
+}
+
+fn build_option(header: OptionHeader, body: &[String], section: &str) -> Option<CliOption> {
+    let name = header.long?;
+    if BUILTIN_FLAGS.contains(&name.as_str()) {
+        return None;
+    }
+
+    let mut help = body.join("\n");
+
+    let default_value = take_annotation(&mut help, "default");
+    let env = take_annotation(&mut help, "env")
+        .map(|env| env.split('=').next().unwrap_or_default().trim().to_string());
+    let mut aliases: Vec<String> = take_annotation(&mut help, "aliases")
+        .or_else(|| take_annotation(&mut help, "alias"))
+        .map(|list| {
+            list.split(',')
+                .map(|alias| alias.trim())
+                .filter(|alias| !alias.is_empty())
+                .map(|alias| if alias.starts_with('-') { alias.to_string() } else { format!("--{}", alias) })
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(short_aliases) = take_annotation(&mut help, "short aliases") {
+        aliases.extend(short_aliases.split(',').map(|alias| format!("-{}", alias.trim())));
+    }
+    let possible_values = take_annotation(&mut help, "possible values")
+        .map(|list| {
+            list.split(',')
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .or_else(|| take_possible_values_block(&mut help))
+        .filter(|values| !values.is_empty());
+
+    // Collapse the blank lines left behind by removed annotations
+    let help = help
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let description = help.lines().next().unwrap_or_default().trim().to_string();
+
+    let help_lower = help.to_lowercase();
+    let accepts_multiple = header.repeatable
+        || help_lower.contains("comma-separated")
+        || help_lower.contains("comma separated")
+        || help_lower.contains("list of")
+        || name.contains(".api")
+        || name.contains(".namespaces");
+
+    Some(CliOption {
+        takes_value: header.value_name.is_some(),
+        value_name: header.value_name,
+        name,
+        description,
+        possible_values,
+        accepts_multiple,
+        short: header.short,
+        default_value,
+        env,
+        section: section.to_string(),
+        aliases,
+        help,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../node-help.txt");
+
+    fn find<'a>(options: &'a [CliOption], name: &str) -> &'a CliOption {
+        options.iter().find(|option| option.name == name).unwrap_or_else(|| panic!("{} not parsed", name))
+    }
+
+    #[test]
+    fn parses_every_option_in_the_fixture() {
+        let options = parse_help(FIXTURE);
+
+        let option_lines = FIXTURE
+            .lines()
+            .filter(|line| {
+                let indent = line.len() - line.trim_start().len();
+                indent > 0 && indent < 10 && line.trim_start().starts_with('-')
+            })
+            .count();
+        // Everything except --help
+        assert_eq!(options.len(), option_lines - 1);
+        assert!(options.iter().all(|option| !BUILTIN_FLAGS.contains(&option.name.as_str())));
+
+        let mut names: Vec<&str> = options.iter().map(|option| option.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), options.len());
+    }
+
+    #[test]
+    fn keeps_sections() {
+        let options = parse_help(FIXTURE);
+        assert_eq!(find(&options, "--config").section, "Options");
+        assert_eq!(find(&options, "--metrics").section, "Metrics");
+        assert_eq!(find(&options, "--disable-discovery").section, "Networking");
+        assert_eq!(find(&options, "--rpc-cache.max-blocks").section, "RPC State Cache");
+        assert_eq!(find(&options, "--verbosity").section, "Display");
+    }
+
+    #[test]
+    fn reads_values_and_defaults() {
+        let options = parse_help(FIXTURE);
+
+        let port = find(&options, "--http.port");
+        assert!(port.takes_value);
+        assert_eq!(port.value_name.as_deref(), Some("HTTP_PORT"));
+        assert_eq!(port.default_value.as_deref(), Some("8545"));
+        assert_eq!(port.description, "Http server port to listen on");
+
+        let http = find(&options, "--http");
+        assert!(!http.takes_value);
+        assert_eq!(http.default_value, None);
+
+        let etherscan = find(&options, "--debug.etherscan");
+        assert_eq!(etherscan.value_name.as_deref(), Some("ETHERSCAN_API_URL"));
+
+        assert_eq!(find(&options, "--log.stdout.filter").default_value.as_deref(), Some(""));
+        assert_eq!(find(&options, "--identity").default_value.as_deref(), Some("reth/v1.1.0-1ba631b/x86_64-unknown-linux-gnu"));
+    }
+
+    #[test]
+    fn reads_possible_values() {
+        let options = parse_help(FIXTURE);
+
+        let api = find(&options, "--http.api");
+        assert!(api.accepts_multiple);
+        let values = api.possible_values.as_ref().unwrap();
+        assert!(values.iter().any(|value| value == "eth"));
+        assert!(values.iter().any(|value| value == "txpool"));
+
+        let format = find(&options, "--log.stdout.format");
+        assert_eq!(format.default_value.as_deref(), Some("terminal"));
+        assert_eq!(format.possible_values.as_deref(), Some(&["json".to_string(), "log-fmt".to_string(), "terminal".to_string()][..]));
+        assert!(!format.help.contains("Possible values"));
+
+        let hook = find(&options, "--debug.invalid-block-hook");
+        assert_eq!(hook.default_value.as_deref(), Some("witness"));
+        assert_eq!(hook.possible_values.as_ref().map(Vec::len), Some(3));
+    }
+
+    #[test]
+    fn reads_short_flags_and_aliases() {
+        let options = parse_help(FIXTURE);
+
+        assert_eq!(find(&options, "--disable-discovery").short.as_deref(), Some("-d"));
+
+        let verbosity = find(&options, "--verbosity");
+        assert_eq!(verbosity.short.as_deref(), Some("-v"));
+        assert!(verbosity.accepts_multiple);
+        assert!(!verbosity.takes_value);
+
+        let request_size = find(&options, "--rpc.max-request-size");
+        assert_eq!(request_size.aliases, vec!["--rpc-max-request-size".to_string()]);
+        assert_eq!(request_size.default_value.as_deref(), Some("15"));
+        assert_eq!(find(&options, "--rpc.max-response-size").aliases, vec!["--rpc.returndata.limit".to_string()]);
+    }
+
+    #[test]
+    fn keeps_multi_line_help() {
+        let options = parse_help(FIXTURE);
+
+        let instance = find(&options, "--instance");
+        assert_eq!(instance.description, "Add a new instance of a node.");
+        assert!(instance.help.contains("Max number of instances is 200"));
+        assert!(!instance.help.contains("[default"));
+
+        let verbosity = find(&options, "--verbosity");
+        assert!(verbosity.help.contains("-vvvvv  Traces"));
+    }
+
+    #[test]
+    fn balances_brackets_in_annotations() {
+        let help = "\
+Networking:
+      --addr <ADDR>
+          Network listening address
+
+          [default: [::]:30303]
+
+      --bootnodes <BOOTNODES>
+          Bootstrap nodes [see docs]
+
+          [default: [a, [b]]]
+          [aliases: boot-nodes]
+";
+        let options = parse_help(help);
+
+        let addr = find(&options, "--addr");
+        assert_eq!(addr.default_value.as_deref(), Some("[::]:30303"));
+        assert_eq!(addr.help, "Network listening address");
+
+        let bootnodes = find(&options, "--bootnodes");
+        assert_eq!(bootnodes.default_value.as_deref(), Some("[a, [b]]"));
+        assert_eq!(bootnodes.aliases, vec!["--boot-nodes".to_string()]);
+        assert_eq!(bootnodes.help, "Bootstrap nodes [see docs]");
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::cli_help;
+use crate::launch_args::LaunchArg;
+use crate::reth_node::CliOption;
+
+/// Parsed `reth node --help` for one reth build, cached on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliSchema {
+    pub version: String, // First line of `reth --version`
+    pub generated_at: chrono::DateTime<chrono::Local>,
+    pub options: Vec<CliOption>,
+}
+
+/// Result of loading the schema in the background
+pub struct CliSchemaLoad {
+    pub schema: CliSchema,
+    /// Schema of the previously used reth build, when this version was seen for the first time
+    pub previous: Option<CliSchema>,
+}
+
+impl CliSchema {
+    /// Directory holding the cached schemas
+    pub fn cache_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".reth-desktop")
+            .join("cache")
+    }
+
+    /// Cache file for a version, e.g. cli-reth-Version-1.5.0-dev.json
+    fn path_for(version: &str) -> PathBuf {
+        let sanitized: String = version
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '-' })
+            .collect();
+        let sanitized = sanitized
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        Self::cache_dir().join(format!("cli-{}.json", sanitized))
+    }
+
+    fn load_file(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(schema) => Some(schema),
+            Err(e) => {
+                eprintln!("Failed to parse CLI schema cache {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        std::fs::create_dir_all(Self::cache_dir())?;
+        let path = Self::path_for(&self.version);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        println!("Cached CLI schema for {} at {}", self.version, path.display());
+        Ok(())
+    }
+
+    /// The most recently written schema of a different version
+    fn latest_other(version: &str) -> Option<Self> {
+        let own_path = Self::path_for(version);
+        let mut candidates: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(Self::cache_dir())
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path != &own_path)
+            .filter(|path| {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                name.starts_with("cli-") && name.ends_with(".json")
+            })
+            .filter_map(|path| Some((path.metadata().ok()?.modified().ok()?, path)))
+            .collect();
+        candidates.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        candidates.into_iter().find_map(|(_, path)| Self::load_file(&path))
+    }
+
+    /// Load the schema for the installed binary from the cache, parsing `reth node --help`
+    /// only when this version hasn't been seen before
+    pub async fn load(reth_path: PathBuf) -> Result<CliSchemaLoad, String> {
+        let output = tokio::process::Command::new(&reth_path)
+            .arg("--version")
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run reth --version: {}", e))?;
+        let version = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .ok_or("reth --version returned no output")?;
+
+        if let Some(schema) = Self::load_file(&Self::path_for(&version)) {
+            println!("Loaded cached CLI schema for {}", version);
+            return Ok(CliSchemaLoad { schema, previous: None });
+        }
+
+        let output = tokio::process::Command::new(&reth_path)
+            .arg("node")
+            .arg("--help")
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run reth node --help: {}", e))?;
+        let options = cli_help::parse_help(&String::from_utf8_lossy(&output.stdout));
+        if options.is_empty() {
+            return Err("No options found in reth node --help".to_string());
+        }
+        println!("Parsed {} CLI options for {}", options.len(), version);
+
+        let previous = Self::latest_other(&version);
+        let schema = CliSchema { version, generated_at: chrono::Local::now(), options };
+        if let Err(e) = schema.save() {
+            eprintln!("Failed to cache CLI schema: {}", e);
+        }
+        Ok(CliSchemaLoad { schema, previous })
+    }
+
+    fn find(&self, flag: &str) -> Option<&CliOption> {
+        self.options.iter().find(|option| option.name == flag)
+    }
+}
+
+/// Flags that changed between two reth versions
+#[derive(Debug, Clone)]
+pub struct CliSchemaDiff {
+    pub from_version: String,
+    pub to_version: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub renamed: Vec<(String, String)>, // (old, new)
+    /// Saved launch parameters using a flag that was removed or renamed
+    pub affected_args: Vec<String>,
+}
+
+impl CliSchemaDiff {
+    /// Compare two schemas and note which saved launch parameters are affected
+    pub fn compute(old: &CliSchema, new: &CliSchema, saved_args: &[LaunchArg]) -> Self {
+        let mut added: Vec<String> = new.options.iter()
+            .filter(|option| old.find(&option.name).is_none())
+            .map(|option| option.name.clone())
+            .collect();
+        let mut removed = Vec::new();
+        let mut renamed = Vec::new();
+
+        for option in old.options.iter().filter(|option| new.find(&option.name).is_none()) {
+            // A rename keeps the old name as an alias, or shows up as a close match in the same section
+            let replacement = new.options.iter()
+                .find(|candidate| candidate.aliases.contains(&option.name))
+                .or_else(|| {
+                    added.iter()
+                        .filter_map(|name| new.find(name))
+                        .filter(|candidate| candidate.section == option.section && candidate.takes_value == option.takes_value)
+                        .find(|candidate| is_probable_rename(&option.name, &candidate.name))
+                });
+            match replacement {
+                Some(replacement) => renamed.push((option.name.clone(), replacement.name.clone())),
+                None => removed.push(option.name.clone()),
+            }
+        }
+        added.retain(|name| !renamed.iter().any(|(_, new_name)| new_name == name));
+
+        let affected_args = saved_args.iter()
+            .map(|arg| arg.flag.clone())
+            .filter(|flag| removed.contains(flag) || renamed.iter().any(|(old_name, _)| old_name == flag))
+            .collect();
+
+        Self {
+            from_version: old.version.clone(),
+            to_version: new.version.clone(),
+            added,
+            removed,
+            renamed,
+            affected_args,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+
+    /// New name for a renamed flag
+    pub fn renamed_to(&self, flag: &str) -> Option<&str> {
+        self.renamed.iter().find(|(old, _)| old == flag).map(|(_, new)| new.as_str())
+    }
+}
+
+/// Renames usually change one dotted segment or a few characters, e.g. --rpc-max-tracing-requests
+/// to --rpc.max-tracing-requests
+fn is_probable_rename(old: &str, new: &str) -> bool {
+    let normalize = |flag: &str| flag.trim_start_matches('-').replace(['.', '_'], "-");
+    if normalize(old) == normalize(new) {
+        return true;
+    }
+    let old_last = old.rsplit(['.', '-']).next().unwrap_or(old);
+    let new_last = new.rsplit(['.', '-']).next().unwrap_or(new);
+    old_last == new_last && old.split('.').next() == new.split('.').next() && old_last.len() > 3
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct RethConfig {
@@ -367,6 +367,22 @@ impl RethConfigManager {
         (RethConfig::default(), None)
     }
     
+    /// Load reth.toml from a specific node data directory, e.g. one passed via --datadir
+    pub fn load_reth_config_from_datadir(datadir: &Path) -> Option<(RethConfig, PathBuf)> {
+        let config_path = datadir.join("reth.toml");
+        let content = std::fs::read_to_string(&config_path).ok()?;
+        match toml::from_str::<RethConfig>(&content) {
+            Ok(config) => {
+                println!("Loaded Reth configuration from: {}", config_path.display());
+                Some((config, config_path))
+            }
+            Err(e) => {
+                eprintln!("Failed to parse reth.toml at {}: {}", config_path.display(), e);
+                None
+            }
+        }
+    }
+    
     /// Save Reth configuration to reth.toml
     pub fn save_reth_config(config: &RethConfig, config_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         let toml_string = toml::to_string_pretty(config)?;
@@ -0,0 +1,297 @@
+use std::path::{Path, PathBuf};
+use crate::config::RethConfigManager;
+
+/// A running Reth node found on the system, with the settings read from its command line
+#[derive(Debug, Clone)]
+pub struct DiscoveredProcess {
+    pub pid: u32,
+    pub argv: Vec<String>,
+    pub exe: Option<PathBuf>, // Resolved executable, e.g. from /proc/<pid>/exe
+    pub cwd: Option<PathBuf>, // Working directory relative paths in the argv are resolved against
+    pub chain: String,
+    pub datadir: PathBuf,
+    pub log_directory: Option<PathBuf>,
+    pub metrics_address: Option<String>,
+    pub ports: Vec<u16>,
+}
+
+impl DiscoveredProcess {
+    fn new(pid: u32, argv: Vec<String>, exe: Option<PathBuf>, cwd: Option<PathBuf>, ports: Vec<u16>) -> Self {
+        let resolve = |path: String| match &cwd {
+            Some(cwd) => cwd.join(path),
+            None => PathBuf::from(path),
+        };
+        let chain = flag_value(&argv, "--chain").unwrap_or_else(|| "mainnet".to_string());
+        // Reth nests its default data directory by chain; an explicit --datadir is used as-is
+        let datadir = flag_value(&argv, "--datadir")
+            .map(resolve)
+            .unwrap_or_else(|| RethConfigManager::get_reth_data_dir().join(&chain));
+        let log_directory = flag_value(&argv, "--log.file.directory").map(resolve);
+        let metrics_address = metrics_address_from_argv(&argv);
+
+        Self { pid, argv, exe, cwd, chain, datadir, log_directory, metrics_address, ports }
+    }
+
+    /// The program to relaunch this node with: the resolved executable, or argv[0]
+    /// made absolute against the node's working directory
+    pub fn program(&self) -> Option<PathBuf> {
+        if let Some(exe) = &self.exe {
+            return Some(exe.clone());
+        }
+        let program = Path::new(self.argv.first()?);
+        // A bare name was found on PATH, which the relaunch searches the same way
+        if program.components().count() == 1 {
+            return Some(program.to_path_buf());
+        }
+        match &self.cwd {
+            Some(cwd) => Some(cwd.join(program)),
+            None => Some(program.to_path_buf()),
+        }
+    }
+
+    /// The argv to relaunch with, starting with `program()`; run it from `cwd`
+    /// so relative paths such as `--datadir data` still name the same directories
+    pub fn relaunch_argv(&self) -> Vec<String> {
+        let mut argv = self.argv.clone();
+        if let (Some(first), Some(program)) = (argv.first_mut(), self.program()) {
+            *first = program.to_string_lossy().to_string();
+        }
+        argv
+    }
+}
+
+/// Whether discovery can identify processes exactly; otherwise callers fall back to port probing
+pub fn is_supported() -> bool {
+    cfg!(target_os = "linux")
+}
+
+/// Metrics endpoint address passed to a node with `--metrics`
+pub fn metrics_address_from_argv(argv: &[String]) -> Option<String> {
+    flag_value(argv, "--metrics").map(|addr| {
+        // `--metrics 9001` is shorthand for a port on localhost
+        if addr.contains(':') { addr } else { format!("127.0.0.1:{}", addr) }
+    })
+}
+
+/// Value of a `--flag value` or `--flag=value` argument
+fn flag_value(argv: &[String], flag: &str) -> Option<String> {
+    let prefix = format!("{}=", flag);
+    for (i, arg) in argv.iter().enumerate() {
+        if arg == flag {
+            return argv.get(i + 1).filter(|value| !value.starts_with("--")).cloned();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Whether an argv belongs to a `reth node` process (and not to reth-desktop or another tool)
+fn is_reth_node(argv: &[String]) -> bool {
+    let Some(program) = argv.first() else {
+        return false;
+    };
+    let name = std::path::Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    (name == "reth" || name == "reth.exe") && argv.iter().skip(1).any(|arg| arg == "node")
+}
+
+/// List all running Reth nodes
+pub fn discover_reth_processes() -> Vec<DiscoveredProcess> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::discover()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        // Without /proc we can still match command lines, but not the sockets each process owns
+        use sysinfo::System;
+        let mut system = System::new();
+        system.refresh_processes();
+        let own_pid = std::process::id();
+        system
+            .processes()
+            .iter()
+            .filter(|(pid, _)| pid.as_u32() != own_pid)
+            .filter(|(_, process)| is_reth_node(process.cmd()))
+            .map(|(pid, process)| {
+                DiscoveredProcess::new(
+                    pid.as_u32(),
+                    process.cmd().to_vec(),
+                    process.exe().map(Path::to_path_buf),
+                    process.cwd().map(Path::to_path_buf),
+                    Vec::new(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use super::{is_reth_node, DiscoveredProcess};
+
+    /// TCP state code for a listening socket in /proc/net/tcp
+    const TCP_LISTEN: &str = "0A";
+
+    pub fn discover() -> Vec<DiscoveredProcess> {
+        let own_pid = std::process::id();
+        let Ok(entries) = std::fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+
+        let mut candidates = Vec::new();
+        for entry in entries.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+                continue;
+            };
+            if pid == own_pid {
+                continue;
+            }
+            if let Some(argv) = read_cmdline(&entry.path()) {
+                if is_reth_node(&argv) {
+                    candidates.push((pid, argv));
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        let listening = listening_sockets();
+        candidates
+            .into_iter()
+            .map(|(pid, argv)| {
+                let ports = process_ports(pid, &listening);
+                let proc_dir = Path::new("/proc").join(pid.to_string());
+                DiscoveredProcess::new(pid, argv, read_exe(&proc_dir), std::fs::read_link(proc_dir.join("cwd")).ok(), ports)
+            })
+            .collect()
+    }
+
+    /// The executable a process runs. When the binary was replaced on disk (e.g. by an upgrade)
+    /// the kernel appends " (deleted)"; the path then names the new binary.
+    fn read_exe(proc_dir: &Path) -> Option<PathBuf> {
+        let exe = std::fs::read_link(proc_dir.join("exe")).ok()?;
+        match exe.to_str().and_then(|exe| exe.strip_suffix(" (deleted)")) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => Some(exe),
+        }
+    }
+
+    /// Read the NUL separated argv of a process
+    fn read_cmdline(proc_dir: &Path) -> Option<Vec<String>> {
+        let raw = std::fs::read(proc_dir.join("cmdline")).ok()?;
+        let argv: Vec<String> = raw
+            .split(|byte| *byte == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).to_string())
+            .collect();
+        if argv.is_empty() { None } else { Some(argv) }
+    }
+
+    /// Map socket inode -> local port for every listening TCP socket
+    fn listening_sockets() -> HashMap<u64, u16> {
+        let mut sockets = HashMap::new();
+        for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+            let Ok(content) = std::fs::read_to_string(table) else {
+                continue;
+            };
+            // Columns: sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode
+            for line in content.lines().skip(1) {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                    continue;
+                }
+                let port = fields[1]
+                    .rsplit(':')
+                    .next()
+                    .and_then(|port| u16::from_str_radix(port, 16).ok());
+                let inode = fields[9].parse::<u64>().ok();
+                if let (Some(port), Some(inode)) = (port, inode) {
+                    sockets.insert(inode, port);
+                }
+            }
+        }
+        sockets
+    }
+
+    /// Ports the process listens on, found by matching its socket fds against the TCP tables
+    fn process_ports(pid: u32, listening: &HashMap<u64, u16>) -> Vec<u16> {
+        let mut ports = Vec::new();
+        // Reading another user's fds needs privileges; the argv is still useful without ports
+        let Ok(fds) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            return ports;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            let inode = target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|inode| inode.parse::<u64>().ok());
+            if let Some(port) = inode.and_then(|inode| listening.get(&inode)) {
+                if !ports.contains(port) {
+                    ports.push(*port);
+                }
+            }
+        }
+        ports.sort_unstable();
+        ports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn relaunches_a_relative_program_from_the_original_directory() {
+        let process = DiscoveredProcess::new(
+            42,
+            argv(&["./reth", "node", "--datadir", "data", "--chain", "sepolia"]),
+            None,
+            Some(PathBuf::from("/home/eth/nodes")),
+            Vec::new(),
+        );
+
+        assert_eq!(process.program(), Some(PathBuf::from("/home/eth/nodes/./reth")));
+        assert_eq!(process.relaunch_argv(), argv(&["/home/eth/nodes/./reth", "node", "--datadir", "data", "--chain", "sepolia"]));
+        assert_eq!(process.datadir, PathBuf::from("/home/eth/nodes/data"));
+        assert_eq!(process.chain, "sepolia");
+    }
+
+    #[test]
+    fn prefers_the_resolved_executable() {
+        let process = DiscoveredProcess::new(
+            42,
+            argv(&["reth", "node", "--datadir=/var/lib/reth", "--log.file.directory", "logs"]),
+            Some(PathBuf::from("/usr/local/bin/reth")),
+            Some(PathBuf::from("/srv")),
+            Vec::new(),
+        );
+
+        assert_eq!(process.relaunch_argv()[0], "/usr/local/bin/reth");
+        assert_eq!(process.datadir, PathBuf::from("/var/lib/reth"));
+        assert_eq!(process.log_directory, Some(PathBuf::from("/srv/logs")));
+    }
+
+    #[test]
+    fn keeps_a_bare_program_name_for_path_lookup() {
+        let process = DiscoveredProcess::new(42, argv(&["reth", "node"]), None, Some(PathBuf::from("/srv")), Vec::new());
+        assert_eq!(process.program(), Some(PathBuf::from("reth")));
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::log_record::LogRecord;
+use crate::launch_args;
+
+/// Everything we know about how a managed Reth process ended
+#[derive(Debug, Clone)]
+pub struct NodeExitReport {
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub stop_requested: bool,
+    pub started_at: chrono::DateTime<chrono::Local>,
+    pub exited_at: chrono::DateTime<chrono::Local>,
+    pub uptime: Duration,
+    pub launch_command: Vec<String>,
+    pub recent_logs: Vec<LogRecord>,
+}
+
+impl NodeExitReport {
+    /// Whether the process ended on its own with a failure (non-zero code or a signal).
+    /// Adopted processes aren't our children, so an unknown status isn't counted as a crash.
+    pub fn is_crash(&self) -> bool {
+        !self.stop_requested && !self.is_status_unknown() && self.exit_code != Some(0)
+    }
+
+    /// Whether neither the exit code nor the signal is known, as for re-adopted processes
+    pub fn is_status_unknown(&self) -> bool {
+        self.exit_code.is_none() && self.signal.is_none()
+    }
+
+    /// Short human readable description of the exit status
+    pub fn describe(&self) -> String {
+        match (self.exit_code, self.signal) {
+            (Some(code), _) => format!("exit code {}", code),
+            (None, Some(signal)) => format!("terminated by signal {}", signal),
+            (None, None) => "exited, status unknown".to_string(),
+        }
+    }
+
+    /// Format the uptime as e.g. "2h 05m 13s"
+    pub fn format_uptime(&self) -> String {
+        let secs = self.uptime.as_secs();
+        let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+        if hours > 0 {
+            format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+        } else if minutes > 0 {
+            format!("{}m {:02}s", minutes, seconds)
+        } else {
+            format!("{}s", seconds)
+        }
+    }
+
+    /// Render the report as a single plain-text bundle suitable for attaching to bug reports
+    pub fn to_text_bundle(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Reth Exit Report");
+        let _ = writeln!(out, "================");
+        let _ = writeln!(out, "Generated by:   reth-desktop {}", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(out, "Platform:       {} {}", std::env::consts::OS, std::env::consts::ARCH);
+        let _ = writeln!(out, "Started at:     {}", self.started_at.format("%Y-%m-%d %H:%M:%S %z"));
+        let _ = writeln!(out, "Exited at:      {}", self.exited_at.format("%Y-%m-%d %H:%M:%S %z"));
+        let _ = writeln!(out, "Uptime:         {}", self.format_uptime());
+        let _ = writeln!(out, "Exit code:      {}", self.exit_code.map_or("none".to_string(), |c| c.to_string()));
+        let _ = writeln!(out, "Signal:         {}", self.signal.map_or("none".to_string(), |s| s.to_string()));
+        let _ = writeln!(out, "Stop requested: {}", if self.stop_requested { "yes" } else { "no" });
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Launch command");
+        let _ = writeln!(out, "--------------");
+        if self.launch_command.is_empty() {
+            let _ = writeln!(out, "(unknown)");
+        } else {
+            let _ = writeln!(out, "{}", launch_args::format_command(&self.launch_command));
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Last {} log lines", self.recent_logs.len());
+        let _ = writeln!(out, "-----------------");
+        for line in &self.recent_logs {
+            let _ = writeln!(out, "{}", line);
+        }
+        out
+    }
+
+    /// Directory where exit reports are written
+    pub fn crashes_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".reth-desktop")
+            .join("crashes")
+    }
+
+    /// File name used for this report, based on the exit time
+    pub fn file_name(&self) -> String {
+        format!("reth-exit-{}.txt", self.exited_at.format("%Y%m%d-%H%M%S"))
+    }
+
+    /// Save the report under ~/.reth-desktop/crashes/
+    pub fn save(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let dir = Self::crashes_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(self.file_name());
+        std::fs::write(&path, self.to_text_bundle())?;
+        println!("Saved exit report to: {}", path.display());
+        Ok(path)
+    }
+
+    /// Export the report to the user's downloads directory (or home as a fallback)
+    pub fn export(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let dir = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or("No export directory available")?;
+        let path = dir.join(self.file_name());
+        std::fs::write(&path, self.to_text_bundle())?;
+        println!("Exported exit report to: {}", path.display());
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(exit_code: Option<i32>, signal: Option<i32>, stop_requested: bool) -> NodeExitReport {
+        let now = chrono::Local::now();
+        NodeExitReport {
+            exit_code,
+            signal,
+            stop_requested,
+            started_at: now,
+            exited_at: now,
+            uptime: Duration::from_secs(3725),
+            launch_command: vec!["reth".to_string(), "node".to_string()],
+            recent_logs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn clean_exit_is_not_a_crash() {
+        let exit = report(Some(0), None, false);
+        assert!(!exit.is_crash());
+        assert_eq!(exit.describe(), "exit code 0");
+    }
+
+    #[test]
+    fn non_zero_code_is_a_crash() {
+        let exit = report(Some(101), None, false);
+        assert!(exit.is_crash());
+        assert_eq!(exit.describe(), "exit code 101");
+    }
+
+    #[test]
+    fn signal_is_a_crash() {
+        let exit = report(None, Some(9), false);
+        assert!(exit.is_crash());
+        assert_eq!(exit.describe(), "terminated by signal 9");
+    }
+
+    #[test]
+    fn requested_stop_is_not_a_crash() {
+        assert!(!report(None, Some(15), true).is_crash());
+        assert!(!report(Some(1), None, true).is_crash());
+    }
+
+    #[test]
+    fn unknown_status_is_not_a_crash() {
+        let exit = report(None, None, false);
+        assert!(exit.is_status_unknown());
+        assert!(!exit.is_crash());
+        assert_eq!(exit.describe(), "exited, status unknown");
+    }
+
+    #[test]
+    fn formats_uptime() {
+        assert_eq!(report(Some(0), None, false).format_uptime(), "1h 02m 05s");
+    }
+}
@@ -0,0 +1,297 @@
+use std::fmt;
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::reth_node::CliOption;
+
+/// A single launch parameter: a flag and the values passed with it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchArg {
+    pub flag: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub joined: bool, // Multiple values go in one comma-separated argument, as the option takes a delimited list
+}
+
+impl LaunchArg {
+    pub fn new(flag: impl Into<String>, values: Vec<String>) -> Self {
+        Self { flag: normalize_flag(&flag.into()), values, joined: false }
+    }
+
+    /// A parameter for a known option, remembering how the option takes multiple values
+    /// so it renders the same before the CLI schema has loaded
+    pub fn for_option(option: &CliOption, values: Vec<String>) -> Self {
+        Self { joined: option.accepts_multiple, ..Self::new(option.name.clone(), values) }
+    }
+
+    /// Replace the values, e.g. after editing, recording the join style if the option is known
+    pub fn set_values(&mut self, values: Vec<String>, option: Option<&CliOption>) {
+        self.values = values;
+        if let Some(option) = option {
+            self.joined = option.accepts_multiple;
+        }
+    }
+
+    /// A flag without a value, e.g. `--http`
+    pub fn flag(flag: impl Into<String>) -> Self {
+        Self::new(flag, Vec::new())
+    }
+
+    /// Render into argv elements. Multiple values are comma-joined for options that take
+    /// a delimited list, and otherwise passed by repeating the flag. Parameters saved before
+    /// the join style was recorded fall back to the option from the CLI schema.
+    pub fn to_argv(&self, option: Option<&CliOption>) -> Vec<String> {
+        if self.values.is_empty() {
+            return vec![self.flag.clone()];
+        }
+
+        if self.joined || option.is_some_and(|option| option.accepts_multiple) {
+            return vec![self.flag.clone(), self.values.join(",")];
+        }
+
+        self.values
+            .iter()
+            .flat_map(|value| [self.flag.clone(), value.clone()])
+            .collect()
+    }
+
+    /// Values as shown in an edit field
+    pub fn values_text(&self) -> Option<String> {
+        if self.values.is_empty() { None } else { Some(self.values.join(",")) }
+    }
+
+    /// Parse the text of an edit field back into values
+    pub fn parse_values(text: &str, option: Option<&CliOption>) -> Vec<String> {
+        if option.is_some_and(|option| option.accepts_multiple) {
+            text.split(',')
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect()
+        } else if text.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![text.trim().to_string()]
+        }
+    }
+}
+
+impl fmt::Display for LaunchArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.flag)?;
+        for value in &self.values {
+            write!(f, " {}", quote_arg(value))?;
+        }
+        Ok(())
+    }
+}
+
+/// Render launch parameters into argv, looking up each flag in the parsed CLI options
+pub fn render_argv(args: &[LaunchArg], options: &[CliOption]) -> Vec<String> {
+    args.iter()
+        .flat_map(|arg| arg.to_argv(options.iter().find(|option| option.name == arg.flag)))
+        .collect()
+}
+
+/// Quote an argument for display in a shell-like command line
+pub fn quote_arg(arg: &str) -> String {
+    let needs_quotes = arg.is_empty()
+        || arg.chars().any(|c| c.is_whitespace() || "'\"\\$`!*?&|;<>()[]{}#~".contains(c));
+    if needs_quotes {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Format an argv as a copyable command line
+pub fn format_command(argv: &[String]) -> String {
+    argv.iter().map(|arg| quote_arg(arg)).collect::<Vec<_>>().join(" ")
+}
+
+/// Collapse accidental extra leading dashes, e.g. "----http" from older versions
+fn normalize_flag(flag: &str) -> String {
+    let trimmed = flag.trim();
+    if trimmed.starts_with("---") {
+        format!("--{}", trimmed.trim_start_matches('-'))
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Convert the old string format, where an entry was either "--flag value", "--flag"
+/// or a bare value belonging to the preceding flag
+pub fn migrate_legacy(entries: &[String]) -> Vec<LaunchArg> {
+    let mut args: Vec<LaunchArg> = Vec::new();
+    for entry in entries {
+        push_legacy(&mut args, entry);
+    }
+    args
+}
+
+/// Add one old-format entry, attaching a bare value to the preceding parameter
+fn push_legacy(args: &mut Vec<LaunchArg>, entry: &str) {
+    let entry = entry.trim();
+    if entry.is_empty() {
+        return;
+    }
+
+    if entry.starts_with('-') {
+        let split = entry
+            .split_once(char::is_whitespace)
+            .or_else(|| entry.split_once('='));
+        match split {
+            Some((flag, value)) if !value.trim().is_empty() => {
+                args.push(LaunchArg::new(flag, vec![value.trim().to_string()]));
+            }
+            _ => args.push(LaunchArg::flag(entry)),
+        }
+    } else if let Some(last) = args.last_mut() {
+        last.values.push(entry.to_string());
+    } else {
+        args.push(LaunchArg::flag(entry));
+    }
+}
+
+/// Either the current structured format or a string from older settings files
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredLaunchArg {
+    Structured(LaunchArg),
+    Legacy(String),
+}
+
+/// Deserialize launch parameters, migrating entries saved in the old string format
+pub fn deserialize_launch_args<'de, D>(deserializer: D) -> Result<Vec<LaunchArg>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let stored = Vec::<StoredLaunchArg>::deserialize(deserializer)?;
+    let mut args = Vec::new();
+    for entry in stored {
+        match entry {
+            StoredLaunchArg::Structured(arg) => args.push(LaunchArg { flag: normalize_flag(&arg.flag), ..arg }),
+            // A bare value may belong to a structured entry saved just before it
+            StoredLaunchArg::Legacy(entry) => push_legacy(&mut args, &entry),
+        }
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|entry| entry.to_string()).collect()
+    }
+
+    fn option(name: &str, accepts_multiple: bool) -> CliOption {
+        CliOption { name: name.to_string(), takes_value: true, accepts_multiple, ..CliOption::default() }
+    }
+
+    #[derive(Deserialize)]
+    struct Stored {
+        #[serde(deserialize_with = "deserialize_launch_args")]
+        args: Vec<LaunchArg>,
+    }
+
+    #[derive(Serialize)]
+    struct Saved {
+        args: Vec<LaunchArg>,
+    }
+
+    fn load(toml: &str) -> Vec<LaunchArg> {
+        toml::from_str::<Stored>(toml).unwrap().args
+    }
+
+    #[test]
+    fn migrates_flag_and_value_strings() {
+        let args = migrate_legacy(&strings(&["--http.port 8545", "--http", "  --ws.port   8546 "]));
+        assert_eq!(args, vec![
+            LaunchArg::new("--http.port", strings(&["8545"])),
+            LaunchArg::flag("--http"),
+            LaunchArg::new("--ws.port", strings(&["8546"])),
+        ]);
+    }
+
+    #[test]
+    fn migrates_equals_values() {
+        let args = migrate_legacy(&strings(&["--metrics=127.0.0.1:9001", "--debug.tip="]));
+        assert_eq!(args, vec![
+            LaunchArg::new("--metrics", strings(&["127.0.0.1:9001"])),
+            LaunchArg::flag("--debug.tip="),
+        ]);
+    }
+
+    #[test]
+    fn migrates_separate_flag_and_value_entries() {
+        let args = migrate_legacy(&strings(&["--http.api", "eth", "net", "----trusted-only", ""]));
+        assert_eq!(args, vec![
+            LaunchArg::new("--http.api", strings(&["eth", "net"])),
+            LaunchArg::flag("--trusted-only"),
+        ]);
+    }
+
+    #[test]
+    fn loads_mixed_legacy_and_structured_entries() {
+        let args = load(r#"
+            args = [
+                "--http",
+                { flag = "--http.api", values = ["eth"] },
+                "net",
+                { flag = "---ws" },
+                "--ws.port 8546",
+            ]
+        "#);
+        assert_eq!(args, vec![
+            LaunchArg::flag("--http"),
+            LaunchArg::new("--http.api", strings(&["eth", "net"])),
+            LaunchArg::flag("--ws"),
+            LaunchArg::new("--ws.port", strings(&["8546"])),
+        ]);
+    }
+
+    #[test]
+    fn keeps_the_join_style_through_a_save() {
+        let api = option("--http.api", true);
+        let arg = LaunchArg::for_option(&api, strings(&["eth", "net"]));
+        let saved = toml::to_string(&Saved { args: vec![arg.clone()] }).unwrap();
+        assert_eq!(load(&saved), vec![arg]);
+    }
+
+    #[test]
+    fn renders_joined_and_repeated_values() {
+        let api = option("--http.api", true);
+        let peers = option("--trusted-peers", false);
+
+        let joined = LaunchArg::for_option(&api, strings(&["eth", "net"]));
+        assert_eq!(joined.to_argv(None), strings(&["--http.api", "eth,net"]));
+
+        let repeated = LaunchArg::for_option(&peers, strings(&["enode://a", "enode://b"]));
+        assert_eq!(repeated.to_argv(None), strings(&["--trusted-peers", "enode://a", "--trusted-peers", "enode://b"]));
+
+        // Saved before the join style was recorded: the schema decides
+        let legacy = LaunchArg::new("--http.api", strings(&["eth", "net"]));
+        assert_eq!(legacy.to_argv(None), strings(&["--http.api", "eth", "--http.api", "net"]));
+        assert_eq!(render_argv(&[legacy, LaunchArg::flag("--http")], &[api]), strings(&["--http.api", "eth,net", "--http"]));
+    }
+
+    #[test]
+    fn parses_edited_values() {
+        let api = option("--http.api", true);
+        assert_eq!(LaunchArg::parse_values(" eth, net ,,", Some(&api)), strings(&["eth", "net"]));
+        assert_eq!(LaunchArg::parse_values(" a,b ", None), strings(&["a,b"]));
+        assert!(LaunchArg::parse_values("  ", None).is_empty());
+    }
+
+    #[test]
+    fn quotes_arguments_for_display() {
+        assert_eq!(quote_arg("--http"), "--http");
+        assert_eq!(quote_arg(""), "''");
+        assert_eq!(quote_arg("my data"), "'my data'");
+        assert_eq!(quote_arg("it's"), "'it'\\''s'");
+
+        let argv = strings(&["reth", "node", "--datadir", "/mnt/my disk", "--http"]);
+        assert_eq!(format_command(&argv), "reth node --datadir '/mnt/my disk' --http");
+        assert_eq!(LaunchArg::new("--datadir", strings(&["/mnt/my disk"])).to_string(), "--datadir '/mnt/my disk'");
+    }
+}
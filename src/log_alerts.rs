@@ -0,0 +1,320 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::log_record::{LogLevel, LogRecord};
+
+/// Events kept in the timeline
+const MAX_EVENTS: usize = 500;
+
+/// Longest message shown for an event
+const MAX_MESSAGE_CHARS: usize = 240;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    pub const ALL: [AlertSeverity; 3] = [AlertSeverity::Info, AlertSeverity::Warning, AlertSeverity::Critical];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertSeverity::Info => "Info",
+            AlertSeverity::Warning => "Warning",
+            AlertSeverity::Critical => "Critical",
+        }
+    }
+}
+
+/// A condition on the log stream. A record matches when its level, target and text all match;
+/// the rule fires once `threshold` matches fall inside `window_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub levels: Vec<LogLevel>, // Empty matches every level
+    #[serde(default)]
+    pub target: String, // Substring of the record target; empty matches any
+    #[serde(default)]
+    pub pattern: String, // Regex over the message and fields; empty matches any
+    #[serde(default = "default_threshold")]
+    pub threshold: u32,
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    #[serde(default)]
+    pub cooldown_secs: u64, // Minimum time between two events of this rule
+    #[serde(default = "default_severity")]
+    pub severity: AlertSeverity,
+    #[serde(default = "default_true")]
+    pub notify: bool, // Raise an in-app alert; otherwise the event only goes to the timeline
+}
+
+fn default_true() -> bool { true }
+fn default_threshold() -> u32 { 1 }
+fn default_window_secs() -> u64 { 60 }
+fn default_severity() -> AlertSeverity { AlertSeverity::Warning }
+
+impl AlertRule {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            enabled: true,
+            levels: Vec::new(),
+            target: String::new(),
+            pattern: String::new(),
+            threshold: default_threshold(),
+            window_secs: default_window_secs(),
+            cooldown_secs: 0,
+            severity: default_severity(),
+            notify: true,
+        }
+    }
+
+    fn with_pattern(mut self, pattern: &str) -> Self {
+        self.pattern = pattern.to_string();
+        self
+    }
+
+    fn with_levels(mut self, levels: &[LogLevel]) -> Self {
+        self.levels = levels.to_vec();
+        self
+    }
+
+    fn with_rate(mut self, threshold: u32, window_secs: u64) -> Self {
+        self.threshold = threshold;
+        self.window_secs = window_secs;
+        self
+    }
+
+    fn with_cooldown(mut self, cooldown_secs: u64) -> Self {
+        self.cooldown_secs = cooldown_secs;
+        self
+    }
+
+    fn with_severity(mut self, severity: AlertSeverity, notify: bool) -> Self {
+        self.severity = severity;
+        self.notify = notify;
+        self
+    }
+
+    /// Short description of the condition, e.g. "WARN, 20 in 60s"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.levels.is_empty() {
+            parts.push(self.levels.iter().map(|level| level.as_str()).collect::<Vec<_>>().join("/"));
+        }
+        if !self.target.is_empty() {
+            parts.push(format!("target ~ {}", self.target));
+        }
+        if !self.pattern.is_empty() {
+            parts.push(format!("/{}/", self.pattern));
+        }
+        if self.threshold > 1 {
+            parts.push(format!("{} in {}s", self.threshold, self.window_secs));
+        }
+        if parts.is_empty() {
+            "every line".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Rules for common reth conditions, enabled unless switched off in the settings
+pub fn builtin_rules() -> Vec<AlertRule> {
+    vec![
+        AlertRule::new("Stage finished")
+            .with_pattern(r"(?i)^finished stage")
+            .with_severity(AlertSeverity::Info, false),
+        AlertRule::new("Forkchoice updated")
+            .with_pattern(r"(?i)forkchoice updated")
+            .with_cooldown(300)
+            .with_severity(AlertSeverity::Info, false),
+        AlertRule::new("Beacon client online")
+            .with_pattern(r"(?i)beacon client online")
+            .with_cooldown(300)
+            .with_severity(AlertSeverity::Info, false),
+        AlertRule::new("No beacon client")
+            .with_pattern(r"(?i)(never seen beacon client|beacon client offline|no beacon client)")
+            .with_cooldown(600)
+            .with_severity(AlertSeverity::Warning, true),
+        AlertRule::new("Peer banned")
+            .with_pattern(r"(?i)(banned peer|peer .*banned|banning peer)")
+            .with_cooldown(60)
+            .with_severity(AlertSeverity::Info, false),
+        AlertRule::new("Database error")
+            .with_levels(&[LogLevel::Error])
+            .with_pattern(r"(?i)(database|mdbx|static file|\bdb\b)")
+            .with_cooldown(60)
+            .with_severity(AlertSeverity::Critical, true),
+        AlertRule::new("Panic")
+            .with_pattern(r"(?i)panicked at")
+            .with_severity(AlertSeverity::Critical, true),
+        AlertRule::new("Error burst")
+            .with_levels(&[LogLevel::Error])
+            .with_rate(5, 60)
+            .with_cooldown(300)
+            .with_severity(AlertSeverity::Critical, true),
+        AlertRule::new("Warning burst")
+            .with_levels(&[LogLevel::Warn])
+            .with_rate(20, 60)
+            .with_cooldown(300)
+            .with_severity(AlertSeverity::Warning, true),
+    ]
+}
+
+/// Something a rule detected, kept in the timeline
+#[derive(Debug, Clone)]
+pub struct AlertEvent {
+    pub time: DateTime<Local>,
+    pub rule: String,
+    pub severity: AlertSeverity,
+    pub message: String,
+    pub notify: bool,
+}
+
+struct CompiledRule {
+    rule: AlertRule,
+    pattern: Option<Regex>,
+    hits: VecDeque<DateTime<Local>>, // Match times inside the current window
+    last_fired: Option<DateTime<Local>>,
+}
+
+impl CompiledRule {
+    fn matches(&self, record: &LogRecord, text: &str) -> bool {
+        (self.rule.levels.is_empty() || self.rule.levels.contains(&record.level))
+            && (self.rule.target.is_empty() || record.target.contains(&self.rule.target))
+            && self.pattern.as_ref().is_none_or(|pattern| pattern.is_match(text))
+    }
+
+    /// Record a match and return the event if the rule fires
+    fn hit(&mut self, record: &LogRecord, text: &str) -> Option<AlertEvent> {
+        let now = record.timestamp;
+        let window = Duration::seconds(self.rule.window_secs as i64);
+        self.hits.push_back(now);
+        while self.hits.front().is_some_and(|first| now - *first > window) {
+            self.hits.pop_front();
+        }
+        if self.hits.len() < self.rule.threshold.max(1) as usize {
+            return None;
+        }
+
+        let cooldown = Duration::seconds(self.rule.cooldown_secs as i64);
+        if self.last_fired.is_some_and(|last| now - last < cooldown) {
+            return None;
+        }
+
+        let count = self.hits.len();
+        self.hits.clear();
+        self.last_fired = Some(now);
+
+        let mut message: String = text.chars().take(MAX_MESSAGE_CHARS).collect();
+        if self.rule.threshold > 1 {
+            message = format!("{} matching lines in {}s, last: {}", count, self.rule.window_secs, message);
+        }
+        Some(AlertEvent {
+            time: now,
+            rule: self.rule.name.clone(),
+            severity: self.rule.severity,
+            message,
+            notify: self.rule.notify,
+        })
+    }
+}
+
+/// Runs alert rules over the log stream, keeping a timeline of events and the alerts not yet dismissed
+#[derive(Default)]
+pub struct AlertEngine {
+    rules: Vec<CompiledRule>,
+    errors: Vec<String>, // Rules skipped because their pattern doesn't compile
+    timeline: VecDeque<AlertEvent>,
+    active: Vec<AlertEvent>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        let mut engine = Self::default();
+        engine.set_rules(rules);
+        engine
+    }
+
+    /// Replace the rules, keeping the timeline and active alerts
+    pub fn set_rules(&mut self, rules: Vec<AlertRule>) {
+        self.errors.clear();
+        self.rules = rules
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| {
+                let pattern = if rule.pattern.is_empty() {
+                    None
+                } else {
+                    match Regex::new(&rule.pattern) {
+                        Ok(pattern) => Some(pattern),
+                        Err(e) => {
+                            self.errors.push(format!("{}: {}", rule.name, e));
+                            return None;
+                        }
+                    }
+                };
+                Some(CompiledRule { rule, pattern, hits: VecDeque::new(), last_fired: None })
+            })
+            .collect();
+    }
+
+    /// Run every rule over new records
+    pub fn process(&mut self, records: &[Arc<LogRecord>]) {
+        for record in records {
+            let text = record.text();
+            for rule in &mut self.rules {
+                if !rule.matches(record, &text) {
+                    continue;
+                }
+                if let Some(event) = rule.hit(record, &text) {
+                    println!("Alert [{}] {}: {}", event.severity.as_str(), event.rule, event.message);
+                    if event.notify {
+                        // One alert per rule; a repeat replaces the older one
+                        self.active.retain(|active| active.rule != event.rule);
+                        self.active.push(event.clone());
+                    }
+                    self.timeline.push_back(event);
+                    if self.timeline.len() > MAX_EVENTS {
+                        self.timeline.pop_front();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn timeline(&self) -> &VecDeque<AlertEvent> {
+        &self.timeline
+    }
+
+    pub fn active(&self) -> &[AlertEvent] {
+        &self.active
+    }
+
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    pub fn dismiss(&mut self, index: usize) {
+        if index < self.active.len() {
+            self.active.remove(index);
+        }
+    }
+
+    pub fn dismiss_all(&mut self) {
+        self.active.clear();
+    }
+
+    pub fn clear_timeline(&mut self) {
+        self.timeline.clear();
+    }
+}
+
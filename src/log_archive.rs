@@ -0,0 +1,368 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use crate::log_record::{LogLevel, LogRecord};
+
+/// Uncompressed size at which the active segment is compressed and a new one started
+const SEGMENT_MAX_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Oldest sessions are removed once either limit is exceeded
+const MAX_SESSIONS: usize = 100;
+const MAX_ARCHIVE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// How often the index is refreshed while a session is being written
+const INDEX_UPDATE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Serializes index read-modify-write cycles between sessions
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Summary of one node run, stored in the archive index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub started_at: DateTime<Local>,
+    pub ended_at: Option<DateTime<Local>>, // None while running, or if the desktop exited uncleanly
+    pub launch_command: Vec<String>,
+    pub lines: u64,
+    pub errors: u64,
+    pub warnings: u64,
+    pub bytes: u64, // Size on disk
+}
+
+impl SessionInfo {
+    /// One-line description for session pickers
+    pub fn label(&self) -> String {
+        let end = match self.ended_at {
+            Some(ended_at) => ended_at.format("%H:%M").to_string(),
+            None => "?".to_string(),
+        };
+        format!(
+            "{} - {} ({} lines, {} errors)",
+            self.started_at.format("%Y-%m-%d %H:%M"),
+            end,
+            self.lines,
+            self.errors
+        )
+    }
+}
+
+/// On-disk history of node output under ~/.reth-desktop/logs/, one directory of
+/// JSON lines segments per session
+pub struct LogArchive;
+
+impl LogArchive {
+    pub fn dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".reth-desktop")
+            .join("logs")
+    }
+
+    fn index_path() -> PathBuf {
+        Self::dir().join("index.json")
+    }
+
+    pub fn session_dir(id: &str) -> PathBuf {
+        Self::dir().join(id)
+    }
+
+    /// All archived sessions, newest first
+    pub fn load_index() -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = std::fs::read_to_string(Self::index_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.started_at));
+        sessions
+    }
+
+    fn save_index(sessions: &[SessionInfo]) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(Self::dir())?;
+        // Write to a temporary file first so a crash can't leave a truncated index
+        let tmp = Self::index_path().with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(sessions)?)?;
+        std::fs::rename(&tmp, Self::index_path())?;
+        Ok(())
+    }
+
+    /// Insert or replace a session in the index
+    fn update_index(info: &SessionInfo) {
+        let _guard = INDEX_LOCK.lock().unwrap();
+        let mut sessions = Self::load_index();
+        match sessions.iter_mut().find(|session| session.id == info.id) {
+            Some(session) => *session = info.clone(),
+            None => sessions.push(info.clone()),
+        }
+        if let Err(e) = Self::save_index(&sessions) {
+            eprintln!("Failed to update log archive index: {}", e);
+        }
+    }
+
+    /// Remove the oldest finished sessions beyond the count and size limits
+    fn prune() {
+        let _guard = INDEX_LOCK.lock().unwrap();
+        let mut sessions = Self::load_index();
+        let mut total: u64 = sessions.iter().map(|session| session.bytes).sum();
+        let mut removed = false;
+
+        while sessions.len() > MAX_SESSIONS || total > MAX_ARCHIVE_BYTES {
+            // Newest first, so the last finished session is the oldest
+            let Some(pos) = sessions.iter().rposition(|session| session.ended_at.is_some()) else {
+                break;
+            };
+            let session = sessions.remove(pos);
+            total = total.saturating_sub(session.bytes);
+            if let Err(e) = std::fs::remove_dir_all(Self::session_dir(&session.id)) {
+                eprintln!("Failed to remove archived session {}: {}", session.id, e);
+            }
+            println!("Removed archived log session {}", session.id);
+            removed = true;
+        }
+
+        if removed {
+            if let Err(e) = Self::save_index(&sessions) {
+                eprintln!("Failed to update log archive index: {}", e);
+            }
+        }
+    }
+
+    /// Segment files of a session in write order
+    fn segments(id: &str) -> Vec<PathBuf> {
+        let mut segments: Vec<PathBuf> = std::fs::read_dir(Self::session_dir(id))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                        name.starts_with("segment-") && (name.ends_with(".jsonl") || name.ends_with(".jsonl.gz"))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        segments.sort();
+        segments
+    }
+
+    /// Read the records of an archived session, keeping at most the newest `max_lines`.
+    /// Returns the records and the number of older records that were skipped.
+    pub fn read_session(id: &str, max_lines: usize) -> Result<(Vec<LogRecord>, u64), Box<dyn std::error::Error>> {
+        let mut records = std::collections::VecDeque::new();
+        let mut skipped = 0u64;
+
+        for segment in Self::segments(id) {
+            let file = File::open(&segment)?;
+            let reader: Box<dyn Read> = if segment.extension().is_some_and(|ext| ext == "gz") {
+                Box::new(GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    // A segment cut short by a crash ends with a partial line
+                    break;
+                };
+                if let Ok(record) = serde_json::from_str::<LogRecord>(&line) {
+                    records.push_back(record);
+                    if records.len() > max_lines {
+                        records.pop_front();
+                        skipped += 1;
+                    }
+                }
+            }
+        }
+
+        Ok((records.into(), skipped))
+    }
+}
+
+enum ArchiveCommand {
+    Records(Vec<Arc<LogRecord>>),
+    Finish,
+}
+
+/// The session currently being written; records are written on a background thread
+pub struct ArchiveSession {
+    sender: mpsc::Sender<ArchiveCommand>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ArchiveSession {
+    /// Start a new session for a node run
+    pub fn start(launch_command: Vec<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let started_at = Local::now();
+        let base_id = started_at.format("%Y%m%d-%H%M%S").to_string();
+        let mut id = base_id.clone();
+        let mut suffix = 2;
+        while LogArchive::session_dir(&id).exists() {
+            id = format!("{}-{}", base_id, suffix);
+            suffix += 1;
+        }
+        std::fs::create_dir_all(LogArchive::session_dir(&id))?;
+
+        let info = SessionInfo {
+            id: id.clone(),
+            started_at,
+            ended_at: None,
+            launch_command,
+            lines: 0,
+            errors: 0,
+            warnings: 0,
+            bytes: 0,
+        };
+        LogArchive::update_index(&info);
+
+        let mut writer = SessionWriter::new(info)?;
+        let (sender, receiver) = mpsc::channel::<ArchiveCommand>();
+        let handle = std::thread::spawn(move || {
+            while let Ok(command) = receiver.recv() {
+                match command {
+                    ArchiveCommand::Records(records) => {
+                        if let Err(e) = writer.write(&records) {
+                            eprintln!("Failed to write log archive: {}", e);
+                        }
+                    }
+                    ArchiveCommand::Finish => break,
+                }
+            }
+            // Also reached when the app drops the session without finishing it
+            writer.finish();
+            LogArchive::prune();
+        });
+
+        println!("Started log archive session {}", id);
+        Ok(Self { sender, handle: Some(handle) })
+    }
+
+    pub fn write(&self, records: &[Arc<LogRecord>]) {
+        if !records.is_empty() {
+            let _ = self.sender.send(ArchiveCommand::Records(records.to_vec()));
+        }
+    }
+
+    /// End the session; compression of the last segment finishes in the background
+    pub fn finish(mut self) {
+        let _ = self.sender.send(ArchiveCommand::Finish);
+        self.handle.take();
+    }
+}
+
+impl Drop for ArchiveSession {
+    fn drop(&mut self) {
+        // Dropped without finish(), e.g. on app exit: wait so the last segment is compressed
+        if let Some(handle) = self.handle.take() {
+            let _ = self.sender.send(ArchiveCommand::Finish);
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Writes JSON lines into numbered segments and keeps the index entry current
+struct SessionWriter {
+    info: SessionInfo,
+    segment_number: u32,
+    segment_path: PathBuf,
+    segment: BufWriter<File>,
+    segment_bytes: u64,
+    finished_bytes: u64, // Compressed size of earlier segments
+    last_index_update: Instant,
+}
+
+impl SessionWriter {
+    fn new(info: SessionInfo) -> Result<Self, Box<dyn std::error::Error>> {
+        let segment_path = Self::segment_path(&info.id, 1);
+        let segment = BufWriter::new(File::create(&segment_path)?);
+        Ok(Self {
+            info,
+            segment_number: 1,
+            segment_path,
+            segment,
+            segment_bytes: 0,
+            finished_bytes: 0,
+            last_index_update: Instant::now(),
+        })
+    }
+
+    fn segment_path(id: &str, number: u32) -> PathBuf {
+        LogArchive::session_dir(id).join(format!("segment-{:04}.jsonl", number))
+    }
+
+    fn write(&mut self, records: &[Arc<LogRecord>]) -> Result<(), Box<dyn std::error::Error>> {
+        for record in records {
+            let line = serde_json::to_string(&**record)?;
+            self.segment.write_all(line.as_bytes())?;
+            self.segment.write_all(b"\n")?;
+            self.segment_bytes += line.len() as u64 + 1;
+
+            self.info.lines += 1;
+            match record.level {
+                LogLevel::Error => self.info.errors += 1,
+                LogLevel::Warn => self.info.warnings += 1,
+                _ => {}
+            }
+
+            if self.segment_bytes >= SEGMENT_MAX_BYTES {
+                self.rotate()?;
+            }
+        }
+        self.segment.flush()?;
+
+        if self.last_index_update.elapsed() >= INDEX_UPDATE_INTERVAL {
+            self.info.bytes = self.finished_bytes + self.segment_bytes;
+            LogArchive::update_index(&self.info);
+            self.last_index_update = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Compress the active segment and continue in a new one
+    fn rotate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.segment.flush()?;
+        self.finished_bytes += compress_segment(&self.segment_path)?;
+        self.segment_number += 1;
+        self.segment_path = Self::segment_path(&self.info.id, self.segment_number);
+        self.segment = BufWriter::new(File::create(&self.segment_path)?);
+        self.segment_bytes = 0;
+        self.info.bytes = self.finished_bytes;
+        LogArchive::update_index(&self.info);
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        let _ = self.segment.flush();
+        match compress_segment(&self.segment_path) {
+            Ok(bytes) => self.finished_bytes += bytes,
+            Err(e) => {
+                eprintln!("Failed to compress log segment {}: {}", self.segment_path.display(), e);
+                self.finished_bytes += self.segment_bytes;
+            }
+        }
+        self.info.bytes = self.finished_bytes;
+        self.info.ended_at = Some(Local::now());
+        LogArchive::update_index(&self.info);
+        println!("Finished log archive session {} ({} lines)", self.info.id, self.info.lines);
+    }
+}
+
+/// Gzip a segment next to itself and remove the original. Returns the compressed size.
+fn compress_segment(path: &Path) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(&gz_path)?), Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.flush()?;
+    std::fs::remove_file(path)?;
+    Ok(std::fs::metadata(&gz_path)?.len())
+}
@@ -0,0 +1,76 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::borrow::Borrow;
+use crate::log_record::LogRecord;
+
+/// File formats a log export can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Text,
+    JsonLines,
+    Gzip, // JSON lines, gzip compressed like the archive segments
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Text, ExportFormat::JsonLines, ExportFormat::Gzip];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Text => "Plain text",
+            ExportFormat::JsonLines => "JSON lines",
+            ExportFormat::Gzip => "JSON lines (gzip)",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Text => "log",
+            ExportFormat::JsonLines => "jsonl",
+            ExportFormat::Gzip => "jsonl.gz",
+        }
+    }
+}
+
+/// Directory exports are written to: downloads, or home as a fallback
+pub fn export_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or("No export directory available")?)
+}
+
+/// Write records to `<export dir>/<name>.<extension>` and return the path
+pub fn export_records<R: Borrow<LogRecord>>(records: &[R], name: &str, format: ExportFormat) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = export_dir()?.join(format!("{}.{}", name, format.extension()));
+    let file = BufWriter::new(File::create(&path)?);
+
+    match format {
+        ExportFormat::Text => write_records(file, records, false)?,
+        ExportFormat::JsonLines => write_records(file, records, true)?,
+        ExportFormat::Gzip => {
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            write_records(&mut encoder, records, true)?;
+            encoder.finish()?.flush()?;
+        }
+    }
+
+    println!("Exported {} log lines to: {}", records.len(), path.display());
+    Ok(path)
+}
+
+/// One record per line, either as displayed text or as a JSON object
+pub fn write_records<W: Write, R: Borrow<LogRecord>>(mut writer: W, records: &[R], json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    for record in records {
+        let record = record.borrow();
+        if json {
+            serde_json::to_writer(&mut writer, record)?;
+            writer.write_all(b"\n")?;
+        } else {
+            writeln!(writer, "{}", record)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use chrono::{DateTime, Local, TimeZone};
+use crate::log_record::{LogLevel, LogRecord};
+
+/// Seconds of history kept for the rate plot
+pub const RATE_WINDOW_SECS: i64 = 600;
+
+/// Line counts for one second of log output
+#[derive(Debug, Clone, Default)]
+struct RateBucket {
+    second: i64, // Unix timestamp
+    by_level: HashMap<LogLevel, u32>,
+    by_target: HashMap<String, u32>,
+}
+
+/// Log lines per second over the last few minutes, split by level and by target
+#[derive(Debug, Clone, Default)]
+pub struct LogRateTracker {
+    buckets: VecDeque<RateBucket>, // One per second that had output, oldest first
+}
+
+impl LogRateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Count new records into their second, by the time reth logged them
+    pub fn record(&mut self, records: &[Arc<LogRecord>]) {
+        let now = Local::now().timestamp();
+        for record in records {
+            let second = record.timestamp.timestamp();
+            if second <= now - RATE_WINDOW_SECS {
+                continue;
+            }
+
+            // Records arrive mostly in order; search from the back for the bucket
+            let position = self.buckets.iter().rposition(|bucket| bucket.second <= second);
+            let index = match position {
+                Some(index) if self.buckets[index].second == second => index,
+                Some(index) => {
+                    self.buckets.insert(index + 1, RateBucket { second, ..Default::default() });
+                    index + 1
+                }
+                None => {
+                    self.buckets.push_front(RateBucket { second, ..Default::default() });
+                    0
+                }
+            };
+            let bucket = &mut self.buckets[index];
+            *bucket.by_level.entry(record.level).or_default() += 1;
+            let target = if record.target.is_empty() { "(none)" } else { record.target.as_str() };
+            *bucket.by_target.entry(target.to_string()).or_default() += 1;
+        }
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: i64) {
+        while self.buckets.front().is_some_and(|bucket| bucket.second <= now - RATE_WINDOW_SECS) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Points of (seconds relative to now, lines per second), with zeros for quiet seconds
+    fn series(&self, count: impl Fn(&RateBucket) -> u32) -> Vec<[f64; 2]> {
+        let now = Local::now().timestamp();
+        let mut counts = vec![0u32; RATE_WINDOW_SECS as usize];
+        for bucket in &self.buckets {
+            let age = now - bucket.second;
+            if (0..RATE_WINDOW_SECS).contains(&age) {
+                counts[(RATE_WINDOW_SECS - 1 - age) as usize] += count(bucket);
+            }
+        }
+        counts
+            .iter()
+            .enumerate()
+            .map(|(index, count)| [index as f64 - (RATE_WINDOW_SECS - 1) as f64, *count as f64])
+            .collect()
+    }
+
+    pub fn level_series(&self, level: LogLevel) -> Vec<[f64; 2]> {
+        self.series(|bucket| bucket.by_level.get(&level).copied().unwrap_or(0))
+    }
+
+    pub fn target_series(&self, target: &str) -> Vec<[f64; 2]> {
+        self.series(|bucket| bucket.by_target.get(target).copied().unwrap_or(0))
+    }
+
+    /// Total lines per level across the window
+    pub fn level_totals(&self) -> HashMap<LogLevel, u32> {
+        let mut totals = HashMap::new();
+        for bucket in &self.buckets {
+            for (level, count) in &bucket.by_level {
+                *totals.entry(*level).or_default() += count;
+            }
+        }
+        totals
+    }
+
+    /// The busiest targets across the window, most lines first
+    pub fn top_targets(&self, limit: usize) -> Vec<(String, u32)> {
+        let mut totals: HashMap<&str, u32> = HashMap::new();
+        for bucket in &self.buckets {
+            for (target, count) in &bucket.by_target {
+                *totals.entry(target.as_str()).or_default() += count;
+            }
+        }
+        let mut targets: Vec<(String, u32)> = totals
+            .into_iter()
+            .map(|(target, count)| (target.to_string(), count))
+            .collect();
+        targets.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        targets.truncate(limit);
+        targets
+    }
+}
+
+/// Wall-clock time of a plot x value (seconds relative to now)
+pub fn time_at_offset(offset_secs: f64) -> DateTime<Local> {
+    let second = Local::now().timestamp() + offset_secs.round() as i64;
+    Local.timestamp_opt(second, 0).single().unwrap_or_else(Local::now)
+}
@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// Parse a tracing level name such as "INFO" or "warn"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_uppercase().as_str() {
+            "TRACE" => Some(LogLevel::Trace),
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "ERROR" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+/// A single log event from reth, or a status line generated by the desktop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub timestamp: DateTime<Local>,
+    pub level: LogLevel,
+    pub target: String, // Rust module path, e.g. reth_node_events::node; empty when unknown
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Target used for lines generated by the desktop itself
+pub const DESKTOP_TARGET: &str = "reth_desktop";
+
+impl LogRecord {
+    /// A status line generated by the desktop, e.g. "Reth shut down gracefully"
+    pub fn status(message: String, level: LogLevel) -> Self {
+        Self {
+            timestamp: Local::now(),
+            level,
+            target: DESKTOP_TARGET.to_string(),
+            message,
+            fields: BTreeMap::new(),
+        }
+    }
+
+    /// Parse one line of reth output. JSON records (`--log.stdout.format json`) are read
+    /// field by field; terminal-format and ANSI-colored lines go through a text parser.
+    /// Lines without a recognizable level (panics, clap errors) get `fallback_level`.
+    pub fn parse(line: &str, fallback_level: LogLevel) -> Self {
+        let line = strip_ansi(line);
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('{') {
+            if let Some(record) = Self::parse_json(trimmed) {
+                return record;
+            }
+        }
+
+        Self::parse_terminal(trimmed).unwrap_or_else(|| Self {
+            timestamp: Local::now(),
+            level: fallback_level,
+            target: String::new(),
+            message: trimmed.to_string(),
+            fields: BTreeMap::new(),
+        })
+    }
+
+    /// Parse a tracing-subscriber JSON record:
+    /// {"timestamp":"…","level":"INFO","fields":{"message":"Status","connected_peers":4},"target":"reth::cli"}
+    fn parse_json(line: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(line).ok()?;
+        let object = value.as_object()?;
+        let level = object.get("level").and_then(|level| level.as_str()).and_then(LogLevel::parse)?;
+
+        let timestamp = object
+            .get("timestamp")
+            .and_then(|timestamp| timestamp.as_str())
+            .and_then(parse_timestamp)
+            .unwrap_or_else(Local::now);
+        let target = object
+            .get("target")
+            .and_then(|target| target.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        // Fields are nested under "fields" unless the subscriber flattens them into the record
+        let mut fields = BTreeMap::new();
+        let mut message = String::new();
+        let field_values: Vec<(&String, &serde_json::Value)> = match object.get("fields").and_then(|fields| fields.as_object()) {
+            Some(nested) => nested.iter().collect(),
+            None => object
+                .iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "timestamp" | "level" | "target" | "span" | "spans"))
+                .collect(),
+        };
+        for (key, value) in field_values {
+            let text = match value {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            if key == "message" {
+                message = text;
+            } else {
+                fields.insert(key.clone(), text);
+            }
+        }
+
+        Some(Self { timestamp, level, target, message, fields })
+    }
+
+    /// Parse a terminal-format line: `2025-07-03T19:20:27.151425Z  INFO reth::cli: Status connected_peers=4`.
+    /// The timestamp and target are optional; the level is required.
+    fn parse_terminal(line: &str) -> Option<Self> {
+        let mut rest = line;
+        let mut timestamp = None;
+
+        let (first, after_first) = split_token(rest);
+        if first.starts_with(|c: char| c.is_ascii_digit()) {
+            timestamp = Some(parse_timestamp(first)?);
+            rest = after_first;
+        }
+
+        let (level, after_level) = split_token(rest);
+        let level = LogLevel::parse(level).filter(|_| level.chars().all(|c| c.is_ascii_uppercase()))?;
+        rest = after_level;
+
+        let mut target = String::new();
+        let (candidate, after_target) = split_token(rest);
+        if is_target(candidate) {
+            target = candidate.trim_end_matches(':').to_string();
+            rest = after_target;
+        }
+
+        let (message, fields) = split_fields(rest);
+        Some(Self {
+            timestamp: timestamp.unwrap_or_else(Local::now),
+            level,
+            target,
+            message,
+            fields,
+        })
+    }
+
+    /// Message followed by its fields, e.g. "Status connected_peers=4 latest_block=100"
+    pub fn text(&self) -> String {
+        let mut text = self.message.clone();
+        for (key, value) in &self.fields {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            if value.contains(char::is_whitespace) {
+                text.push_str(&format!("{}=\"{}\"", key, value));
+            } else {
+                text.push_str(&format!("{}={}", key, value));
+            }
+        }
+        text
+    }
+
+    /// Local time of day, as shown next to each line
+    pub fn time_text(&self) -> String {
+        self.timestamp.format("%H:%M:%S").to_string()
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:5}", self.timestamp.format("%Y-%m-%d %H:%M:%S%.3f"), self.level.as_str())?;
+        if !self.target.is_empty() {
+            write!(f, " {}:", self.target)?;
+        }
+        write!(f, " {}", self.text())
+    }
+}
+
+/// Remove ANSI escape sequences such as color codes
+pub fn strip_ansi(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            if chars.peek() == Some(&'[') {
+                chars.next();
+                // CSI sequences end with a byte in the range @ to ~
+                for next in chars.by_ref() {
+                    if ('@'..='~').contains(&next) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        cleaned.push(ch);
+    }
+    cleaned
+}
+
+/// RFC 3339 timestamps, or naive ones which tracing writes in UTC
+fn parse_timestamp(text: &str) -> Option<DateTime<Local>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+        return Some(timestamp.with_timezone(&Local));
+    }
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc).with_timezone(&Local))
+}
+
+/// Split off the first whitespace separated token
+fn split_token(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.find(char::is_whitespace) {
+        Some(pos) => (&text[..pos], text[pos..].trim_start()),
+        None => (text, ""),
+    }
+}
+
+/// Module paths are printed as `reth_node_events::node:` before the message
+fn is_target(token: &str) -> bool {
+    let Some(path) = token.strip_suffix(':') else {
+        return false;
+    };
+    !path.is_empty()
+        && (path.contains("::") || path.contains('_') || path == "reth")
+        && path.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+/// Split trailing `key=value` pairs off a message. Quoted values may contain spaces.
+fn split_fields(text: &str) -> (String, BTreeMap<String, String>) {
+    let mut message_tokens: Vec<String> = Vec::new();
+    let mut fields: BTreeMap<String, String> = BTreeMap::new();
+    let mut last_key: Option<String> = None;
+
+    for token in quoted_tokens(text) {
+        match token.split_once('=') {
+            Some((key, value)) if is_field_key(key) => {
+                fields.insert(key.to_string(), value.trim_matches('"').to_string());
+                last_key = Some(key.to_string());
+            }
+            _ => match &last_key {
+                // Unquoted values with spaces, e.g. elapsed=1.2s (estimated)
+                Some(key) => {
+                    if let Some(value) = fields.get_mut(key) {
+                        value.push(' ');
+                        value.push_str(&token);
+                    }
+                }
+                None => message_tokens.push(token),
+            },
+        }
+    }
+
+    (message_tokens.join(" "), fields)
+}
+
+fn is_field_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Whitespace separated tokens, keeping double-quoted sections together
+fn quoted_tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for ch in text.chars() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(ch);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn parses_json_with_nested_fields() {
+        let line = r#"{"timestamp":"2025-07-03T19:20:27.151425Z","level":"INFO","fields":{"message":"Status","connected_peers":4,"latest_block":"100"},"target":"reth::cli"}"#;
+        let record = LogRecord::parse(line, LogLevel::Error);
+        assert_eq!(record.level, LogLevel::Info);
+        assert_eq!(record.target, "reth::cli");
+        assert_eq!(record.message, "Status");
+        assert_eq!(record.fields, fields(&[("connected_peers", "4"), ("latest_block", "100")]));
+        assert_eq!(record.timestamp.with_timezone(&Utc).to_rfc3339(), "2025-07-03T19:20:27.151425+00:00");
+    }
+
+    #[test]
+    fn parses_json_with_flattened_fields() {
+        let line = r#"{"timestamp":"2025-07-03T19:20:27Z","level":"WARN","message":"Slow peer","peer_id":"0xab","span":{"name":"p2p"},"target":"reth_network"}"#;
+        let record = LogRecord::parse(line, LogLevel::Info);
+        assert_eq!(record.level, LogLevel::Warn);
+        assert_eq!(record.target, "reth_network");
+        assert_eq!(record.message, "Slow peer");
+        assert_eq!(record.fields, fields(&[("peer_id", "0xab")]));
+    }
+
+    #[test]
+    fn parses_terminal_lines_with_a_target() {
+        let line = r#"2025-07-03T19:20:27.151425Z  INFO reth_node_events::node: Received headers count=512 stage="Headers (1/12)" elapsed=1.2s (estimated)"#;
+        let record = LogRecord::parse(line, LogLevel::Error);
+        assert_eq!(record.level, LogLevel::Info);
+        assert_eq!(record.target, "reth_node_events::node");
+        assert_eq!(record.message, "Received headers");
+        assert_eq!(record.fields, fields(&[("count", "512"), ("stage", "Headers (1/12)"), ("elapsed", "1.2s (estimated)")]));
+    }
+
+    #[test]
+    fn parses_terminal_lines_without_a_target() {
+        let record = LogRecord::parse("ERROR Database error: out of space", LogLevel::Info);
+        assert_eq!(record.level, LogLevel::Error);
+        assert_eq!(record.target, "");
+        assert_eq!(record.message, "Database error: out of space");
+        assert!(record.fields.is_empty());
+    }
+
+    #[test]
+    fn strips_ansi_colors() {
+        let line = "\x1b[2m2025-07-03T19:20:27.151425Z\x1b[0m \x1b[32m INFO\x1b[0m \x1b[2mreth::cli\x1b[0m\x1b[2m:\x1b[0m Status \x1b[3mconnected_peers\x1b[0m\x1b[2m=\x1b[0m4";
+        let record = LogRecord::parse(line, LogLevel::Error);
+        assert_eq!(record.level, LogLevel::Info);
+        assert_eq!(record.target, "reth::cli");
+        assert_eq!(record.message, "Status");
+        assert_eq!(record.fields, fields(&[("connected_peers", "4")]));
+    }
+
+    #[test]
+    fn uses_the_fallback_level_for_unleveled_lines() {
+        // A stderr panic line carries no level and must not be promoted to an error
+        let record = LogRecord::parse("thread 'main' panicked at src/main.rs:10:5: received headers", LogLevel::Warn);
+        assert_eq!(record.level, LogLevel::Warn);
+        assert_eq!(record.target, "");
+        assert_eq!(record.message, "thread 'main' panicked at src/main.rs:10:5: received headers");
+
+        // Lowercase words that look like levels are message text
+        assert_eq!(LogRecord::parse("error: unexpected argument '--htp'", LogLevel::Info).level, LogLevel::Info);
+        // Invalid JSON falls through to the text parser
+        assert_eq!(LogRecord::parse("{not json", LogLevel::Debug).level, LogLevel::Debug);
+    }
+}
@@ -0,0 +1,402 @@
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use crate::log_record::{LogLevel, LogRecord};
+
+/// Lines waiting to be parsed before producers start dropping them
+const INGEST_QUEUE_LINES: usize = 10_000;
+
+struct Slot {
+    seq: u64,
+    record: Arc<LogRecord>,
+}
+
+/// Lines read from the ring, in sequence order starting at `first_seq`
+pub struct LogSlice {
+    pub first_seq: u64,
+    pub records: Vec<Arc<LogRecord>>,
+    pub missed: u64, // Lines overwritten before the reader got to them
+}
+
+impl LogSlice {
+    /// Sequence number to read from next
+    pub fn next_seq(&self) -> u64 {
+        self.first_seq + self.records.len() as u64
+    }
+}
+
+/// Fixed-size ring of log records numbered by sequence. A single writer publishes records
+/// without blocking readers; readers copy out `Arc`s for any sequence range still in the ring.
+///
+/// Slots hold pointers that the writer swaps. A replaced slot is only freed once the writer
+/// has seen no reader active, so a reader never touches freed memory.
+pub struct LogRing {
+    slots: Box<[AtomicPtr<Slot>]>,
+    head: AtomicU64, // Sequence number of the next record to be written
+    readers: AtomicUsize,
+    dropped: AtomicU64, // Lines producers threw away because ingestion fell behind
+}
+
+impl LogRing {
+    /// Create a ring and its only writer
+    pub fn new(capacity: usize) -> (Arc<Self>, RingWriter) {
+        let slots = (0..capacity.max(1)).map(|_| AtomicPtr::new(ptr::null_mut())).collect();
+        let ring = Arc::new(Self {
+            slots,
+            head: AtomicU64::new(0),
+            readers: AtomicUsize::new(0),
+            dropped: AtomicU64::new(0),
+        });
+        let writer = RingWriter { ring: ring.clone(), retired: Vec::new() };
+        (ring, writer)
+    }
+
+    /// Sequence number the next record will get; everything below it has been written
+    pub fn head(&self) -> u64 {
+        self.head.load(Ordering::Acquire)
+    }
+
+    /// Lines dropped before they reached the ring
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn note_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Read up to `max` records starting at sequence `from`
+    pub fn read(&self, from: u64, max: usize) -> LogSlice {
+        let head = self.head();
+        let oldest = head.saturating_sub(self.slots.len() as u64);
+        let first_seq = from.max(oldest).min(head);
+        let end = head.min(first_seq + max as u64);
+
+        let _guard = ReadGuard::new(self);
+        let mut records = Vec::with_capacity((end - first_seq) as usize);
+        let mut next = first_seq;
+        for seq in first_seq..end {
+            let slot = self.slots[(seq % self.slots.len() as u64) as usize].load(Ordering::SeqCst);
+            // SAFETY: the guard keeps the writer from freeing slots replaced while we read
+            let Some(slot) = (unsafe { slot.as_ref() }) else {
+                break;
+            };
+            if slot.seq != seq {
+                // Overwritten since `head` was read: the reader fell a whole ring behind
+                if slot.seq > seq && records.is_empty() {
+                    next = seq + 1;
+                    continue;
+                }
+                break;
+            }
+            records.push(slot.record.clone());
+        }
+
+        LogSlice { first_seq: next, records, missed: next.saturating_sub(from) }
+    }
+
+    /// The newest `count` records
+    pub fn tail(&self, count: usize) -> Vec<Arc<LogRecord>> {
+        let from = self.head().saturating_sub(count as u64);
+        self.read(from, count).records
+    }
+}
+
+impl Drop for LogRing {
+    fn drop(&mut self) {
+        for slot in self.slots.iter() {
+            let slot = slot.swap(ptr::null_mut(), Ordering::SeqCst);
+            if !slot.is_null() {
+                // SAFETY: no readers or writer remain once the ring itself is dropped
+                drop(unsafe { Box::from_raw(slot) });
+            }
+        }
+    }
+}
+
+/// Marks a reader as active for as long as it exists
+struct ReadGuard<'a>(&'a LogRing);
+
+impl<'a> ReadGuard<'a> {
+    fn new(ring: &'a LogRing) -> Self {
+        ring.readers.fetch_add(1, Ordering::SeqCst);
+        Self(ring)
+    }
+}
+
+impl Drop for ReadGuard<'_> {
+    fn drop(&mut self) {
+        self.0.readers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// The single writer of a `LogRing`
+pub struct RingWriter {
+    ring: Arc<LogRing>,
+    retired: Vec<*mut Slot>, // Replaced slots waiting for a moment without readers
+}
+
+// SAFETY: the retired pointers are owned by the writer and only freed by it
+unsafe impl Send for RingWriter {}
+
+impl RingWriter {
+    pub fn push(&mut self, record: LogRecord) {
+        let seq = self.ring.head.load(Ordering::Relaxed);
+        let slot = Box::into_raw(Box::new(Slot { seq, record: Arc::new(record) }));
+        let index = (seq % self.ring.slots.len() as u64) as usize;
+        let old = self.ring.slots[index].swap(slot, Ordering::SeqCst);
+        self.ring.head.store(seq + 1, Ordering::Release);
+        if !old.is_null() {
+            self.retired.push(old);
+        }
+    }
+
+    /// Free replaced slots if no reader could still be looking at them
+    pub fn reclaim(&mut self) {
+        if !self.retired.is_empty() && self.ring.readers.load(Ordering::SeqCst) == 0 {
+            for slot in self.retired.drain(..) {
+                // SAFETY: swapped out of the ring before a moment with no active readers
+                drop(unsafe { Box::from_raw(slot) });
+            }
+        }
+    }
+}
+
+impl Drop for RingWriter {
+    fn drop(&mut self) {
+        while !self.retired.is_empty() {
+            self.reclaim();
+            std::thread::yield_now();
+        }
+    }
+}
+
+enum IngestLine {
+    Raw(String, LogLevel), // Unparsed output and the level to use if it has none
+    Record(LogRecord),
+    Flush(SyncSender<u64>), // Reply with the ring's head once everything queued before it is written
+}
+
+/// Hands lines to the ingestion thread. Never blocks: when the queue is full the line is
+/// counted as dropped, so a log burst can't stall the node's output pipes or the UI.
+#[derive(Clone)]
+pub struct LogSender {
+    sender: SyncSender<IngestLine>,
+    ring: Arc<LogRing>,
+}
+
+impl LogSender {
+    /// Queue a raw output line; returns false once ingestion has shut down
+    pub fn send_line(&self, line: String, fallback_level: LogLevel) -> bool {
+        self.send(IngestLine::Raw(line, fallback_level))
+    }
+
+    /// Queue an already built record, e.g. a status line from the desktop
+    pub fn send_record(&self, record: LogRecord) -> bool {
+        self.send(IngestLine::Record(record))
+    }
+
+    /// Wait until every line queued so far is in the ring and return the ring's head,
+    /// or None once ingestion has shut down
+    pub fn flush(&self) -> Option<u64> {
+        let (reply, done) = mpsc::sync_channel(1);
+        self.sender.send(IngestLine::Flush(reply)).ok()?;
+        done.recv().ok()
+    }
+
+    fn send(&self, line: IngestLine) -> bool {
+        match self.sender.try_send(line) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.ring.note_dropped();
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// Start the background thread that parses queued lines into the ring
+pub fn spawn_ingest(ring: Arc<LogRing>, writer: RingWriter) -> LogSender {
+    let (sender, receiver) = mpsc::sync_channel(INGEST_QUEUE_LINES);
+    std::thread::spawn(move || ingest(receiver, writer));
+    LogSender { sender, ring }
+}
+
+fn ingest(receiver: Receiver<IngestLine>, mut writer: RingWriter) {
+    for line in receiver {
+        let record = match line {
+            IngestLine::Raw(line, fallback_level) => LogRecord::parse(&line, fallback_level),
+            IngestLine::Record(record) => record,
+            IngestLine::Flush(reply) => {
+                let _ = reply.send(writer.ring.head());
+                continue;
+            }
+        };
+        writer.push(record);
+        writer.reclaim();
+    }
+}
+
+/// Stop flag shared with a producer thread, such as a log file tailer
+#[derive(Clone, Default)]
+pub struct StopFlag(Arc<AtomicBool>);
+
+impl StopFlag {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+
+// Also run under Miri: MIRIFLAGS=-Zmiri-disable-isolation cargo +nightly miri test log_ring
+// (isolation is disabled because records read the clock)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(n: u64) -> LogRecord {
+        LogRecord::status(n.to_string(), LogLevel::Info)
+    }
+
+    fn numbers(records: &[Arc<LogRecord>]) -> Vec<u64> {
+        records.iter().map(|record| record.message.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn reads_by_sequence_across_wraparound() {
+        let (ring, mut writer) = LogRing::new(4);
+        assert_eq!(ring.head(), 0);
+        assert!(ring.read(0, 10).records.is_empty());
+
+        for n in 0..10 {
+            writer.push(record(n));
+        }
+        assert_eq!(ring.head(), 10);
+
+        let slice = ring.read(6, 10);
+        assert_eq!(slice.first_seq, 6);
+        assert_eq!(numbers(&slice.records), vec![6, 7, 8, 9]);
+        assert_eq!(slice.missed, 0);
+        assert_eq!(slice.next_seq(), 10);
+
+        let slice = ring.read(7, 2);
+        assert_eq!(numbers(&slice.records), vec![7, 8]);
+
+        // Reading from the head returns nothing until more is written
+        let slice = ring.read(10, 10);
+        assert!(slice.records.is_empty());
+        assert_eq!(slice.next_seq(), 10);
+    }
+
+    #[test]
+    fn counts_lines_missed_by_a_reader_a_full_ring_behind() {
+        let (ring, mut writer) = LogRing::new(4);
+        for n in 0..3 {
+            writer.push(record(n));
+        }
+        let slice = ring.read(0, 2);
+        assert_eq!(numbers(&slice.records), vec![0, 1]);
+        let cursor = slice.next_seq();
+
+        for n in 3..12 {
+            writer.push(record(n));
+        }
+        let slice = ring.read(cursor, 100);
+        assert_eq!(slice.first_seq, 8);
+        assert_eq!(slice.missed, 6);
+        assert_eq!(numbers(&slice.records), vec![8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn tail_returns_the_newest_records() {
+        let (ring, mut writer) = LogRing::new(4);
+        assert!(ring.tail(3).is_empty());
+
+        writer.push(record(0));
+        writer.push(record(1));
+        assert_eq!(numbers(&ring.tail(3)), vec![0, 1]);
+
+        for n in 2..7 {
+            writer.push(record(n));
+        }
+        assert_eq!(numbers(&ring.tail(2)), vec![5, 6]);
+        assert_eq!(numbers(&ring.tail(10)), vec![3, 4, 5, 6]);
+        assert!(ring.tail(0).is_empty());
+    }
+
+    #[test]
+    fn reclaims_replaced_slots_only_without_readers() {
+        let (ring, mut writer) = LogRing::new(2);
+        for n in 0..4 {
+            writer.push(record(n));
+        }
+
+        let guard = ReadGuard::new(&ring);
+        writer.reclaim();
+        assert_eq!(writer.retired.len(), 2);
+        drop(guard);
+
+        writer.reclaim();
+        assert!(writer.retired.is_empty());
+        assert_eq!(numbers(&ring.tail(2)), vec![2, 3]);
+    }
+
+    #[test]
+    fn flush_waits_for_queued_lines() {
+        let (ring, writer) = LogRing::new(16);
+        let sender = spawn_ingest(ring.clone(), writer);
+        for n in 0..5 {
+            assert!(sender.send_record(record(n)));
+        }
+        assert_eq!(sender.flush(), Some(5));
+        assert_eq!(numbers(&ring.tail(5)), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn concurrent_reader_sees_records_in_order() {
+        let total: u64 = if cfg!(miri) { 200 } else { 200_000 };
+        let (ring, mut writer) = LogRing::new(64);
+
+        let producer = std::thread::spawn(move || {
+            for n in 0..total {
+                writer.push(record(n));
+                writer.reclaim();
+            }
+        });
+
+        let readers: Vec<_> = (0..3)
+            .map(|_| {
+                let ring = ring.clone();
+                std::thread::spawn(move || {
+                    let mut cursor = 0;
+                    let mut seen = 0;
+                    let mut missed = 0;
+                    while cursor < total {
+                        let slice = ring.read(cursor, 16);
+                        assert_eq!(slice.first_seq, cursor + slice.missed);
+                        for (offset, number) in numbers(&slice.records).into_iter().enumerate() {
+                            assert_eq!(number, slice.first_seq + offset as u64);
+                        }
+                        seen += slice.records.len() as u64;
+                        missed += slice.missed;
+                        cursor = slice.next_seq();
+                        std::thread::yield_now();
+                    }
+                    assert_eq!(seen + missed, total);
+                })
+            })
+            .collect();
+
+        producer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(ring.head(), total);
+    }
+}
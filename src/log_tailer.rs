@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How long to wait between checks when no change notification arrives
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How much of the end of the file is read for the initial lines
+const RECENT_BYTES: u64 = 256 * 1024;
+
+/// Identifies the file behind a path, so a rename and re-create can be told apart from appends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct FileIdentity(u64, u64);
+
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<FileIdentity> {
+    use std::os::unix::fs::MetadataExt;
+    Some(FileIdentity(metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(metadata: &Metadata) -> Option<FileIdentity> {
+    // No inode numbers; a re-created file has a new creation time
+    let created = metadata.created().ok()?.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+    Some(FileIdentity(created.as_secs(), created.subsec_nanos() as u64))
+}
+
+/// How far a file that isn't being followed right now has been read
+#[derive(Debug, Default)]
+struct ReadState {
+    position: u64,
+    partial: Vec<u8>, // Bytes of a line whose newline hadn't been written yet
+}
+
+/// Follows a reth log file across rotations. Reth's `--log.file.max-size` rotation renames the
+/// active file and starts a new one, and date-rolled files appear next to the old ones; the tailer
+/// finishes reading the old file before switching so lines are neither dropped nor repeated.
+/// Files it leaves keep their read position, so switching back resumes where it stopped.
+pub struct LogTailer {
+    dir: Option<PathBuf>, // Directory watched for newer log files
+    path: PathBuf,
+    file: File,
+    identity: Option<FileIdentity>,
+    position: u64,
+    partial: Vec<u8>, // Bytes of a line whose newline hasn't been written yet
+    left: HashMap<FileIdentity, ReadState>, // Files not being followed, including those that existed at the start
+    watcher: Watcher,
+}
+
+impl LogTailer {
+    /// Start following `path`, also switching to newer `.log` files that appear in `dir`.
+    /// Returns the tailer and up to `recent` lines from the end of the file.
+    pub fn open(path: &Path, dir: Option<PathBuf>, recent: usize) -> std::io::Result<(Self, Vec<String>)> {
+        let mut file = File::open(path)?;
+        let metadata = file.metadata()?;
+        let len = metadata.len();
+
+        // Read the tail of the file for context, starting at a line boundary
+        let start = len.saturating_sub(RECENT_BYTES);
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.by_ref().take(len - start).read_to_end(&mut bytes)?;
+        if start > 0 {
+            let first_newline = bytes.iter().position(|byte| *byte == b'\n').map_or(bytes.len(), |pos| pos + 1);
+            bytes.drain(..first_newline);
+        }
+
+        // Files that already existed when we started are only read from where they were then
+        let identity = file_identity(&metadata);
+        let mut left = dir.as_deref().map(log_file_states).unwrap_or_default();
+        if let Some(identity) = identity {
+            left.remove(&identity);
+        }
+
+        let mut tailer = Self {
+            left,
+            watcher: Watcher::new(dir.as_deref().or(path.parent())),
+            dir,
+            path: path.to_path_buf(),
+            file,
+            identity,
+            position: len,
+            partial: Vec::new(),
+        };
+
+        let mut lines = Vec::new();
+        tailer.take_lines(&bytes, &mut lines);
+        let skip = lines.len().saturating_sub(recent);
+        lines.drain(..skip);
+
+        println!("Tailing log file {} from offset {}", path.display(), len);
+        Ok((tailer, lines))
+    }
+
+    /// The file currently being followed
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Block until the log directory changes or the poll interval has passed
+    pub fn wait(&mut self) {
+        self.watcher.wait(POLL_INTERVAL);
+    }
+
+    /// Read new complete lines, handling truncation, replacement and newer files
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        // Truncated in place: everything from the start is new
+        if self.file.metadata().is_ok_and(|metadata| metadata.len() < self.position) {
+            println!("Log file {} was truncated, reading from the start", self.path.display());
+            self.partial.clear();
+            self.position = 0;
+            if let Err(e) = self.file.seek(SeekFrom::Start(0)) {
+                eprintln!("Failed to rewind truncated log file: {}", e);
+            }
+        }
+
+        // Always drain the open handle first, it still points at the old file after a rename
+        self.read_available(&mut lines);
+
+        if let Some(next) = self.replacement().or_else(|| self.newer_file()) {
+            match self.switch_to(&next) {
+                Ok(()) => self.read_available(&mut lines),
+                Err(e) => eprintln!("Failed to switch to log file {}: {}", next.display(), e),
+            }
+        }
+
+        lines
+    }
+
+    /// The followed path now names a different file (rotated by rename and re-created)
+    fn replacement(&self) -> Option<PathBuf> {
+        let metadata = std::fs::metadata(&self.path).ok()?;
+        let identity = file_identity(&metadata);
+        if identity.is_some() && identity != self.identity {
+            println!("Log file {} was replaced by a new file", self.path.display());
+            Some(self.path.clone())
+        } else {
+            None
+        }
+    }
+
+    /// A more recently written `.log` file in the watched directory, e.g. the next date-rolled file
+    fn newer_file(&self) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let current_modified = self.file.metadata().and_then(|metadata| metadata.modified()).ok()?;
+        let (newest, modified) = newest_log_file(dir)?;
+        if newest == self.path || modified <= current_modified {
+            return None;
+        }
+
+        println!("Newer log file {} appeared, switching from {}", newest.display(), self.path.display());
+        Some(newest)
+    }
+
+    /// Follow `path` instead, resuming where it was left if it was read before.
+    /// The current file's position and unfinished line are kept for when it is written again.
+    fn switch_to(&mut self, path: &Path) -> std::io::Result<()> {
+        let mut file = File::open(path)?;
+        let metadata = file.metadata()?;
+        let identity = file_identity(&metadata);
+        let mut state = identity
+            .and_then(|identity| self.left.remove(&identity))
+            .unwrap_or_default();
+        if state.position > metadata.len() {
+            // Truncated while we were away
+            state = ReadState::default();
+        }
+        file.seek(SeekFrom::Start(state.position))?;
+
+        if let Some(previous) = self.identity {
+            let previous_state = ReadState { position: self.position, partial: std::mem::take(&mut self.partial) };
+            self.left.insert(previous, previous_state);
+        }
+        self.identity = identity;
+        self.file = file;
+        self.path = path.to_path_buf();
+        self.position = state.position;
+        self.partial = state.partial;
+        Ok(())
+    }
+
+    fn read_available(&mut self, lines: &mut Vec<String>) {
+        let mut chunk = [0u8; 64 * 1024];
+        loop {
+            match self.file.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => {
+                    self.position += read as u64;
+                    self.take_lines(&chunk[..read], lines);
+                }
+                Err(e) => {
+                    eprintln!("Error reading log file {}: {}", self.path.display(), e);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Split bytes into complete lines, keeping an unterminated tail for the next read
+    fn take_lines(&mut self, bytes: &[u8], lines: &mut Vec<String>) {
+        self.partial.extend_from_slice(bytes);
+        let Some(last_newline) = self.partial.iter().rposition(|byte| *byte == b'\n') else {
+            return;
+        };
+        let complete: Vec<u8> = self.partial.drain(..=last_newline).collect();
+        for line in complete.split(|byte| *byte == b'\n') {
+            let line = String::from_utf8_lossy(line);
+            let line = line.trim_end_matches('\r');
+            if !line.trim().is_empty() {
+                lines.push(line.to_string());
+            }
+        }
+    }
+}
+
+fn log_files(dir: &Path) -> Vec<(PathBuf, Metadata)> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.file_name().to_string_lossy().ends_with(".log"))
+                .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?)))
+                .filter(|(_, metadata)| metadata.is_file())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Read states placing every existing log file at its current end
+fn log_file_states(dir: &Path) -> HashMap<FileIdentity, ReadState> {
+    log_files(dir)
+        .into_iter()
+        .filter_map(|(_, metadata)| {
+            let state = ReadState { position: metadata.len(), partial: Vec::new() };
+            Some((file_identity(&metadata)?, state))
+        })
+        .collect()
+}
+
+/// The most recently modified `.log` file, by name on ties
+fn newest_log_file(dir: &Path) -> Option<(PathBuf, SystemTime)> {
+    log_files(dir)
+        .into_iter()
+        .filter_map(|(path, metadata)| Some((path, metadata.modified().ok()?)))
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)))
+}
+
+/// Wakes the tailer when files in the log directory change. Uses inotify on Linux and
+/// falls back to sleeping for the poll interval elsewhere or if inotify is unavailable.
+struct Watcher {
+    #[cfg(target_os = "linux")]
+    fd: Option<i32>,
+}
+
+#[cfg(target_os = "linux")]
+impl Watcher {
+    fn new(dir: Option<&Path>) -> Self {
+        use std::os::unix::ffi::OsStrExt;
+        let Some(dir) = dir else {
+            return Self { fd: None };
+        };
+        let Ok(dir) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+            return Self { fd: None };
+        };
+
+        // SAFETY: plain syscalls on a descriptor we own; failures fall back to polling
+        unsafe {
+            let fd = libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC);
+            if fd < 0 {
+                return Self { fd: None };
+            }
+            let mask = libc::IN_MODIFY | libc::IN_CREATE | libc::IN_MOVED_FROM | libc::IN_MOVED_TO | libc::IN_DELETE;
+            if libc::inotify_add_watch(fd, dir.as_ptr(), mask) < 0 {
+                libc::close(fd);
+                return Self { fd: None };
+            }
+            Self { fd: Some(fd) }
+        }
+    }
+
+    fn wait(&mut self, timeout: Duration) {
+        let Some(fd) = self.fd else {
+            std::thread::sleep(timeout);
+            return;
+        };
+
+        let mut poll_fd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+        // SAFETY: poll and read on our own non-blocking descriptor into a local buffer
+        unsafe {
+            // Events only shorten the wait; the caller re-checks everything after waking
+            if libc::poll(&mut poll_fd, 1, timeout.as_millis() as i32) > 0 {
+                let mut events = [0u8; 4096];
+                while libc::read(fd, events.as_mut_ptr() as *mut libc::c_void, events.len()) > 0 {}
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        if let Some(fd) = self.fd.take() {
+            // SAFETY: closing the descriptor opened in `new`
+            unsafe {
+                libc::close(fd);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Watcher {
+    fn new(_dir: Option<&Path>) -> Self {
+        Self {}
+    }
+
+    fn wait(&mut self, timeout: Duration) {
+        std::thread::sleep(timeout);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    /// Append and give the file a distinct modification time, so the newest file is unambiguous
+    fn append_at(path: &Path, text: &str, secs: u64) {
+        append(path, text);
+        let file = OpenOptions::new().append(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)).unwrap();
+    }
+
+    #[test]
+    fn starts_with_recent_lines_and_holds_partial_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reth.log");
+        append(&path, "one\ntwo\nthree\n");
+
+        let (mut tailer, recent) = LogTailer::open(&path, None, 2).unwrap();
+        assert_eq!(recent, vec!["two", "three"]);
+        assert!(tailer.poll().is_empty());
+
+        append(&path, "four\nfi");
+        assert_eq!(tailer.poll(), vec!["four"]);
+        append(&path, "ve\n");
+        assert_eq!(tailer.poll(), vec!["five"]);
+    }
+
+    #[test]
+    fn rereads_a_truncated_file_from_the_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reth.log");
+        append(&path, "first line\nsecond line\n");
+
+        let (mut tailer, _) = LogTailer::open(&path, None, 0).unwrap();
+        OpenOptions::new().write(true).open(&path).unwrap().set_len(0).unwrap();
+        append(&path, "again\n");
+        assert_eq!(tailer.poll(), vec!["again"]);
+    }
+
+    #[test]
+    fn follows_a_file_rotated_by_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reth.log");
+        append(&path, "old\n");
+
+        let (mut tailer, _) = LogTailer::open(&path, Some(dir.path().to_path_buf()), 0).unwrap();
+        append(&path, "before rotation\n");
+        std::fs::rename(&path, dir.path().join("reth.log.1")).unwrap();
+        append(&path, "after rotation\n");
+
+        assert_eq!(tailer.poll(), vec!["before rotation", "after rotation"]);
+        assert_eq!(tailer.path(), path);
+        append(&path, "more\n");
+        assert_eq!(tailer.poll(), vec!["more"]);
+    }
+
+    #[test]
+    fn switches_to_a_new_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("reth-2024-01-15-20.log");
+        let second = dir.path().join("reth-2024-01-15-21.log");
+        append_at(&first, "hour 20\n", 1_000);
+
+        let (mut tailer, _) = LogTailer::open(&first, Some(dir.path().to_path_buf()), 0).unwrap();
+        append_at(&first, "last of hour 20\n", 1_001);
+        append_at(&second, "hour 21\n", 1_002);
+
+        assert_eq!(tailer.poll(), vec!["last of hour 20", "hour 21"]);
+        assert_eq!(tailer.path(), second);
+    }
+
+    #[test]
+    fn resumes_files_it_switches_back_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.log");
+        let b = dir.path().join("b.log");
+        append_at(&a, "a existing\n", 1_000);
+        append_at(&b, "b existing\n", 900);
+
+        let (mut tailer, _) = LogTailer::open(&a, Some(dir.path().to_path_buf()), 0).unwrap();
+        append_at(&a, "a1\n", 1_001);
+        assert_eq!(tailer.poll(), vec!["a1"]);
+
+        // b existed at the start, so only what was written since is read
+        append_at(&b, "b1\n", 1_002);
+        assert_eq!(tailer.poll(), vec!["b1"]);
+
+        append_at(&a, "a2\na3 unfinish", 1_003);
+        assert_eq!(tailer.poll(), vec!["a2"]);
+
+        append_at(&b, "b2\n", 1_004);
+        assert_eq!(tailer.poll(), vec!["b2"]);
+
+        append_at(&a, "ed\n", 1_005);
+        assert_eq!(tailer.poll(), vec!["a3 unfinished"]);
+        assert!(tailer.poll().is_empty());
+    }
+}
@@ -12,17 +12,53 @@ mod config;
 mod settings;
 mod ui;
 mod metrics;
+mod supervisor;
+mod exit_report;
+mod run_file;
+mod discovery;
+mod launch_args;
+mod preflight;
+mod cli_help;
+mod cli_schema;
+mod log_record;
+mod log_archive;
+mod log_tailer;
+mod log_export;
+mod diagnostics;
+mod log_alerts;
+mod sync_stages;
+mod log_rate;
+mod log_ring;
+mod metric_store;
+mod prometheus;
 
 use installer::{RethInstaller, InstallStatus};
 use system_check::SystemRequirements;
 use theme::RethTheme;
-use reth_node::{RethNode, LogLine, LogLevel};
+use reth_node::{LogSource, RethNode};
+use discovery::DiscoveredProcess;
+use log_record::LogRecord;
 use config::{RethConfig, RethConfigManager};
 use settings::{DesktopSettings, DesktopSettingsManager};
-use ui::{DesktopSettingsWindow, NodeSettingsWindow, StartConfigWindow};
+use ui::{AlertRulesWindow, DesktopSettingsWindow, LogViewer, MetricRangeView, NodeSettingsWindow, StartConfigWindow};
 use metrics::RethMetrics;
+use supervisor::{NodeSupervisor, SupervisorDecision};
+use exit_report::NodeExitReport;
+use launch_args::LaunchArg;
+use cli_schema::{CliSchema, CliSchemaDiff, CliSchemaLoad};
+use metric_store::MetricStore;
+use prometheus::Exposition;
+use log_archive::ArchiveSession;
+use diagnostics::DiagnosticBundle;
+use log_alerts::{AlertEngine, AlertSeverity};
+use sync_stages::StageStatus;
+use log_rate::LogRateTracker;
+use log_record::LogLevel;
 
 
+/// Log lines taken from the ring per frame, so a burst doesn't stall the UI
+const MAX_LOG_LINES_PER_FRAME: usize = 5_000;
+
 fn main() -> Result<(), eframe::Error> {
     let mut viewport = egui::ViewportBuilder::default()
         .with_inner_size([1200.0, 800.0])
@@ -77,7 +113,13 @@ struct MyApp {
     system_requirements: SystemRequirements,
     reth_logo: Option<egui::TextureHandle>,
     reth_node: RethNode,
-    node_logs: Vec<LogLine>,
+    log_viewer: LogViewer,
+    log_session: Option<ArchiveSession>, // Archive of the current node run's output
+    log_cursor: u64, // Next log ring sequence number to read
+    missed_log_lines: u64, // Lines overwritten in the ring before they were read
+    alert_engine: AlertEngine,
+    log_rate: LogRateTracker, // Lines per second by level and target, for the rate plot
+    log_rate_by_target: bool,
     is_reth_installed: bool,
     was_detected_on_startup: bool,
     detected_existing_process: bool,
@@ -86,6 +128,7 @@ struct MyApp {
     update_available: bool,
     show_settings: bool,
     show_desktop_settings: bool,
+    show_alert_rules: bool,
     show_start_config: bool,
     desktop_settings: DesktopSettings,
     reth_config: RethConfig,
@@ -96,10 +139,21 @@ struct MyApp {
     last_debug_log: std::time::Instant,
     show_add_parameter: bool,
     available_cli_options: Vec<reth_node::CliOption>,
+    preflight_cache: Option<(Vec<String>, preflight::PreflightReport)>, // Report for the argv it was computed from
+    cli_schema_sender: mpsc::UnboundedSender<Result<CliSchemaLoad, String>>,
+    cli_schema_receiver: mpsc::UnboundedReceiver<Result<CliSchemaLoad, String>>,
+    cli_schema_loading: bool,
+    cli_schema_diff: Option<CliSchemaDiff>, // Flags changed since the previously installed reth version
     selected_cli_option: Option<usize>,
     parameter_value: String,
     selected_values: Vec<String>,
-    pending_launch_args: Vec<String>,
+    pending_launch_args: Vec<LaunchArg>,
+    pending_restart: bool, // Relaunch once the current node has finished shutting down
+    pending_takeover: Option<DiscoveredProcess>, // External node to relaunch under management once it has stopped
+    supervisor: NodeSupervisor,
+    last_exit_report: Option<NodeExitReport>, // How the last managed run ended
+    exit_report_status: Option<String>, // Result of the last save/export action
+    stop_error: Option<String>, // Why the last stop request failed, e.g. the node belongs to another user
     show_restart_prompt: bool,
     command_section_collapsed: bool,
     metrics: RethMetrics,
@@ -107,9 +161,13 @@ struct MyApp {
     metrics_poll_sender: Option<mpsc::UnboundedSender<()>>,
     metrics_receiver: mpsc::UnboundedReceiver<String>,
     metrics_sender: mpsc::UnboundedSender<String>,
+    last_metrics_text: Option<String>, // Latest raw scrape, included in diagnostic bundles
     expanded_metric: Option<String>, // Track which metric is expanded in popup
-    available_metrics: Vec<String>, // All available metrics from Prometheus
+    available_metrics: Vec<metrics::AvailableMetric>, // All available metrics from Prometheus
     show_metric_selector: bool, // Show metric selection dialog
+    metric_draft: Option<metrics::CustomMetric>, // Labelled metric whose filters are being chosen in the selector
+    metric_store: Option<MetricStore>, // Long-term metric history on disk
+    metric_range: MetricRangeView, // Time range shown in the metric popup
 }
 
 enum InstallCommand {
@@ -123,6 +181,7 @@ impl MyApp {
         let (tx, mut rx) = mpsc::unbounded_channel::<InstallCommand>();
         let (update_tx, update_rx) = mpsc::unbounded_channel::<(String, bool)>();
         let (metrics_tx, metrics_rx) = mpsc::unbounded_channel::<String>();
+        let (cli_schema_tx, cli_schema_rx) = mpsc::unbounded_channel::<Result<CliSchemaLoad, String>>();
         
         // Load the Reth logo
         let reth_logo = Self::load_logo(&cc.egui_ctx);
@@ -132,23 +191,11 @@ impl MyApp {
         let installed_version = Self::get_installed_version();
         
         // Load Reth configuration
-        let (reth_config, reth_config_path) = RethConfigManager::load_reth_config();
+        let (mut reth_config, mut reth_config_path) = RethConfigManager::load_reth_config();
         
         // Load desktop settings
         let desktop_settings = DesktopSettingsManager::load_desktop_settings();
         
-        // Load CLI options if Reth is installed
-        let available_cli_options = if is_reth_installed {
-            let reth_path = dirs::home_dir()
-                .unwrap_or_default()
-                .join(".reth-desktop")
-                .join("bin")
-                .join("reth");
-            RethNode::get_available_cli_options(&reth_path.to_string_lossy())
-        } else {
-            Vec::new()
-        };
-        
         // Spawn a task to handle installation commands
         runtime.spawn(async move {
             while let Some(cmd) = rx.recv().await {
@@ -193,14 +240,25 @@ impl MyApp {
         
         // Create RethNode and check for existing processes
         let mut reth_node = RethNode::new();
-        let detect_existing = RethNode::detect_existing_reth_process();
         
-        println!("Startup: Reth installed: {}, External process detected: {}", is_reth_installed, detect_existing);
+        // A detached node from a previous session is re-adopted with full stop/restart control
+        let adopted = reth_node.adopt_detached_process(&desktop_settings);
+        let detect_existing = adopted || RethNode::detect_existing_reth_process();
+        
+        println!("Startup: Reth installed: {}, External process detected: {}, Re-adopted detached node: {}", is_reth_installed, detect_existing, adopted);
         
         // If Reth is running, try to connect to it
-        if detect_existing {
+        if detect_existing && !adopted {
             if let Ok(()) = reth_node.connect_to_existing_process() {
                 println!("Found and connected to existing Reth process");
+                
+                // Show the configuration the external node actually uses
+                if let Some(external) = reth_node.external_process() {
+                    if let Some((config, path)) = RethConfigManager::load_reth_config_from_datadir(&external.datadir) {
+                        reth_config = config;
+                        reth_config_path = Some(path);
+                    }
+                }
             } else {
                 println!("Failed to connect to detected Reth process");
             }
@@ -208,11 +266,11 @@ impl MyApp {
         
         // Initialize metrics with custom metrics from settings
         let mut metrics = RethMetrics::new();
-        for metric_name in &desktop_settings.custom_metrics {
-            metrics.add_custom_metric(metric_name.clone());
+        for custom in &desktop_settings.custom_metrics {
+            metrics.add_custom_metric(custom);
         }
         
-        let app = Self {
+        let mut app = Self {
             installer: Arc::new(Mutex::new(RethInstaller::new())),
             install_status: initial_status,
             installing: false,
@@ -222,7 +280,8 @@ impl MyApp {
             system_requirements: SystemRequirements::check(),
             reth_logo,
             reth_node,
-            node_logs: Vec::new(),
+            log_viewer: LogViewer::new(),
+            log_session: None,
             is_reth_installed,
             was_detected_on_startup: is_reth_installed,
             detected_existing_process: detect_existing,
@@ -231,7 +290,13 @@ impl MyApp {
             update_available: false,
             show_settings: false,
             show_desktop_settings: false,
+            show_alert_rules: false,
             show_start_config: false,
+            log_rate: LogRateTracker::new(),
+            log_cursor: 0,
+            missed_log_lines: 0,
+            log_rate_by_target: false,
+            alert_engine: AlertEngine::new(desktop_settings.alerts.active_rules()),
             desktop_settings,
             reth_config: reth_config.clone(),
             reth_config_path,
@@ -240,11 +305,22 @@ impl MyApp {
             settings_edit_mode: false,
             last_debug_log: std::time::Instant::now(),
             show_add_parameter: false,
-            available_cli_options,
+            available_cli_options: Vec::new(),
+            preflight_cache: None,
+            cli_schema_sender: cli_schema_tx,
+            cli_schema_receiver: cli_schema_rx,
+            cli_schema_loading: false,
+            cli_schema_diff: None,
             selected_cli_option: None,
             parameter_value: String::new(),
             selected_values: Vec::new(),
             pending_launch_args: Vec::new(),
+            pending_restart: false,
+            pending_takeover: None,
+            supervisor: NodeSupervisor::new(),
+            last_exit_report: None,
+            exit_report_status: None,
+            stop_error: None,
             show_restart_prompt: false,
             command_section_collapsed: true,
             metrics,
@@ -252,11 +328,25 @@ impl MyApp {
             metrics_poll_sender: None,
             metrics_receiver: metrics_rx,
             metrics_sender: metrics_tx,
+            last_metrics_text: None,
             expanded_metric: None,
             available_metrics: Vec::new(),
-            show_metric_selector: false
+            show_metric_selector: false,
+            metric_draft: None,
+            metric_store: Some(MetricStore::open()),
+            metric_range: MetricRangeView::new(),
         };
         
+        // Poll metrics right away for nodes whose metrics endpoint we know
+        if adopted || app.reth_node.metrics_address().is_some() {
+            app.start_metrics_polling();
+        }
+        
+        // Load CLI options in the background if Reth is installed
+        if is_reth_installed {
+            app.load_cli_schema();
+        }
+        
         app
     }
     
@@ -545,6 +635,66 @@ impl MyApp {
         let _ = self.install_sender.send(InstallCommand::ResetInstaller(installer));
     }
     
+    /// Saved custom launch parameters rendered into argv elements
+    fn custom_launch_argv(&self) -> Vec<String> {
+        launch_args::render_argv(&self.desktop_settings.custom_launch_args, &self.available_cli_options)
+    }
+    
+    /// Check the arguments the next Start would use. Validation only reruns when the argv
+    /// changes; a newly loaded CLI schema clears the cache.
+    fn preflight_report(&mut self) -> &preflight::PreflightReport {
+        let args = RethNode::build_launch_args(&self.custom_launch_argv(), &self.desktop_settings);
+        if self.preflight_cache.as_ref().is_none_or(|(cached, _)| *cached != args) {
+            let report = preflight::validate_launch_args(&args[1..], &self.available_cli_options);
+            self.preflight_cache = Some((args, report));
+        }
+        &self.preflight_cache.as_ref().expect("preflight report was just cached").1
+    }
+
+    /// Load the CLI options for the installed binary on the runtime, from the per-version cache when possible
+    fn load_cli_schema(&mut self) {
+        if self.cli_schema_loading {
+            return;
+        }
+        self.cli_schema_loading = true;
+
+        let reth_path = dirs::home_dir()
+            .unwrap_or_default()
+            .join(".reth-desktop")
+            .join("bin")
+            .join("reth");
+        let sender = self.cli_schema_sender.clone();
+        self._runtime.spawn(async move {
+            let _ = sender.send(CliSchema::load(reth_path).await);
+        });
+    }
+
+    fn handle_cli_schema(&mut self, result: Result<CliSchemaLoad, String>) {
+        self.cli_schema_loading = false;
+        self.preflight_cache = None;
+        match result {
+            Ok(load) => {
+                if let Some(previous) = &load.previous {
+                    let diff = CliSchemaDiff::compute(previous, &load.schema, &self.desktop_settings.custom_launch_args);
+                    println!("Reth CLI changed from {} to {}: {} added, {} removed, {} renamed",
+                        diff.from_version, diff.to_version, diff.added.len(), diff.removed.len(), diff.renamed.len());
+                    if !diff.is_empty() {
+                        // Surface the changes right away when saved parameters need attention
+                        if !diff.affected_args.is_empty() {
+                            self.show_start_config = true;
+                        }
+                        self.cli_schema_diff = Some(diff);
+                    }
+                }
+                self.available_cli_options = load.schema.options;
+            }
+            Err(e) => {
+                println!("Failed to load CLI options: {}", e);
+                self.available_cli_options = RethNode::fallback_cli_options();
+            }
+        }
+    }
+
     fn launch_reth(&mut self) {
         let reth_path = dirs::home_dir()
             .unwrap_or_default()
@@ -552,7 +702,8 @@ impl MyApp {
             .join("bin")
             .join("reth");
         
-        match self.reth_node.start(&reth_path.to_string_lossy(), &self.desktop_settings.custom_launch_args, &self.desktop_settings) {
+        let custom_args = self.custom_launch_argv();
+        match self.reth_node.start(&reth_path.to_string_lossy(), &custom_args, &self.desktop_settings) {
             Ok(()) => {
                 self.install_status = InstallStatus::Running;
                 // Clear pending args since they've been applied
@@ -567,6 +718,318 @@ impl MyApp {
         }
     }
     
+    fn restart_reth(&mut self) {
+        // Start the node again with new parameters
+        let reth_path = dirs::home_dir()
+            .unwrap_or_default()
+            .join(".reth-desktop")
+            .join("bin")
+            .join("reth");
+        
+        let custom_args = self.custom_launch_argv();
+        match self.reth_node.start(&reth_path.to_string_lossy(), &custom_args, &self.desktop_settings) {
+            Ok(()) => {
+                self.install_status = InstallStatus::Running;
+                self.pending_launch_args.clear();
+                self.start_metrics_polling();
+            }
+            Err(e) => {
+                self.install_status = InstallStatus::Error(format!("Failed to restart Reth: {}", e));
+            }
+        }
+    }
+    
+    fn supervisor_restart(&mut self) {
+        // Relaunch with the same argv or custom arguments the crashed node was started with
+        let reth_path = dirs::home_dir()
+            .unwrap_or_default()
+            .join(".reth-desktop")
+            .join("bin")
+            .join("reth");
+        let custom_args = self.reth_node.last_custom_args().to_vec();
+        
+        let result = match self.reth_node.original_argv().map(|argv| argv.to_vec()) {
+            Some(argv) => {
+                let cwd = self.reth_node.original_cwd().map(std::path::Path::to_path_buf);
+                self.reth_node.start_from_argv(&argv, cwd.as_deref(), &self.desktop_settings)
+            }
+            None => self.reth_node.start(&reth_path.to_string_lossy(), &custom_args, &self.desktop_settings),
+        };
+        match result {
+            Ok(()) => {
+                println!("Supervisor restarted Reth");
+                self.install_status = InstallStatus::Running;
+                self.start_metrics_polling();
+            }
+            Err(e) => {
+                self.install_status = InstallStatus::Error(format!("Failed to restart Reth after crash: {}", e));
+            }
+        }
+    }
+    
+    fn show_supervisor_status(&self, ui: &mut egui::Ui) {
+        let history = self.supervisor.history();
+        let countdown = self.supervisor.time_until_restart();
+        if history.is_empty() && countdown.is_none() && !self.supervisor.is_locked_out() {
+            return;
+        }
+        
+        egui::Frame::none()
+            .fill(RethTheme::SURFACE)
+            .rounding(8.0)
+            .inner_margin(16.0)
+            .stroke(egui::Stroke::new(1.0, RethTheme::BORDER))
+            .show(ui, |ui| {
+                if let Some(remaining) = countdown {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::widgets::Spinner::new().size(12.0).color(RethTheme::WARNING));
+                        ui.add_space(8.0);
+                        ui.label(RethTheme::warning_text(&format!("Reth crashed - restarting in {}s", remaining.as_secs() + 1)));
+                    });
+                } else if self.supervisor.is_locked_out() {
+                    ui.label(RethTheme::error_text("Crash loop detected - automatic restarts are suspended until the node is started manually"));
+                }
+                
+                if !history.is_empty() {
+                    ui.add_space(8.0);
+                    egui::CollapsingHeader::new(format!("Crash history ({})", history.len()))
+                        .id_source("supervisor_crash_history")
+                        .show(ui, |ui| {
+                            for (i, record) in history.iter().enumerate().rev() {
+                                let restart = match record.restart_delay {
+                                    Some(delay) => format!("restart #{} after {}s", record.attempt, delay.as_secs()),
+                                    None => "not restarted".to_string(),
+                                };
+                                egui::CollapsingHeader::new(format!(
+                                    "{} - {} - {}",
+                                    record.exit.exited_at.format("%Y-%m-%d %H:%M:%S"),
+                                    record.exit.describe(),
+                                    restart
+                                ))
+                                .id_source(("supervisor_crash_record", i))
+                                .show(ui, |ui| {
+                                    if record.exit.recent_logs.is_empty() {
+                                        ui.label(RethTheme::muted_text("No log output captured"));
+                                    }
+                                    for line in &record.exit.recent_logs {
+                                        ui.label(egui::RichText::new(format!("{} {:5} {}", line.time_text(), line.level.as_str(), Self::clean_log_content(&line.text())))
+                                            .size(11.0)
+                                            .monospace()
+                                            .color(RethTheme::TEXT_SECONDARY));
+                                    }
+                                });
+                            }
+                        });
+                }
+            });
+        
+        ui.add_space(12.0);
+    }
+    
+    /// Active log alerts and the timeline of events detected by the alert rules
+    fn show_alerts(&mut self, ui: &mut egui::Ui) {
+        if self.alert_engine.active().is_empty() && self.alert_engine.timeline().is_empty() {
+            return;
+        }
+        
+        let mut dismiss = None;
+        let mut dismiss_all = false;
+        let mut clear_timeline = false;
+        let mut edit_rules = false;
+        
+        for (index, alert) in self.alert_engine.active().iter().enumerate() {
+            let color = Self::alert_color(alert.severity);
+            egui::Frame::none()
+                .fill(color.gamma_multiply(0.1))
+                .rounding(8.0)
+                .inner_margin(12.0)
+                .stroke(egui::Stroke::new(1.0, color))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(format!("{} - {}", alert.severity.as_str(), alert.rule)).color(color).strong());
+                        ui.label(RethTheme::muted_text(&alert.time.format("%H:%M:%S").to_string()));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("Dismiss").clicked() {
+                                dismiss = Some(index);
+                            }
+                        });
+                    });
+                    ui.label(RethTheme::monospace_text(&alert.message));
+                });
+            ui.add_space(6.0);
+        }
+        
+        egui::Frame::none()
+            .fill(RethTheme::SURFACE)
+            .rounding(8.0)
+            .inner_margin(16.0)
+            .stroke(egui::Stroke::new(1.0, RethTheme::BORDER))
+            .show(ui, |ui| {
+                egui::CollapsingHeader::new(RethTheme::text(&format!("Events ({})", self.alert_engine.timeline().len())))
+                    .id_source("alert_timeline")
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.button("Edit rules…").clicked() {
+                                edit_rules = true;
+                            }
+                            if ui.button("Clear").clicked() {
+                                clear_timeline = true;
+                            }
+                            if self.alert_engine.active().len() > 1 && ui.button("Dismiss all alerts").clicked() {
+                                dismiss_all = true;
+                            }
+                        });
+                        ui.add_space(6.0);
+                        egui::ScrollArea::vertical()
+                            .max_height(220.0)
+                            .show(ui, |ui| {
+                                // Newest first
+                                for event in self.alert_engine.timeline().iter().rev() {
+                                    ui.horizontal(|ui| {
+                                        ui.label(RethTheme::muted_text(&event.time.format("%H:%M:%S").to_string()));
+                                        ui.label(egui::RichText::new(&event.rule).color(Self::alert_color(event.severity)));
+                                        ui.label(egui::RichText::new(&event.message)
+                                            .size(11.0)
+                                            .monospace()
+                                            .color(RethTheme::TEXT_SECONDARY));
+                                    });
+                                }
+                            });
+                    });
+            });
+        
+        if let Some(index) = dismiss {
+            self.alert_engine.dismiss(index);
+        }
+        if dismiss_all {
+            self.alert_engine.dismiss_all();
+        }
+        if clear_timeline {
+            self.alert_engine.clear_timeline();
+        }
+        if edit_rules {
+            self.show_alert_rules = true;
+        }
+        
+        ui.add_space(12.0);
+    }
+    
+    fn alert_color(severity: AlertSeverity) -> egui::Color32 {
+        match severity {
+            AlertSeverity::Info => RethTheme::PRIMARY,
+            AlertSeverity::Warning => RethTheme::WARNING,
+            AlertSeverity::Critical => RethTheme::ERROR,
+        }
+    }
+    
+    /// Log viewer for browsing archived sessions while no node is running
+    fn show_log_history(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(RethTheme::text("Log History"))
+            .id_source("log_history")
+            .show(ui, |ui| {
+                egui::Frame::none()
+                    .fill(RethTheme::SURFACE)
+                    .rounding(8.0)
+                    .inner_margin(16.0)
+                    .stroke(egui::Stroke::new(1.0, RethTheme::BORDER))
+                    .show(ui, |ui| {
+                        self.log_viewer.show(ui, 300.0);
+                    });
+            });
+        
+        ui.add_space(12.0);
+    }
+    
+    fn show_last_run_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(report) = &self.last_exit_report else {
+            return;
+        };
+        
+        let mut export_result = None;
+        
+        egui::Frame::none()
+            .fill(RethTheme::SURFACE)
+            .rounding(8.0)
+            .inner_margin(16.0)
+            .stroke(egui::Stroke::new(1.0, if report.is_crash() { RethTheme::ERROR } else { RethTheme::BORDER }))
+            .show(ui, |ui| {
+                egui::CollapsingHeader::new(RethTheme::text("Last run"))
+                    .id_source("last_run_panel")
+                    .default_open(report.is_crash())
+                    .show(ui, |ui| {
+                        let outcome = if report.stop_requested {
+                            RethTheme::muted_text(&format!("Stopped by request ({})", report.describe()))
+                        } else if report.is_crash() {
+                            RethTheme::error_text(&format!("Crashed: {}", report.describe()))
+                        } else if report.is_status_unknown() {
+                            RethTheme::muted_text("Exited, status unknown")
+                        } else {
+                            RethTheme::warning_text(&format!("Exited unexpectedly: {}", report.describe()))
+                        };
+                        ui.label(outcome);
+                        ui.add_space(4.0);
+                        
+                        egui::Grid::new("last_run_grid")
+                            .num_columns(2)
+                            .spacing([12.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label(RethTheme::muted_text("Started:"));
+                                ui.label(report.started_at.format("%Y-%m-%d %H:%M:%S").to_string());
+                                ui.end_row();
+                                ui.label(RethTheme::muted_text("Exited:"));
+                                ui.label(report.exited_at.format("%Y-%m-%d %H:%M:%S").to_string());
+                                ui.end_row();
+                                ui.label(RethTheme::muted_text("Uptime:"));
+                                ui.label(report.format_uptime());
+                                ui.end_row();
+                                ui.label(RethTheme::muted_text("Command:"));
+                                ui.label(RethTheme::monospace_text(&launch_args::format_command(&report.launch_command)));
+                                ui.end_row();
+                            });
+                        
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Copy report").on_hover_text("Copy the full report to the clipboard").clicked() {
+                                ui.output_mut(|o| o.copied_text = report.to_text_bundle());
+                                export_result = Some("Report copied to clipboard".to_string());
+                            }
+                            if ui.button("Export…").on_hover_text("Write the report as a text file to your downloads folder").clicked() {
+                                export_result = Some(match report.export() {
+                                    Ok(path) => format!("Exported to {}", path.display()),
+                                    Err(e) => format!("Export failed: {}", e),
+                                });
+                            }
+                            if let Some(status) = &self.exit_report_status {
+                                ui.label(RethTheme::muted_text(status));
+                            }
+                        });
+                        
+                        ui.add_space(8.0);
+                        egui::CollapsingHeader::new(format!("Last {} log lines", report.recent_logs.len()))
+                            .id_source("last_run_logs")
+                            .show(ui, |ui| {
+                                egui::ScrollArea::both()
+                                    .max_height(240.0)
+                                    .stick_to_bottom(true)
+                                    .show(ui, |ui| {
+                                        for line in &report.recent_logs {
+                                            ui.label(egui::RichText::new(format!("{} {:5} {}", line.time_text(), line.level.as_str(), Self::clean_log_content(&line.text())))
+                                                .size(11.0)
+                                                .monospace()
+                                                .color(RethTheme::TEXT_SECONDARY));
+                                        }
+                                    });
+                            });
+                    });
+            });
+        
+        if let Some(result) = export_result {
+            self.exit_report_status = Some(result);
+        }
+        
+        ui.add_space(12.0);
+    }
+    
     fn stop_metrics_polling(&mut self) {
         if let Some(sender) = self.metrics_poll_sender.take() {
             // Send stop signal to the polling task
@@ -575,14 +1038,46 @@ impl MyApp {
         }
     }
     
-    fn stop_reth(&mut self) {
-        // Stop metrics polling first
-        self.stop_metrics_polling();
-        
-        if let Err(e) = self.reth_node.stop() {
+    /// Ask the node to shut down; returns false if it couldn't be signalled and is still running
+    fn stop_reth(&mut self) -> bool {
+        // A manual stop cancels any scheduled automatic restart
+        self.supervisor.reset();
+        
+        // Ask the node to shut down; the update loop moves to Stopped once it has exited
+        self.reth_node.set_shutdown_timeout(std::time::Duration::from_secs(self.desktop_settings.shutdown_timeout_secs));
+        if let Err(e) = self.reth_node.request_stop() {
             eprintln!("Error stopping Reth: {}", e);
+            self.stop_error = Some(e.to_string());
+            return false;
+        }
+        
+        self.stop_error = None;
+        self.stop_metrics_polling();
+        true
+    }
+    
+    /// Stop an external node and relaunch the same command line as a managed child
+    fn restart_under_management(&mut self) {
+        let Some(external) = self.reth_node.external_process().cloned() else {
+            return;
+        };
+        println!("Taking over external Reth process: {}", launch_args::format_command(&external.argv));
+        if self.stop_reth() {
+            self.pending_takeover = Some(external);
+        }
+    }
+    
+    /// Relaunch a stopped external node with its resolved executable, from its original directory
+    fn launch_takeover(&mut self, external: &DiscoveredProcess) {
+        match self.reth_node.start_from_argv(&external.relaunch_argv(), external.cwd.as_deref(), &self.desktop_settings) {
+            Ok(()) => {
+                self.install_status = InstallStatus::Running;
+                self.start_metrics_polling();
+            }
+            Err(e) => {
+                self.install_status = InstallStatus::Error(format!("Failed to relaunch Reth under management: {}", e));
+            }
         }
-        self.install_status = InstallStatus::Stopped;
     }
     
     
@@ -592,12 +1087,20 @@ impl MyApp {
         // Don't reset edit mode here - let the caller decide
     }
     
+    /// Metrics address of the running node, falling back to the configured default
+    fn metrics_address(&self) -> String {
+        self.reth_node.metrics_address()
+            .unwrap_or_else(|| self.desktop_settings.reth_defaults.metrics_address.clone())
+    }
+    
     fn start_metrics_polling(&mut self) {
+        // Only one poller at a time, otherwise every scrape is counted twice
+        self.stop_metrics_polling();
         let (tx, mut rx) = mpsc::unbounded_channel::<()>();
         self.metrics_poll_sender = Some(tx);
         
         let metrics_sender = self.metrics_sender.clone();
-        let metrics_url = format!("http://{}", self.desktop_settings.reth_defaults.metrics_address);
+        let metrics_url = format!("http://{}", self.metrics_address());
         
         // Spawn a task to poll metrics
         self._runtime.spawn(async move {
@@ -605,9 +1108,10 @@ impl MyApp {
             tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
             
             loop {
-                // Check if we should stop polling
-                if rx.try_recv().is_ok() {
-                    break;
+                // Stop when asked, or when the app dropped the sender without asking
+                match rx.try_recv() {
+                    Ok(()) | Err(mpsc::error::TryRecvError::Disconnected) => break,
+                    Err(mpsc::error::TryRecvError::Empty) => {}
                 }
                 
                 // Poll metrics
@@ -628,13 +1132,100 @@ impl MyApp {
         });
     }
 
-    fn disconnect_from_external_reth(&mut self) {
-        // Disconnect from monitoring external Reth process
-        if let Err(e) = self.reth_node.stop() {
-            eprintln!("Error disconnecting from external Reth process: {}", e);
+    /// Write captured output to the session archive, starting a session on the first lines of a run
+    fn archive_logs(&mut self, records: &[Arc<LogRecord>]) {
+        if self.log_session.is_none() {
+            let launch_command = self.reth_node.get_launch_command().cloned().unwrap_or_default();
+            match ArchiveSession::start(launch_command) {
+                Ok(session) => self.log_session = Some(session),
+                Err(e) => eprintln!("Failed to start log archive session: {}", e),
+            }
         }
+        if let Some(session) = &self.log_session {
+            session.write(records);
+        }
+    }
+    
+    /// What the terminal shows until the node's first line arrives: how it was launched,
+    /// where its output is read from, and how long we've been waiting
+    fn show_waiting_for_output(&self, ui: &mut egui::Ui) {
+        let source = self.reth_node.log_source();
+        ui.horizontal(|ui| {
+            if let LogSource::Unavailable(_) = source {
+                ui.label(RethTheme::warning_text("⚠ No log output available"));
+            } else {
+                ui.spinner();
+                ui.label(RethTheme::text("Waiting for output…"));
+            }
+        });
+        ui.add_space(12.0);
+
+        let external = self.reth_node.is_monitoring_external();
+        egui::Grid::new("waiting_for_output")
+            .num_columns(2)
+            .spacing([12.0, 8.0])
+            .show(ui, |ui| {
+                ui.label(RethTheme::muted_text("Command:"));
+                let command = match self.reth_node.get_launch_command() {
+                    Some(command) => launch_args::format_command(command),
+                    None => "unknown".to_string(),
+                };
+                ui.add(egui::Label::new(RethTheme::monospace_text(&command)).wrap(true));
+                ui.end_row();
+
+                ui.label(RethTheme::muted_text("Log source:"));
+                let source_text = match &source {
+                    LogSource::Pipe => "stdout/stderr of the node process".to_string(),
+                    LogSource::File(path) => path.display().to_string(),
+                    LogSource::Unavailable(_) => "none".to_string(),
+                };
+                ui.label(RethTheme::monospace_text(&source_text));
+                ui.end_row();
+
+                if let Some(since) = self.reth_node.running_since() {
+                    ui.label(RethTheme::muted_text(if external { "Connected for:" } else { "Launched:" }));
+                    let elapsed = sync_stages::format_duration(since.elapsed());
+                    ui.label(RethTheme::text(&if external { elapsed } else { format!("{} ago", elapsed) }));
+                    ui.end_row();
+                }
+            });
+
+        if let LogSource::Unavailable(reason) = &source {
+            ui.add_space(12.0);
+            ui.add(egui::Label::new(RethTheme::warning_text(reason)).wrap(true));
+        }
+    }
+    
+    /// Take log lines written to the ring since the last call
+    fn read_new_logs(&mut self, max: usize) -> Vec<Arc<LogRecord>> {
+        let slice = self.reth_node.log_ring().read(self.log_cursor, max);
+        self.missed_log_lines += slice.missed;
+        self.log_cursor = slice.next_seq();
+        slice.records
+    }
+    
+    /// Gather the app state for a diagnostic bundle and write it in the background
+    fn write_diagnostic_bundle(&mut self) {
+        let launch_command = self.reth_node.get_launch_command().cloned()
+            .or_else(|| self.last_exit_report.as_ref().map(|report| report.launch_command.clone()))
+            .unwrap_or_default();
+        let bundle = DiagnosticBundle {
+            launch_command,
+            settings: self.desktop_settings.clone(),
+            reth_config_path: self.reth_config_path.clone(),
+            system_report: diagnostics::system_report(&self.system_requirements),
+            metrics_sample: self.last_metrics_text.clone(),
+            recent_logs: self.reth_node.recent_logs(1000),
+        };
+        self.log_viewer.run_export(move || bundle.write());
+    }
+    
+    fn disconnect_from_external_reth(&mut self) {
+        // Stop monitoring the external Reth process; it isn't ours, so it keeps running
+        self.stop_metrics_polling();
+        self.reth_node.disconnect();
         // Clear logs and reset state
-        self.node_logs.clear();
+        self.log_viewer.clear();
     }
     
     // Removed show_settings_content function - functionality moved to NodeSettingsWindow
@@ -644,12 +1235,13 @@ impl MyApp {
         ui.add_space(20.0);
         
         // Initialize custom metrics if needed
-        for metric_name in &self.desktop_settings.custom_metrics.clone() {
-            self.metrics.add_custom_metric(metric_name.clone());
+        for custom in &self.desktop_settings.custom_metrics {
+            self.metrics.add_custom_metric(custom);
         }
         
         let mut expanded_metric_name: Option<String> = None;
         let mut metric_to_remove: Option<String> = None;
+        let mut mode_change: Option<(String, metrics::DisplayMode)> = None;
         
         // Metrics grid matching mockup design
         egui::Grid::new("metrics_grid_mockup")
@@ -659,17 +1251,9 @@ impl MyApp {
                 let mut count = 0;
                 
                 // Show default metrics
-                let default_metrics = vec![
-                    ("Connected Peers", self.metrics.peers_connected.clone()),
-                    ("Block Height", self.metrics.block_height.clone()),
-                    ("Sync Progress", self.metrics.sync_progress.clone()),
-                    ("Memory Usage", self.metrics.memory_usage.clone()),
-                    ("Active Downloads", self.metrics.disk_io.clone()),
-                ];
-                
-                for (name, metric) in default_metrics {
-                    if self.show_mockup_metric_card(ui, &metric) {
-                        expanded_metric_name = Some(name.to_string());
+                for metric in self.metrics.default_cards() {
+                    if self.show_mockup_metric_card(ui, metric) {
+                        expanded_metric_name = Some(metric.name.clone());
                     }
                     count += 1;
                     if count % 3 == 0 {
@@ -684,9 +1268,12 @@ impl MyApp {
                     .collect();
                     
                 for (metric_name, metric) in custom_metrics {
-                    let (expand_clicked, remove_clicked) = self.show_custom_metric_card(ui, &metric, &metric_name);
+                    let (expand_clicked, remove_clicked, mode) = self.show_custom_metric_card(ui, &metric, &metric_name);
                     if expand_clicked {
-                        expanded_metric_name = Some(metric.name.clone());
+                        expanded_metric_name = Some(metric_name.clone());
+                    }
+                    if let Some(mode) = mode {
+                        mode_change = Some((metric_name.clone(), mode));
                     }
                     if remove_clicked {
                         metric_to_remove = Some(metric_name.clone());
@@ -712,12 +1299,25 @@ impl MyApp {
             self.expanded_metric = Some(name);
         }
         
+        // Switch a custom card between raw, rate and delta
+        if let Some((metric_name, mode)) = mode_change {
+            for custom in self.desktop_settings.custom_metrics.iter_mut().filter(|custom| custom.key() == metric_name) {
+                custom.mode = mode;
+            }
+            if let Some(metric) = self.metrics.custom_metrics.get_mut(&metric_name) {
+                metric.set_mode(mode);
+            }
+            if let Err(e) = DesktopSettingsManager::save_desktop_settings(&self.desktop_settings) {
+                eprintln!("Failed to save custom metrics: {}", e);
+            }
+        }
+        
         // Handle metric removal
         if let Some(metric_name) = metric_to_remove {
             // Remove from settings
-            self.desktop_settings.custom_metrics.retain(|m| m != &metric_name);
+            self.desktop_settings.custom_metrics.retain(|m| m.key() != metric_name);
             // Remove from metrics
-            self.metrics.custom_metrics.remove(&metric_name);
+            self.metrics.remove_custom_metric(&metric_name);
             // Save settings
             if let Err(e) = DesktopSettingsManager::save_desktop_settings(&self.desktop_settings) {
                 eprintln!("Failed to save custom metrics: {}", e);
@@ -725,6 +1325,188 @@ impl MyApp {
         }
     }
     
+    /// Stage-by-stage view of the sync pipeline with checkpoints, entity counts and ETAs
+    fn show_sync_stages(&self, ui: &mut egui::Ui) {
+        let tracker = &self.metrics.sync_stages;
+        if tracker.is_empty() {
+            return;
+        }
+        
+        ui.add_space(20.0);
+        egui::Frame::none()
+            .fill(RethTheme::SURFACE)
+            .rounding(8.0)
+            .inner_margin(16.0)
+            .stroke(egui::Stroke::new(1.0, RethTheme::BORDER))
+            .show(ui, |ui| {
+                let title = match tracker.current_stage() {
+                    Some(stage) => format!("Sync Pipeline - {}", stage),
+                    None => "Sync Pipeline".to_string(),
+                };
+                egui::CollapsingHeader::new(RethTheme::text(&title))
+                    .id_source("sync_stages")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        if let Some(progress) = tracker.overall_progress() {
+                            ui.label(RethTheme::muted_text(&format!("Overall: {:.1}%", progress)));
+                            ui.add_space(6.0);
+                        }
+                        
+                        egui::Grid::new("sync_stages_grid")
+                            .num_columns(5)
+                            .spacing([16.0, 6.0])
+                            .show(ui, |ui| {
+                                for (stage, status) in tracker.stages() {
+                                    let (icon, color) = match status {
+                                        StageStatus::Finished => ("✓", RethTheme::SUCCESS),
+                                        StageStatus::Running => ("▶", RethTheme::PRIMARY),
+                                        StageStatus::Pending => ("○", RethTheme::TEXT_SECONDARY),
+                                    };
+                                    ui.label(egui::RichText::new(format!("{} {}", icon, stage.name)).color(color));
+                                    
+                                    let fraction = match status {
+                                        StageStatus::Finished => 1.0,
+                                        _ => stage.fraction().unwrap_or(0.0),
+                                    };
+                                    ui.add(egui::ProgressBar::new(fraction as f32)
+                                        .desired_width(200.0)
+                                        .fill(color)
+                                        .text(format!("{:.1}%", fraction * 100.0)));
+                                    
+                                    let blocks = match (stage.checkpoint, stage.target) {
+                                        (Some(checkpoint), Some(target)) => format!("block {} / {}", checkpoint, target),
+                                        (Some(checkpoint), None) => format!("block {}", checkpoint),
+                                        _ => String::new(),
+                                    };
+                                    ui.label(RethTheme::muted_text(&blocks));
+                                    
+                                    let entities = match (stage.entities_processed, stage.entities_total) {
+                                        (Some(processed), Some(total)) => format!("{} / {} entities", processed, total),
+                                        _ => String::new(),
+                                    };
+                                    ui.label(RethTheme::muted_text(&entities));
+                                    
+                                    let eta = match status {
+                                        StageStatus::Running => stage.eta().map(|eta| format!("ETA {}", eta)).unwrap_or_default(),
+                                        _ => String::new(),
+                                    };
+                                    ui.label(RethTheme::muted_text(&eta));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+    }
+    
+    /// Log lines per second split by level or by the busiest targets; clicking a point
+    /// opens the log viewer filtered to the lines around that time
+    fn show_log_rate(&mut self, ui: &mut egui::Ui) {
+        if self.log_rate.is_empty() {
+            return;
+        }
+        
+        const TOP_TARGETS: usize = 5;
+        const TARGET_COLORS: [egui::Color32; TOP_TARGETS] = [
+            RethTheme::PRIMARY,
+            RethTheme::SUCCESS,
+            RethTheme::WARNING,
+            egui::Color32::from_rgb(188, 140, 255),
+            egui::Color32::from_rgb(255, 123, 172),
+        ];
+        
+        let mut clicked_at = None;
+        ui.add_space(20.0);
+        egui::Frame::none()
+            .fill(RethTheme::SURFACE)
+            .rounding(8.0)
+            .inner_margin(16.0)
+            .stroke(egui::Stroke::new(1.0, RethTheme::BORDER))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RethTheme::text("Log Rate"));
+                    ui.add_space(8.0);
+                    ui.selectable_value(&mut self.log_rate_by_target, false, "By level");
+                    ui.selectable_value(&mut self.log_rate_by_target, true, "By target");
+                    
+                    let totals = self.log_rate.level_totals();
+                    for level in [LogLevel::Error, LogLevel::Warn] {
+                        let count = totals.get(&level).copied().unwrap_or(0);
+                        if count > 0 {
+                            ui.label(egui::RichText::new(format!("{} {}", count, level.as_str()))
+                                .color(LogViewer::level_color(level)));
+                        }
+                    }
+                    ui.label(RethTheme::muted_text("last 10 minutes - click to view those lines"));
+                });
+                ui.add_space(6.0);
+                
+                let lines: Vec<Line> = if self.log_rate_by_target {
+                    self.log_rate.top_targets(TOP_TARGETS)
+                        .into_iter()
+                        .zip(TARGET_COLORS)
+                        .map(|((target, _), color)| {
+                            Line::new(PlotPoints::new(self.log_rate.target_series(&target)))
+                                .color(color)
+                                .name(target)
+                        })
+                        .collect()
+                } else {
+                    [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace]
+                        .into_iter()
+                        .filter(|level| self.log_rate.level_totals().contains_key(level))
+                        .map(|level| {
+                            Line::new(PlotPoints::new(self.log_rate.level_series(level)))
+                                .color(LogViewer::level_color(level))
+                                .name(level.as_str())
+                        })
+                        .collect()
+                };
+                
+                Plot::new("log_rate_plot")
+                    .height(140.0)
+                    .include_y(0.0)
+                    .allow_zoom(false)
+                    .allow_drag(false)
+                    .allow_boxed_zoom(false)
+                    .allow_scroll(false)
+                    .show_background(false)
+                    .show_grid([false, false])
+                    .legend(egui_plot::Legend::default().position(egui_plot::Corner::LeftTop))
+                    .label_formatter(|name, value| {
+                        let time = log_rate::time_at_offset(value.x).format("%H:%M:%S");
+                        if name.is_empty() {
+                            time.to_string()
+                        } else {
+                            format!("{}\n{}: {:.0}/s", time, name, value.y)
+                        }
+                    })
+                    .x_axis_formatter(|value, _max_chars, _range| {
+                        let seconds = -value as i32;
+                        if seconds == 0 {
+                            "now".to_string()
+                        } else if seconds % 60 == 0 {
+                            format!("-{}m", seconds / 60)
+                        } else {
+                            String::new()
+                        }
+                    })
+                    .show(ui, |plot_ui| {
+                        for line in lines {
+                            plot_ui.line(line);
+                        }
+                        if plot_ui.response().clicked() {
+                            clicked_at = plot_ui.pointer_coordinate().map(|point| point.x);
+                        }
+                    });
+            });
+        
+        if let Some(offset) = clicked_at {
+            // Ten seconds around the clicked point
+            let at = log_rate::time_at_offset(offset);
+            self.log_viewer.show_time_window(at - chrono::Duration::seconds(5), at + chrono::Duration::seconds(5));
+        }
+    }
+    
     fn show_mockup_metric_card(&self, ui: &mut egui::Ui, metric: &metrics::MetricHistory) -> bool {
         let mut expand_clicked = false;
         
@@ -764,7 +1546,7 @@ impl MyApp {
                     ui.set_min_size(egui::Vec2::new(350.0, 180.0));
                     
                     // Check if we have data
-                    if metric.values.is_empty() {
+                    if !metric.has_data() {
                         // Show "No data" message
                         ui.centered_and_justified(|ui| {
                             ui.label(egui::RichText::new("No data")
@@ -781,9 +1563,11 @@ impl MyApp {
         expand_clicked
     }
     
-    fn show_custom_metric_card(&self, ui: &mut egui::Ui, metric: &metrics::MetricHistory, _metric_key: &str) -> (bool, bool) {
+    /// Returns whether View or Remove was clicked, and the display mode if it was changed
+    fn show_custom_metric_card(&self, ui: &mut egui::Ui, metric: &metrics::MetricHistory, metric_key: &str) -> (bool, bool, Option<metrics::DisplayMode>) {
         let mut expand_clicked = false;
         let mut remove_clicked = false;
+        let mut mode_change = None;
         
         ui.vertical(|ui| {
             // Title outside the box with expand and remove buttons
@@ -818,6 +1602,23 @@ impl MyApp {
                     if ui.add(view_button).on_hover_text("View full history").clicked() {
                         expand_clicked = true;
                     }
+                    
+                    ui.add_space(4.0);
+                    
+                    // Raw value, or its change per second or per poll; summaries and histograms only show quantiles
+                    if metric.distribution.is_some() {
+                        return;
+                    }
+                    egui::ComboBox::from_id_source(("metric_mode", metric_key))
+                        .selected_text(metric.mode.label())
+                        .width(60.0)
+                        .show_ui(ui, |ui| {
+                            for mode in metrics::DisplayMode::ALL {
+                                if ui.selectable_label(metric.mode == mode, mode.label()).clicked() && metric.mode != mode {
+                                    mode_change = Some(mode);
+                                }
+                            }
+                        });
                 });
             });
             
@@ -833,7 +1634,7 @@ impl MyApp {
                     ui.set_min_size(egui::Vec2::new(350.0, 180.0));
                     
                     // Check if we have data
-                    if metric.values.is_empty() {
+                    if !metric.has_data() {
                         // Show "No data" message
                         ui.centered_and_justified(|ui| {
                             ui.label(egui::RichText::new("No data")
@@ -847,7 +1648,89 @@ impl MyApp {
                 });
         });
         
-        (expand_clicked, remove_clicked)
+        (expand_clicked, remove_clicked, mode_change)
+    }
+    
+    /// Label filters and grouping for a metric picked in the selector.
+    /// Returns whether Add or Back was clicked.
+    fn show_metric_draft(&self, ui: &mut egui::Ui, custom: &mut metrics::CustomMetric) -> (bool, bool) {
+        let mut add_clicked = false;
+        let mut back_clicked = false;
+        let available = self.available_metrics.iter().find(|metric| metric.name == custom.name);
+        let labels = available.map(|metric| metric.labels.clone()).unwrap_or_default();
+        
+        ui.label(RethTheme::subheading_text(&custom.name));
+        ui.label("Keep only the series with these label values, or draw one line per value of a label:");
+        ui.separator();
+        
+        egui::Grid::new("metric_label_filters")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                for (label, values) in &labels {
+                    ui.label(label);
+                    let current = custom.matchers.iter().find(|m| &m.label == label).map(|m| m.value.clone());
+                    let mut chosen = current.clone();
+                    egui::ComboBox::from_id_source(("metric_label_filter", label))
+                        .selected_text(current.as_deref().unwrap_or("any"))
+                        .width(260.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut chosen, None, "any");
+                            for value in values {
+                                ui.selectable_value(&mut chosen, Some(value.clone()), value);
+                            }
+                        });
+                    if chosen != current {
+                        custom.matchers.retain(|m| &m.label != label);
+                        if let Some(value) = chosen {
+                            custom.matchers.push(metrics::LabelMatcher { label: label.clone(), value });
+                            custom.matchers.sort_by(|a, b| a.label.cmp(&b.label));
+                            if custom.group_by.as_ref() == Some(label) {
+                                custom.group_by = None;
+                            }
+                        }
+                    }
+                    ui.end_row();
+                }
+                
+                // Summaries and histograms are drawn as quantile bands unless one quantile is picked
+                let is_distribution = available.is_some_and(|metric| metric.is_distribution())
+                    && !custom.matchers.iter().any(|m| m.label == "quantile");
+                if is_distribution {
+                    custom.group_by = None;
+                    ui.label("Shows");
+                    ui.label(RethTheme::muted_text("p50, p90 and p99 bands and the mean"));
+                    ui.end_row();
+                    return;
+                }
+                
+                ui.label("Group by");
+                egui::ComboBox::from_id_source("metric_group_by")
+                    .selected_text(custom.group_by.as_deref().unwrap_or("none"))
+                    .width(260.0)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut custom.group_by, None, "none");
+                        // A label filtered to one value would only give one line
+                        for label in labels.keys().filter(|label| !custom.matchers.iter().any(|m| &m.label == *label)) {
+                            ui.selectable_value(&mut custom.group_by, Some(label.clone()), label);
+                        }
+                    });
+                ui.end_row();
+            });
+        
+        ui.separator();
+        let exists = self.desktop_settings.custom_metrics.iter().any(|existing| existing.key() == custom.key());
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!exists, egui::Button::new("Add")).on_disabled_hover_text("Already on the dashboard").clicked() {
+                add_clicked = true;
+            }
+            if ui.button("Back").clicked() {
+                back_clicked = true;
+            }
+            ui.label(RethTheme::muted_text(&custom.key()));
+        });
+        
+        (add_clicked, back_clicked)
     }
     
     fn show_add_metric_card(&mut self, ui: &mut egui::Ui) {
@@ -930,33 +1813,15 @@ impl MyApp {
     
     fn draw_metric_graph_limited(&self, ui: &mut egui::Ui, metric: &metrics::MetricHistory, max_seconds: usize) {
         // Don't draw anything if there's no data (handled by caller)
-        if metric.values.is_empty() {
+        if !metric.has_data() {
             return;
         }
         
         // Only show the last N data points (max_seconds)
-        let start_idx = metric.values.len().saturating_sub(max_seconds);
-        
-        // Convert metric values to plot points with time on x-axis
-        let points: Vec<[f64; 2]> = metric.values
-            .iter()
-            .skip(start_idx)
-            .enumerate()
-            .map(|(i, value)| {
-                [i as f64, value.value]
-            })
-            .collect();
-        let plot_points = PlotPoints::new(points);
-        
-        // Configure the plot
-        let line = Line::new(plot_points)
-            .color(RethTheme::PRIMARY)
-            .style(egui_plot::LineStyle::Solid)
-            .width(2.0)
-            .fill(0.0); // Fill to y=0
+        let lines = Self::metric_lines(metric, max_seconds);
         
         // Clone the unit to avoid lifetime issues
-        let unit = metric.unit.clone();
+        let unit = metric.display_unit();
         let unit_for_formatter = unit.clone();
         
         // Create the plot with proper axis labels and formatting
@@ -971,9 +1836,9 @@ impl MyApp {
             .allow_scroll(false)
             .show_background(false)
             .y_axis_width(4) // Give more space for y-axis labels
-            .label_formatter(move |_name, value| {
-                // Format hover values
-                match unit.as_str() {
+            .label_formatter(move |name, value| {
+                // Format hover values, naming the line on grouped cards
+                let text = match unit.as_str() {
                     "%" => format!("{:.0}%", value.y),
                     "MB" => format!("{:.0} MB", value.y),
                     "peers" => format!("{:.0} peers", value.y),
@@ -988,7 +1853,8 @@ impl MyApp {
                     },
                     "txs" => format!("{:.0} txs", value.y),
                     _ => format!("{:.1} {}", value.y, unit),
-                }
+                };
+                if name.is_empty() { text } else { format!("{}: {}", name, text) }
             })
             .x_axis_formatter(|value, _max_chars, _range| {
                 // Show time labels - convert from data point index to time
@@ -1037,38 +1903,67 @@ impl MyApp {
                     },
                 }
             });
+        let plot = if metric.groups.is_empty() { plot } else { plot.legend(egui_plot::Legend::default().position(egui_plot::Corner::LeftTop)) };
         
         // Show the plot
         plot.show(ui, |plot_ui| {
-            plot_ui.line(line);
+            for line in lines {
+                plot_ui.line(line);
+            }
         });
     }
     
+    /// Plot lines for a metric's last `max_points` values: its shown series filled in the
+    /// theme color, one named line per label value on grouped cards, or quantile bands
+    /// and a dashed mean for summaries and histograms
+    fn metric_lines(metric: &metrics::MetricHistory, max_points: usize) -> Vec<Line> {
+        let series = metric.lines();
+        let grouped = !metric.groups.is_empty();
+        // Right-align the lines so the newest values of each share an x position
+        let longest = series.iter().map(|(_, values)| values.len().min(max_points)).max().unwrap_or(0);
+        series
+            .into_iter()
+            .map(|(name, values)| {
+                let start_idx = values.len().saturating_sub(max_points);
+                let offset = longest - (values.len() - start_idx);
+                
+                // Convert metric values to plot points with time on x-axis
+                let points: Vec<[f64; 2]> = values
+                    .iter()
+                    .skip(start_idx)
+                    .enumerate()
+                    .map(|(i, value)| [(offset + i) as f64, value.value])
+                    .collect();
+                let line = Line::new(PlotPoints::new(points))
+                    .style(egui_plot::LineStyle::Solid)
+                    .width(2.0);
+                if metric.distribution.is_some() {
+                    // Overlapping fills shade the bands between the quantiles
+                    let line = line.name(name).color(RethTheme::quantile_color(name));
+                    if name == metrics::MEAN_LINE {
+                        line.style(egui_plot::LineStyle::dashed_loose())
+                    } else {
+                        line.fill(0.0)
+                    }
+                } else if grouped {
+                    line.name(name)
+                } else {
+                    line.color(RethTheme::PRIMARY).fill(0.0) // Fill to y=0
+                }
+            })
+            .collect()
+    }
+    
     fn draw_metric_graph(&self, ui: &mut egui::Ui, metric: &metrics::MetricHistory) {
         // Don't draw anything if there's no data (handled by caller)
-        if metric.values.is_empty() {
+        if !metric.has_data() {
             return;
         }
         
-        // Convert metric values to plot points with time on x-axis
-        let points: Vec<[f64; 2]> = metric.values
-            .iter()
-            .enumerate()
-            .map(|(i, value)| {
-                [i as f64, value.value]
-            })
-            .collect();
-        let plot_points = PlotPoints::new(points);
-        
-        // Configure the plot
-        let line = Line::new(plot_points)
-            .color(RethTheme::PRIMARY)
-            .style(egui_plot::LineStyle::Solid)
-            .width(2.0)
-            .fill(0.0); // Fill to y=0
+        let lines = Self::metric_lines(metric, usize::MAX);
         
         // Clone the unit to avoid lifetime issues
-        let unit = metric.unit.clone();
+        let unit = metric.display_unit();
         let unit_for_formatter = unit.clone();
         
         // Create the plot with proper axis labels and formatting
@@ -1083,9 +1978,9 @@ impl MyApp {
             .allow_scroll(false)
             .show_background(false)
             .y_axis_width(4) // Give more space for y-axis labels
-            .label_formatter(move |_name, value| {
-                // Format hover values
-                match unit.as_str() {
+            .label_formatter(move |name, value| {
+                // Format hover values, naming the line on grouped cards
+                let text = match unit.as_str() {
                     "%" => format!("{:.0}%", value.y),
                     "MB" => format!("{:.0} MB", value.y),
                     "peers" => format!("{:.0} peers", value.y),
@@ -1100,7 +1995,8 @@ impl MyApp {
                     },
                     "txs" => format!("{:.0} txs", value.y),
                     _ => format!("{:.1} {}", value.y, unit),
-                }
+                };
+                if name.is_empty() { text } else { format!("{}: {}", name, text) }
             })
             .x_axis_formatter(|value, _max_chars, _range| {
                 // Show time labels - convert from data point index to time
@@ -1149,13 +2045,71 @@ impl MyApp {
                     },
                 }
             });
+        let plot = if metric.groups.is_empty() { plot } else { plot.legend(egui_plot::Legend::default().position(egui_plot::Corner::LeftTop)) };
         
         // Show the plot
         plot.show(ui, |plot_ui| {
-            plot_ui.line(line);
+            for line in lines {
+                plot_ui.line(line);
+            }
         });
     }
     
+    /// Histogram buckets over time: one column per poll, one row per bucket with the smallest
+    /// at the bottom, brighter for more observations
+    fn draw_heatmap(&self, ui: &mut egui::Ui, heatmap: &metrics::Heatmap, unit: &str) {
+        if heatmap.columns.is_empty() || heatmap.bounds.is_empty() {
+            ui.label(RethTheme::muted_text("Waiting for a second poll"));
+            return;
+        }
+        
+        let label_width = 70.0;
+        let (rect, response) = ui.allocate_exact_size(egui::Vec2::new(ui.available_width(), 160.0), egui::Sense::hover());
+        let grid = egui::Rect::from_min_max(egui::Pos2::new(rect.left() + label_width, rect.top()), rect.max);
+        let column_width = grid.width() / heatmap.columns.len() as f32;
+        let row_height = grid.height() / heatmap.bounds.len() as f32;
+        let cell = |column: usize, row: usize| {
+            let min = egui::Pos2::new(grid.left() + column as f32 * column_width, grid.bottom() - (row + 1) as f32 * row_height);
+            egui::Rect::from_min_size(min, egui::Vec2::new(column_width, row_height))
+        };
+        let bucket_label = |row: usize| match heatmap.bounds[row] {
+            bound if bound.is_infinite() => "+Inf".to_string(),
+            bound => format!("≤ {} {}", bound, unit),
+        };
+        
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(grid, 0.0, RethTheme::SURFACE);
+        let max = heatmap.columns.iter().flat_map(|(_, counts)| counts.iter().copied()).fold(0.0, f64::max);
+        for (column, (_, counts)) in heatmap.columns.iter().enumerate() {
+            for (row, count) in counts.iter().enumerate() {
+                if *count > 0.0 && max > 0.0 {
+                    // Square root so rarely hit buckets stay visible next to busy ones
+                    let intensity = (count / max).sqrt() as f32;
+                    painter.rect_filled(cell(column, row), 0.0, RethTheme::PRIMARY.gamma_multiply(intensity.max(0.15)));
+                }
+            }
+        }
+        
+        // Label as many buckets as fit
+        let every = ((14.0 / row_height).ceil() as usize).max(1);
+        for row in (0..heatmap.bounds.len()).step_by(every) {
+            painter.text(
+                egui::Pos2::new(rect.left() + label_width - 6.0, cell(0, row).center().y),
+                egui::Align2::RIGHT_CENTER,
+                bucket_label(row),
+                egui::FontId::proportional(11.0),
+                RethTheme::TEXT_SECONDARY,
+            );
+        }
+        
+        if let Some(pointer) = response.hover_pos().filter(|pointer| grid.contains(*pointer)) {
+            let column = (((pointer.x - grid.left()) / column_width) as usize).min(heatmap.columns.len() - 1);
+            let row = (((grid.bottom() - pointer.y) / row_height) as usize).min(heatmap.bounds.len() - 1);
+            let (at, counts) = &heatmap.columns[column];
+            response.on_hover_text(format!("{}\n{}: {} observations", at.format("%H:%M:%S"), bucket_label(row), counts[row]));
+        }
+    }
+    
     fn show_large_metric_card(&self, ui: &mut egui::Ui, metric: &metrics::MetricHistory, is_primary: bool) {
         let bg_color = if is_primary { RethTheme::PRIMARY.gamma_multiply(0.1) } else { RethTheme::BACKGROUND };
         let border_color = if is_primary { RethTheme::PRIMARY.gamma_multiply(0.3) } else { RethTheme::BORDER };
@@ -1215,16 +2169,16 @@ impl MyApp {
     
     fn draw_large_graph(&self, ui: &mut egui::Ui, metric: &metrics::MetricHistory) {
         // Use egui_plot for large graph with full axis labels
-        let plot_points: PlotPoints = if metric.values.is_empty() {
+        let plot_points: PlotPoints = if metric.series().is_empty() {
             PlotPoints::new(vec![[0.0, 0.0]])
         } else {
             // Convert metric values to plot points with time on x-axis
-            let points: Vec<[f64; 2]> = metric.values
+            let points: Vec<[f64; 2]> = metric.series()
                 .iter()
                 .enumerate()
                 .map(|(i, value)| {
                     // Use seconds ago for x-axis
-                    let seconds_ago = (metric.values.len() - 1 - i) as f64;
+                    let seconds_ago = (metric.series().len() - 1 - i) as f64;
                     [-seconds_ago, value.value]
                 })
                 .collect();
@@ -1239,7 +2193,7 @@ impl MyApp {
             .fill(0.0); // Fill to y=0
         
         // Clone the unit to avoid lifetime issues
-        let unit = metric.unit.clone();
+        let unit = metric.display_unit();
         let unit_for_formatter = unit.clone();
         let unit_for_hover = unit.clone();
         
@@ -1336,13 +2290,14 @@ impl eframe::App for MyApp {
         
         // Update status from installer using try_lock (only if we're actively installing)
         if self.installing {
-            if let Ok(installer) = self.installer.try_lock() {
-                let new_status = installer.status().clone();
+            let installer_status = self.installer.try_lock().ok().map(|installer| installer.status().clone());
+            if let Some(new_status) = installer_status {
                 
                 // Check if installation just completed
                 if matches!(new_status, InstallStatus::Completed) && !matches!(self.install_status, InstallStatus::Completed) {
                     self.is_reth_installed = true;
                     self.was_detected_on_startup = false; // This was a fresh install
+                    self.load_cli_schema(); // The new binary may have different flags
                 }
                 
                 self.install_status = new_status;
@@ -1363,6 +2318,11 @@ impl eframe::App for MyApp {
             }
         }
         
+        // Handle CLI schema results from background task
+        while let Ok(result) = self.cli_schema_receiver.try_recv() {
+            self.handle_cli_schema(result);
+        }
+        
         // Auto-start terminal if we detected an existing Reth process
         if self.detected_existing_process && !matches!(self.install_status, InstallStatus::Running) {
             self.install_status = InstallStatus::Running;
@@ -1372,50 +2332,106 @@ impl eframe::App for MyApp {
         // Process incoming metrics
         while let Ok(metrics_text) = self.metrics_receiver.try_recv() {
             // Update available metrics list
-            self.available_metrics = metrics::RethMetrics::get_available_metrics(&metrics_text);
+            let exposition = Exposition::parse(&metrics_text);
+            self.available_metrics = metrics::RethMetrics::get_available_metrics(&exposition);
             
-            self.metrics.update_from_prometheus_text(&metrics_text);
+            let polled_at = chrono::Local::now();
+            self.metrics.update_from_exposition(&exposition);
             self.metrics.mark_polled();
+            if let Some(store) = &self.metric_store {
+                store.record(polled_at, self.metrics.snapshot(polled_at));
+            }
+            self.last_metrics_text = Some(metrics_text);
+        }
+        
+        if self.log_viewer.take_bundle_request() {
+            self.write_diagnostic_bundle();
         }
         
         // Update Reth node status and collect logs
         if matches!(self.install_status, InstallStatus::Running) {
             self.reth_node.check_process_status();
-            let new_logs = self.reth_node.get_logs();
+            let new_logs = self.read_new_logs(MAX_LOG_LINES_PER_FRAME);
             if !new_logs.is_empty() {
                 println!("Got {} new log lines", new_logs.len());
+                self.archive_logs(&new_logs);
+                self.alert_engine.process(&new_logs);
+                self.metrics.sync_stages.process_logs(&new_logs);
+                self.log_rate.record(&new_logs);
+                self.log_viewer.push(&new_logs);
+                if new_logs.len() == MAX_LOG_LINES_PER_FRAME {
+                    // More are waiting; pick them up next frame instead of stalling this one
+                    ctx.request_repaint();
+                }
             }
-            self.node_logs.extend(new_logs);
-            
-            // Keep only last 1000 logs for performance
-            if self.node_logs.len() > 1000 {
-                self.node_logs.drain(0..self.node_logs.len() - 1000);
-            }
+            self.log_viewer.set_dropped_lines(self.reth_node.log_ring().dropped() + self.missed_log_lines);
             
             // Periodically log the current state for debugging
             let now = std::time::Instant::now();
             if now.duration_since(self.last_debug_log).as_secs() >= 5 {
                 self.last_debug_log = now;
                 println!("Current state - Total logs: {}, Is external: {}, Log path: {:?}", 
-                    self.node_logs.len(), 
+                    self.log_viewer.len(), 
                     self.reth_node.is_monitoring_external(),
                     self.reth_node.get_external_log_path()
                 );
             }
             
             if !self.reth_node.is_running() {
+                // Managed, adopted and external nodes alike stop being scraped once they're gone
+                self.stop_metrics_polling();
+                
+                // Each node run is archived as its own session
+                if let Some(session) = self.log_session.take() {
+                    session.finish();
+                }
+                
                 // If we were monitoring an external process, go back to Completed
                 // If we were running our own process, mark as Stopped
-                if self.reth_node.get_external_log_path().is_some() {
+                if let Some(external) = self.pending_takeover.take() {
+                    println!("External Reth process stopped, relaunching it under management");
+                    self.launch_takeover(&external);
+                } else if self.reth_node.get_external_log_path().is_some() {
                     println!("External Reth process stopped, returning to main interface");
                     self.install_status = InstallStatus::Completed;
                 } else {
                     println!("Managed Reth process stopped");
                     self.install_status = InstallStatus::Stopped;
+                    
+                    // Let the supervisor decide whether a crash should be restarted
+                    if let Some(exit) = self.reth_node.take_last_exit() {
+                        // Persist reports for exits we didn't ask for and know the outcome of
+                        self.exit_report_status = None;
+                        if !exit.stop_requested && !exit.is_status_unknown() {
+                            match exit.save() {
+                                Ok(path) => self.exit_report_status = Some(format!("Saved to {}", path.display())),
+                                Err(e) => eprintln!("Failed to save exit report: {}", e),
+                            }
+                        }
+                        self.last_exit_report = Some(exit.clone());
+                        
+                        if let SupervisorDecision::LockedOut = self.supervisor.handle_exit(exit, &self.desktop_settings.supervisor) {
+                            eprintln!("Reth is crash looping, automatic restarts suspended");
+                        }
+                    }
+                    
+                    // Relaunch with the new parameters if a restart was requested
+                    if self.pending_restart {
+                        self.pending_restart = false;
+                        self.restart_reth();
+                    }
                 }
             }
         }
 
+        // Restart a crashed node once its backoff delay has elapsed
+        if self.supervisor.take_due_restart() && !self.reth_node.is_running() {
+            self.supervisor_restart();
+        }
+        if self.supervisor.time_until_restart().is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(500));
+        }
+
         // Top menu bar
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -1424,6 +2440,10 @@ impl eframe::App for MyApp {
                         self.show_desktop_settings = true;
                         ui.close_menu();
                     }
+                    if ui.button("Alert Rules").clicked() {
+                        self.show_alert_rules = true;
+                        ui.close_menu();
+                    }
                     if ui.button("Node Configuration").clicked() {
                         self.show_settings = true;
                         self.reset_editable_config(); // Reset to current saved state when opening
@@ -1433,12 +2453,7 @@ impl eframe::App for MyApp {
                         self.show_start_config = true;
                         // Load CLI options if they're not already loaded
                         if self.available_cli_options.is_empty() && self.is_reth_installed {
-                            let reth_path = dirs::home_dir()
-                                .unwrap_or_default()
-                                .join(".reth-desktop")
-                                .join("bin")
-                                .join("reth");
-                            self.available_cli_options = RethNode::get_available_cli_options(&reth_path.to_string_lossy());
+                            self.load_cli_schema();
                         }
                         ui.close_menu();
                     }
@@ -1499,6 +2514,26 @@ impl eframe::App for MyApp {
             }
         }
         
+        // Alert Rules window
+        if self.show_alert_rules {
+            let mut open = true;
+            let mut rules_changed = false;
+            egui::Window::new("Log Alert Rules")
+                .resizable(true)
+                .default_width(520.0)
+                .default_height(420.0)
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    rules_changed = AlertRulesWindow::show_content(ui, &mut self.desktop_settings, self.alert_engine.errors());
+                });
+            if rules_changed {
+                self.alert_engine.set_rules(self.desktop_settings.alerts.active_rules());
+            }
+            if !open {
+                self.show_alert_rules = false;
+            }
+        }
+        
         // Node Settings window
         if self.show_settings {
             let mut open = true;
@@ -1541,6 +2576,7 @@ impl eframe::App for MyApp {
                         &mut self.parameter_value,
                         &mut self.selected_values,
                         &mut self.pending_launch_args,
+                        &mut self.cli_schema_diff,
                     );
                 });
             if !open {
@@ -1548,33 +2584,20 @@ impl eframe::App for MyApp {
             }
             
             // Handle restart request
-            if restart_requested {
-                if self.reth_node.is_running() {
-                    // Stop the current node
-                    if let Err(e) = self.reth_node.stop() {
-                        eprintln!("Failed to stop Reth node: {}", e);
-                    } else {
-                        self.install_status = InstallStatus::Stopped;
-                        self.stop_metrics_polling();
-                        
-                        // Start the node again with new parameters
-                        let reth_path = dirs::home_dir()
-                            .unwrap_or_default()
-                            .join(".reth-desktop")
-                            .join("bin")
-                            .join("reth");
-                        
-                        match self.reth_node.start(&reth_path.to_string_lossy(), &self.pending_launch_args, &self.desktop_settings) {
-                            Ok(()) => {
-                                self.install_status = InstallStatus::Running;
-                                self.pending_launch_args.clear();
-                                self.start_metrics_polling();
-                            }
-                            Err(e) => {
-                                self.install_status = InstallStatus::Error(format!("Failed to restart Reth: {}", e));
-                            }
-                        }
-                    }
+            if restart_requested && self.reth_node.is_running() {
+                // Stop the current node gracefully; it is started again once it has exited
+                if let Err(e) = self.reth_node.request_stop() {
+                    eprintln!("Failed to stop Reth node: {}", e);
+                    self.stop_error = Some(e.to_string());
+                } else if self.reth_node.is_running() {
+                    self.stop_error = None;
+                    self.stop_metrics_polling();
+                    self.pending_restart = true;
+                } else {
+                    // External processes are released immediately
+                    self.stop_error = None;
+                    self.install_status = InstallStatus::Stopped;
+                    self.restart_reth();
                 }
             }
         }
@@ -1746,19 +2769,17 @@ impl eframe::App for MyApp {
                                 if let Some(selected) = self.selected_cli_option {
                                     if let Some(option) = self.available_cli_options.get(selected) {
                                         // Add the parameter
-                                        if option.takes_value {
-                                            if !self.parameter_value.is_empty() {
-                                                self.desktop_settings.custom_launch_args.push(option.name.clone());
-                                                self.desktop_settings.custom_launch_args.push(self.parameter_value.clone());
-                                                // Also add to pending list for immediate display
-                                                self.pending_launch_args.push(option.name.clone());
-                                                self.pending_launch_args.push(self.parameter_value.clone());
-                                            }
+                                        let new_arg = if option.takes_value {
+                                            let values = LaunchArg::parse_values(&self.parameter_value, Some(option));
+                                            if values.is_empty() { None } else { Some(LaunchArg::for_option(option, values)) }
                                         } else {
                                             // Flag parameter - just add the name
-                                            self.desktop_settings.custom_launch_args.push(option.name.clone());
+                                            Some(LaunchArg::flag(option.name.clone()))
+                                        };
+                                        if let Some(new_arg) = new_arg {
+                                            self.desktop_settings.custom_launch_args.push(new_arg.clone());
                                             // Also add to pending list for immediate display
-                                            self.pending_launch_args.push(option.name.clone());
+                                            self.pending_launch_args.push(new_arg);
                                         }
                                         
                                         // Save settings
@@ -1789,11 +2810,12 @@ impl eframe::App for MyApp {
         // Metric selector window
         if self.show_metric_selector {
             let mut open = true;
-            let mut selected_metric: Option<String> = None;
+            let mut selected_metric: Option<metrics::CustomMetric> = None;
+            let mut draft = self.metric_draft.take();
             
             // Fetch available metrics if we haven't already
             if self.available_metrics.is_empty() {
-                let metrics_endpoint = format!("http://{}/debug/metrics/prometheus", self.desktop_settings.reth_defaults.metrics_address);
+                let metrics_endpoint = format!("http://{}/debug/metrics/prometheus", self.metrics_address());
                 if let Ok(metrics_text) = std::process::Command::new("curl")
                     .arg("-s")
                     .arg(&metrics_endpoint)
@@ -1801,7 +2823,7 @@ impl eframe::App for MyApp {
                 {
                     if metrics_text.status.success() {
                         if let Ok(text) = String::from_utf8(metrics_text.stdout) {
-                            self.available_metrics = metrics::RethMetrics::get_available_metrics(&text);
+                            self.available_metrics = metrics::RethMetrics::get_available_metrics(&Exposition::parse(&text));
                         }
                     }
                 }
@@ -1813,6 +2835,18 @@ impl eframe::App for MyApp {
                 .default_height(500.0)
                 .open(&mut open)
                 .show(ctx, |ui| {
+                    // Labelled metrics get a second step to pick filters and grouping
+                    if let Some(custom) = &mut draft {
+                        let (add_clicked, back_clicked) = self.show_metric_draft(ui, custom);
+                        if add_clicked {
+                            selected_metric = Some(custom.clone());
+                        }
+                        if back_clicked {
+                            draft = None;
+                        }
+                        return;
+                    }
+                    
                     ui.label("Select a metric from the list below:");
                     ui.separator();
                     
@@ -1839,14 +2873,15 @@ impl eframe::App for MyApp {
                     egui::ScrollArea::vertical()
                         .max_height(400.0)
                         .show(ui, |ui| {
-                            for metric_name in &self.available_metrics {
+                            for metric in &self.available_metrics {
+                                let metric_name = &metric.name;
                                 // Filter by search text
                                 if !search_text.is_empty() && !metric_name.to_lowercase().contains(&search_text.to_lowercase()) {
                                     continue;
                                 }
                                 
-                                // Skip metrics we already have
-                                if self.desktop_settings.custom_metrics.contains(metric_name) {
+                                // Skip metrics we already have; labelled ones can be added again with other filters
+                                if metric.labels.is_empty() && self.desktop_settings.custom_metrics.iter().any(|custom| &custom.key() == metric_name) {
                                     continue;
                                 }
                                 
@@ -1860,22 +2895,44 @@ impl eframe::App for MyApp {
                                     continue;
                                 }
                                 
-                                if ui.selectable_label(false, metric_name).clicked() {
-                                    selected_metric = Some(metric_name.clone());
+                                let response = ui.horizontal(|ui| {
+                                    let response = ui.selectable_label(false, metric_name);
+                                    ui.label(RethTheme::muted_text(metric.metric_type.as_str()));
+                                    if !metric.labels.is_empty() {
+                                        let labels: Vec<&str> = metric.labels.keys().map(String::as_str).collect();
+                                        ui.label(RethTheme::muted_text(&format!("by {}", labels.join(", "))));
+                                    }
+                                    response
+                                }).inner;
+                                let mut hover = match &metric.help {
+                                    Some(help) if metric.family != *metric_name => format!("{}\nPart of {} {}", help, metric.metric_type.as_str(), metric.family),
+                                    Some(help) => help.clone(),
+                                    None => format!("{} {}", metric.metric_type.as_str(), metric.family),
+                                };
+                                if metric.is_distribution() {
+                                    hover.push_str("\nShown as p50, p90 and p99 bands and the mean");
+                                }
+                                if response.on_hover_text(hover).clicked() {
+                                    if metric.labels.is_empty() {
+                                        selected_metric = Some(metrics::CustomMetric::new(metric_name.clone()));
+                                    } else {
+                                        draft = Some(metrics::CustomMetric::new(metric_name.clone()));
+                                    }
                                 }
                             }
                         });
                 });
                 
+            self.metric_draft = if open { draft } else { None };
             if !open {
                 self.show_metric_selector = false;
                 ctx.data_mut(|d| d.remove::<String>(egui::Id::new("metric_search_text")));
             }
             
             // Add the selected metric
-            if let Some(metric_name) = selected_metric {
-                self.desktop_settings.custom_metrics.push(metric_name.clone());
-                self.metrics.add_custom_metric(metric_name);
+            if let Some(custom) = selected_metric {
+                self.metrics.add_custom_metric(&custom);
+                self.desktop_settings.custom_metrics.push(custom);
                 
                 // Save settings
                 if let Err(e) = DesktopSettingsManager::save_desktop_settings(&self.desktop_settings) {
@@ -1883,6 +2940,7 @@ impl eframe::App for MyApp {
                 }
                 
                 self.show_metric_selector = false;
+                self.metric_draft = None;
                 ctx.data_mut(|d| d.remove::<String>(egui::Id::new("metric_search_text")));
             }
         }
@@ -1891,38 +2949,19 @@ impl eframe::App for MyApp {
         if let Some(metric_name) = &self.expanded_metric.clone() {
             let mut open = true;
             
-            // Check default metrics first
-            let metric = match metric_name.as_str() {
-                "Connected Peers" => Some(&self.metrics.peers_connected),
-                "Block Height" => Some(&self.metrics.block_height),
-                "Sync Progress" => Some(&self.metrics.sync_progress),
-                "Memory Usage" => Some(&self.metrics.memory_usage),
-                "Active Downloads" => Some(&self.metrics.disk_io),
-                _ => {
-                    // Check custom metrics
-                    self.metrics.custom_metrics.get(metric_name)
-                }
-            };
+            // Check default metrics first, then custom metrics
+            let metric = self.metrics.default_cards()
+                .into_iter()
+                .find(|metric| &metric.name == metric_name)
+                .or_else(|| self.metrics.custom_metrics.get(metric_name));
             
             if let Some(metric) = metric {
-                let display_name = if metric_name.contains('_') {
-                    // For custom metrics, display a nicer name
-                    metric_name.replace('_', " ")
-                        .split_whitespace()
-                        .map(|word| {
-                            let mut chars = word.chars();
-                            match chars.next() {
-                                None => String::new(),
-                                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str()
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                        .join(" ")
-                } else {
-                    metric_name.clone()
-                };
+                let grouped = metric.distribution.is_some() || self.desktop_settings.custom_metrics
+                    .iter()
+                    .any(|custom| &custom.key() == metric_name && custom.group_by.is_some());
+                let mut range_view = std::mem::take(&mut self.metric_range);
                 
-                egui::Window::new(&format!("{} - Full History", display_name))
+                egui::Window::new(&format!("{} - Full History", metric.name))
                     .resizable(true)
                     .default_width(900.0)
                     .default_height(600.0)
@@ -1931,23 +2970,41 @@ impl eframe::App for MyApp {
                         // Show current value
                         if let Some(current) = metric.get_latest() {
                             let unit_display = if metric.unit == "bytes" {
-                                "MB"
+                                "MB".to_string()
                             } else {
-                                &metric.unit
+                                metric.display_unit()
                             };
                             ui.heading(format!("Current: {:.2} {}", current, unit_display));
                         }
+                        range_view.show_range_picker(ui);
                         ui.separator();
                         
-                        // Show the full graph
+                        if !range_view.is_live() {
+                            // History from the metric store
+                            ui.vertical(|ui| {
+                                ui.set_height(500.0);
+                                range_view.show_stored(ui, metric, metric_name, grouped);
+                            });
+                            return;
+                        }
+                        
+                        // Show the full graph, above the bucket heatmap for histograms
+                        let heatmap = metric.distribution.as_ref().and_then(|distribution| distribution.heatmap.as_ref());
                         ui.vertical(|ui| {
-                            ui.set_height(500.0);
+                            ui.set_height(if heatmap.is_some() { 320.0 } else { 500.0 });
                             self.draw_metric_graph(ui, metric);
                         });
+                        if let Some(heatmap) = heatmap {
+                            ui.separator();
+                            ui.label(RethTheme::muted_text("Observations per bucket"));
+                            self.draw_heatmap(ui, heatmap, &metric.unit);
+                        }
                         
                         ui.separator();
-                        ui.label(format!("Showing {} data points (up to 10 minutes)", metric.values.len()));
+                        let data_points = metric.lines().iter().map(|(_, series)| series.len()).max().unwrap_or(0);
+                        ui.label(format!("Showing {} data points (up to 10 minutes)", data_points));
                     });
+                self.metric_range = range_view;
                     
                 if !open {
                     self.expanded_metric = None;
@@ -1995,7 +3052,24 @@ impl eframe::App for MyApp {
                             ui.add_space(20.0);
                             
                             // Status indicator and controls
-                            if self.reth_node.is_running() {
+                            if self.reth_node.is_stopping() {
+                                ui.add_enabled(false, egui::Button::new(egui::RichText::new("Stop")
+                                    .color(egui::Color32::WHITE))
+                                    .fill(RethTheme::ERROR.gamma_multiply(0.5))
+                                    .rounding(6.0)
+                                    .min_size(egui::Vec2::new(60.0, 32.0)));
+                                
+                                ui.add_space(12.0);
+                                
+                                ui.horizontal(|ui| {
+                                    ui.add(egui::widgets::Spinner::new().size(12.0).color(RethTheme::WARNING));
+                                    ui.add_space(8.0);
+                                    ui.label(egui::RichText::new("Stopping…")
+                                        .size(14.0)
+                                        .color(RethTheme::WARNING)
+                                        .strong());
+                                });
+                            } else if self.reth_node.is_running() {
                                 if ui.add(egui::Button::new(egui::RichText::new("Stop")
                                     .color(egui::Color32::WHITE))
                                     .fill(RethTheme::ERROR)
@@ -2005,6 +3079,29 @@ impl eframe::App for MyApp {
                                     self.stop_reth();
                                 }
                                 
+                                if let Some(error) = &self.stop_error {
+                                    ui.add_space(8.0);
+                                    ui.label(egui::RichText::new("Stop failed")
+                                        .size(12.0)
+                                        .color(RethTheme::ERROR))
+                                        .on_hover_text(error);
+                                }
+                                
+                                // External nodes with a known PID can be taken over by the desktop
+                                if self.reth_node.is_monitoring_external() && self.reth_node.can_stop() {
+                                    ui.add_space(8.0);
+                                    if ui.add(egui::Button::new(egui::RichText::new("Restart under management")
+                                        .color(RethTheme::TEXT_PRIMARY))
+                                        .fill(RethTheme::SURFACE)
+                                        .stroke(egui::Stroke::new(1.0, RethTheme::BORDER))
+                                        .rounding(6.0)
+                                        .min_size(egui::Vec2::new(60.0, 32.0)))
+                                        .on_hover_text("Stop this node and relaunch the same command from the desktop, adding its log and metrics flags")
+                                        .clicked() {
+                                        self.restart_under_management();
+                                    }
+                                }
+                                
                                 ui.add_space(12.0);
                                 
                                 ui.horizontal(|ui| {
@@ -2016,18 +3113,28 @@ impl eframe::App for MyApp {
                                         .strong());
                                 });
                             } else {
-                                if ui.add(egui::Button::new(egui::RichText::new("Start")
+                                // Launch parameter errors must be fixed in Start Config before starting
+                                let preflight = self.preflight_report();
+                                let start_response = ui.add_enabled(!preflight.has_errors(), egui::Button::new(egui::RichText::new("Start")
                                     .color(egui::Color32::WHITE))
                                     .fill(RethTheme::SUCCESS)
                                     .rounding(6.0)
-                                    .min_size(egui::Vec2::new(60.0, 32.0)))
-                                    .clicked() {
+                                    .min_size(egui::Vec2::new(60.0, 32.0)));
+                                let start_response = if preflight.has_errors() {
+                                    start_response.on_disabled_hover_text(format!("{} launch parameter error(s), see Start Config", preflight.error_count()))
+                                } else {
+                                    start_response
+                                };
+                                if start_response.clicked() {
+                                    // Starting manually clears any crash-loop lockout
+                                    self.supervisor.reset();
                                     let reth_path = dirs::home_dir()
                                         .unwrap_or_default()
                                         .join(".reth-desktop")
                                         .join("bin")
                                         .join("reth");
-                                    match self.reth_node.start(&reth_path.to_string_lossy(), &self.pending_launch_args, &self.desktop_settings) {
+                                    let custom_args = self.custom_launch_argv();
+                                    match self.reth_node.start(&reth_path.to_string_lossy(), &custom_args, &self.desktop_settings) {
                                         Ok(()) => {
                                             self.install_status = InstallStatus::Running;
                                             // Clear pending args since they've been applied
@@ -2238,16 +3345,23 @@ impl eframe::App for MyApp {
                         ctx.request_repaint_after(std::time::Duration::from_millis(100));
                     }
                     InstallStatus::Running => {
-                        // Show metrics section
+                        // Show crash and restart information from the supervisor
                         ui.set_max_width(max_width);
+                        self.show_supervisor_status(ui);
+                        self.show_last_run_panel(ui);
+                        self.show_alerts(ui);
+                        
+                        // Show metrics section
                         self.show_metrics_section(ui);
+                        self.show_sync_stages(ui);
+                        self.show_log_rate(ui);
                         
                         ui.add_space(12.0);
                         
-                        // Command Terminal section matching mockup
+                        // Command terminal section
                         ui.add_space(20.0);
                         
-                        // Terminal output matching mockup style
+                        // Terminal output, or the startup state until the node logs its first line
                         let _available_rect = ui.available_rect_before_wrap();
                         let terminal_height = 300.0; // Increased height for better visibility
                         
@@ -2258,74 +3372,30 @@ impl eframe::App for MyApp {
                             .stroke(egui::Stroke::new(1.0, RethTheme::BORDER))
                             .show(ui, |ui| {
                                         ui.set_min_height(terminal_height);
-                                        // Add both vertical and horizontal scroll areas
-                                        egui::ScrollArea::both()
-                                            .max_height(terminal_height)
-                                            .auto_shrink([false; 2])
-                                            .stick_to_bottom(true)
-                                            .show(ui, |ui| {
-                                                // Use a vertical layout with left alignment
-                                                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-                                                    // Show recent log lines or sample data if no logs
-                                                    if self.node_logs.is_empty() {
-                                                        // Show sample terminal output like in mockup
-                                                        let sample_logs = vec![
-                                                            "13:31:05 INFO Status connected_peers=4 latest_block=4",
-                                                            "13:31:10 INFO Status connected_peers=4 latest_block=4", 
-                                                            "13:31:15 INFO Status connected_peers=4 latest_block=4",
-                                                            "13:31:20 INFO Very long log line that demonstrates horizontal scrolling capability when terminal output exceeds the visible width of the terminal window area and maintains proper left alignment"
-                                                        ];
-                                                        
-                                                        for log in sample_logs {
-                                                            // Disable wrapping for each line
-                                                            ui.style_mut().wrap = Some(false);
-                                                            ui.label(egui::RichText::new(log)
-                                                                .size(12.0)
-                                                                .color(egui::Color32::from_rgb(255, 193, 7)) // Orange like in mockup
-                                                                .monospace());
-                                                        }
-                                                    } else {
-                                                        // Show actual log lines - clean and left-aligned
-                                                        let logs_to_show: Vec<_> = self.node_logs.iter().rev().take(40).collect();
-                                                        
-                                                        for log_line in logs_to_show.into_iter().rev() {
-                                                            // Clean the log content to remove ANSI escape codes
-                                                            let cleaned_content = Self::clean_log_content(&log_line.content);
-                                                            
-                                                            // Format: timestamp + cleaned content
-                                                            let formatted_line = format!("{} {}", 
-                                                                log_line.timestamp.split(' ').next().unwrap_or(""),
-                                                                cleaned_content
-                                                            );
-                                                            
-                                                            let color = match log_line.level {
-                                                                LogLevel::Error => egui::Color32::from_rgb(255, 100, 100),
-                                                                LogLevel::Warn => egui::Color32::from_rgb(255, 200, 100),
-                                                                LogLevel::Info => egui::Color32::from_rgb(255, 193, 7), // Orange like mockup
-                                                                LogLevel::Debug => egui::Color32::from_rgb(150, 150, 255),
-                                                                LogLevel::Trace => egui::Color32::GRAY,
-                                                            };
-                                                            
-                                                            // Disable wrapping for each line
-                                                            ui.style_mut().wrap = Some(false);
-                                                            ui.label(egui::RichText::new(&formatted_line)
-                                                                .size(12.0)
-                                                                .color(color)
-                                                                .monospace());
-                                                        }
-                                                    }
-                                                });
-                                            });
+                                        // The viewer keeps earlier runs, so look for a line from this one
+                                        if self.log_cursor > self.reth_node.run_first_seq() || self.log_viewer.is_viewing_session() {
+                                            self.log_viewer.show(ui, terminal_height);
+                                            return;
+                                        }
+                                        
+                                        self.show_waiting_for_output(ui);
                                     });
                         
                         // Auto-refresh for live updates
                         ctx.request_repaint_after(std::time::Duration::from_millis(500));
                     }
                     InstallStatus::Completed => {
-                        // Reth is installed and ready - no UI needed, use header controls
+                        // Reth is installed and ready - use header controls, with past output available
+                        ui.set_max_width(max_width);
+                        self.show_log_history(ui);
                     }
                     InstallStatus::Stopped => {
-                        // Reth is stopped - no UI needed, use header controls  
+                        // Reth is stopped - use header controls, but surface any crash information
+                        ui.set_max_width(max_width);
+                        self.show_supervisor_status(ui);
+                        self.show_last_run_panel(ui);
+                        self.show_alerts(ui);
+                        self.show_log_history(ui);
                     }
                     InstallStatus::Error(error) => {
                         let error_message = error.clone();
@@ -2361,17 +3431,32 @@ impl eframe::App for MyApp {
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         // Handle application shutdown based on settings
         if self.reth_node.is_running() {
-            if self.desktop_settings.keep_reth_running_in_background {
+            if self.reth_node.is_detached() {
+                println!("Leaving detached Reth process {} running in background", self.reth_node.managed_pid().unwrap_or_default());
+                // Don't stop the process - it will be re-adopted on the next launch
+            } else if self.desktop_settings.keep_reth_running_in_background {
                 println!("Keeping Reth running in background (setting enabled)");
-                // Don't stop the process - let it continue running
+                // Started before the setting was enabled, so its output pipes close with the app
+                eprintln!("Warning: Reth was not started detached and may stop when its output pipes close");
             } else {
                 println!("Stopping Reth on application exit (setting disabled)");
+                // Graceful shutdown: SIGTERM, wait for the grace period, then kill
                 if let Err(e) = self.reth_node.stop() {
                     eprintln!("Error stopping Reth on exit: {}", e);
                 }
             }
         }
         
+        // Archive the last output and wait for the session to be written out
+        let remaining = self.read_new_logs(usize::MAX);
+        if let Some(session) = &self.log_session {
+            session.write(&remaining);
+        }
+        self.log_session = None;
+        
+        // Write out the metric averages still being built
+        self.metric_store = None;
+        
         // Save desktop settings before closing
         if let Err(e) = DesktopSettingsManager::save_desktop_settings(&self.desktop_settings) {
             eprintln!("Failed to save desktop settings on exit: {}", e);
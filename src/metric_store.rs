@@ -0,0 +1,428 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// A resolution the store keeps and for how long, all in seconds
+struct Tier {
+    name: &'static str,
+    resolution: i64,
+    retention: i64,
+    segment_span: i64, // Data per segment file
+}
+
+/// Every poll for an hour, then one-minute averages for a week and ten-minute averages for 90 days
+static TIERS: [Tier; 3] = [
+    Tier { name: "1s", resolution: 1, retention: 3600, segment_span: 3600 },
+    Tier { name: "1m", resolution: 60, retention: 7 * 86400, segment_span: 86400 },
+    Tier { name: "10m", resolution: 600, retention: 90 * 86400, segment_span: 86400 },
+];
+
+/// One line of a segment: every series recorded at a moment
+#[derive(Serialize, Deserialize)]
+struct StoredPoint {
+    t: i64, // Unix seconds
+    v: HashMap<String, f64>,
+}
+
+/// Series id of one label value's line on a grouped card
+pub fn group_series_id(series: &str, group: &str) -> String {
+    format!("{}/{}", series, group)
+}
+
+/// Unix seconds and value of each series, oldest first
+type SeriesPoints = BTreeMap<String, Vec<[f64; 2]>>;
+
+/// Points read back from the store
+pub struct StoredRange {
+    pub resolution: Duration,
+    pub series: BTreeMap<String, Vec<[f64; 2]>>, // Unix seconds and value, oldest first
+}
+
+enum StoreCommand {
+    Record(DateTime<Local>, Vec<(String, f64)>),
+    Finish,
+}
+
+/// On-disk metric history under ~/.reth-desktop/metrics/, one directory of append-only
+/// JSON lines segments per resolution. Values are written on a background thread.
+pub struct MetricStore {
+    sender: mpsc::Sender<StoreCommand>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MetricStore {
+    pub fn dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".reth-desktop")
+            .join("metrics")
+    }
+
+    fn tier_dir(dir: &Path, tier: &Tier) -> PathBuf {
+        dir.join(tier.name)
+    }
+
+    /// Start the writer thread
+    pub fn open() -> Self {
+        Self::open_in(Self::dir())
+    }
+
+    /// Start the writer thread for a store kept in `dir`
+    fn open_in(dir: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel::<StoreCommand>();
+        let handle = std::thread::spawn(move || {
+            let mut tiers: Vec<TierWriter> = TIERS.iter().map(|tier| TierWriter::new(dir.clone(), tier)).collect();
+            while let Ok(command) = receiver.recv() {
+                match command {
+                    StoreCommand::Record(at, values) => {
+                        for tier in &mut tiers {
+                            if let Err(e) = tier.add(at.timestamp(), &values) {
+                                eprintln!("Failed to write {} metric history: {}", tier.tier.name, e);
+                            }
+                        }
+                    }
+                    StoreCommand::Finish => break,
+                }
+            }
+            // Keep the partly averaged minutes, so short runs still show up in the long ranges
+            for tier in &mut tiers {
+                if let Err(e) = tier.flush() {
+                    eprintln!("Failed to write {} metric history: {}", tier.tier.name, e);
+                }
+            }
+        });
+        Self { sender, handle: Some(handle) }
+    }
+
+    /// Record the values of one poll, keyed by series id
+    pub fn record(&self, at: DateTime<Local>, values: Vec<(String, f64)>) {
+        // JSON has no NaN or infinity
+        let values: Vec<(String, f64)> = values.into_iter().filter(|(_, value)| value.is_finite()).collect();
+        if !values.is_empty() {
+            let _ = self.sender.send(StoreCommand::Record(at, values));
+        }
+    }
+
+    /// Segment files of a tier and the time they start at, oldest first
+    fn segments(dir: &Path, tier: &Tier) -> Vec<(i64, PathBuf)> {
+        let mut segments: Vec<(i64, PathBuf)> = std::fs::read_dir(Self::tier_dir(dir, tier))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter_map(|path| {
+                        let start = path.file_name()?.to_str()?.strip_suffix(".jsonl")?.parse().ok()?;
+                        Some((start, path))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        segments.sort();
+        segments
+    }
+
+    /// The series `wanted` accepts between `from` and `to`, from the finest resolution
+    /// that still reaches back to `from`
+    pub fn query(
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        wanted: impl Fn(&str) -> bool,
+    ) -> Result<StoredRange, Box<dyn std::error::Error>> {
+        let (from, to) = (from.timestamp(), to.timestamp());
+        let tier = tier_for_age(Local::now().timestamp() - from);
+        let series = Self::read(&Self::dir(), tier, from, to, wanted)?;
+        Ok(StoredRange { resolution: Duration::from_secs(tier.resolution as u64), series })
+    }
+
+    /// Points of one tier between unix seconds `from` and `to`, oldest first
+    fn read(
+        dir: &Path,
+        tier: &Tier,
+        from: i64,
+        to: i64,
+        wanted: impl Fn(&str) -> bool,
+    ) -> Result<SeriesPoints, Box<dyn std::error::Error>> {
+        let mut series = SeriesPoints::new();
+        for (start, path) in Self::segments(dir, tier) {
+            if start + tier.segment_span <= from || start > to {
+                continue;
+            }
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                // A line cut short by a crash is skipped
+                let Ok(point) = serde_json::from_str::<StoredPoint>(&line) else {
+                    continue;
+                };
+                if point.t < from || point.t > to {
+                    continue;
+                }
+                for (name, value) in point.v {
+                    if wanted(&name) {
+                        series.entry(name).or_default().push([point.t as f64, value]);
+                    }
+                }
+            }
+        }
+        for points in series.values_mut() {
+            points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+            merge_same_time(points);
+        }
+        Ok(series)
+    }
+}
+
+/// The finest tier that still keeps data `age` seconds old
+fn tier_for_age(age: i64) -> &'static Tier {
+    TIERS.iter().find(|tier| age <= tier.retention).unwrap_or(&TIERS[TIERS.len() - 1])
+}
+
+/// Average points that share a timestamp into one. A step flushed at exit is written again
+/// when the app restarts within it, and two values at one time would break rates and deltas.
+fn merge_same_time(points: &mut Vec<[f64; 2]>) {
+    let mut merged: Vec<[f64; 2]> = Vec::with_capacity(points.len());
+    let mut count = 0.0;
+    for [t, value] in points.drain(..) {
+        match merged.last_mut() {
+            Some(last) if last[0] == t => {
+                count += 1.0;
+                last[1] += (value - last[1]) / count;
+            }
+            _ => {
+                merged.push([t, value]);
+                count = 1.0;
+            }
+        }
+    }
+    *points = merged;
+}
+
+impl Drop for MetricStore {
+    fn drop(&mut self) {
+        // Wait so the averages being built are written out
+        if let Some(handle) = self.handle.take() {
+            let _ = self.sender.send(StoreCommand::Finish);
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Sum and count of each series' values within a resolution step
+type StepSums = HashMap<String, (f64, u32)>;
+
+/// Averages values into one point per resolution step and appends them to the tier's segments
+struct TierWriter {
+    dir: PathBuf, // Store directory, holding one directory per tier
+    tier: &'static Tier,
+    bucket: Option<(i64, StepSums)>, // Start of the step being averaged
+    segment: Option<(i64, File)>,
+}
+
+impl TierWriter {
+    fn new(dir: PathBuf, tier: &'static Tier) -> Self {
+        Self { dir, tier, bucket: None, segment: None }
+    }
+
+    fn add(&mut self, at: i64, values: &[(String, f64)]) -> Result<(), Box<dyn std::error::Error>> {
+        let bucket_start = at.div_euclid(self.tier.resolution) * self.tier.resolution;
+        if self.bucket.as_ref().is_some_and(|(start, _)| *start != bucket_start) {
+            self.flush()?;
+        }
+        let (_, sums) = self.bucket.get_or_insert_with(|| (bucket_start, HashMap::new()));
+        for (name, value) in values {
+            let (sum, count) = sums.entry(name.clone()).or_insert((0.0, 0));
+            *sum += value;
+            *count += 1;
+        }
+        Ok(())
+    }
+
+    /// Write the averages of the current step
+    fn flush(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some((start, sums)) = self.bucket.take() else {
+            return Ok(());
+        };
+        let point = StoredPoint {
+            t: start,
+            v: sums.into_iter().map(|(name, (sum, count))| (name, sum / count as f64)).collect(),
+        };
+        let mut line = serde_json::to_string(&point)?;
+        line.push('\n');
+        self.open_segment(start)?;
+        if let Some((_, file)) = &mut self.segment {
+            file.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Make the segment covering `at` the open one, pruning expired segments when a new span starts
+    fn open_segment(&mut self, at: i64) -> Result<(), Box<dyn std::error::Error>> {
+        let segment_start = at.div_euclid(self.tier.segment_span) * self.tier.segment_span;
+        if self.segment.as_ref().is_some_and(|(start, _)| *start == segment_start) {
+            return Ok(());
+        }
+
+        let tier_dir = MetricStore::tier_dir(&self.dir, self.tier);
+        std::fs::create_dir_all(&tier_dir)?;
+        let path = tier_dir.join(format!("{}.jsonl", segment_start));
+        let mut file = OpenOptions::new().create(true).read(true).append(true).open(&path)?;
+        // Start on a fresh line if the last run was cut off mid-line
+        if file.metadata()?.len() > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+            }
+        }
+        self.segment = Some((segment_start, file));
+        self.prune(at);
+        Ok(())
+    }
+
+    /// Remove segments whose newest data is older than the retention
+    fn prune(&self, now: i64) {
+        for (start, path) in MetricStore::segments(&self.dir, self.tier) {
+            if start + self.tier.segment_span + self.tier.retention < now {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    eprintln!("Failed to remove expired metric history {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn tier(name: &str) -> &'static Tier {
+        TIERS.iter().find(|tier| tier.name == name).unwrap()
+    }
+
+    fn segment_starts(dir: &Path, tier: &Tier) -> Vec<i64> {
+        MetricStore::segments(dir, tier).into_iter().map(|(start, _)| start).collect()
+    }
+
+    fn value(name: &str, value: f64) -> Vec<(String, f64)> {
+        vec![(name.to_string(), value)]
+    }
+
+    #[test]
+    fn picks_the_finest_tier_that_reaches_back_far_enough() {
+        assert_eq!(tier_for_age(0).name, "1s");
+        assert_eq!(tier_for_age(3600).name, "1s");
+        assert_eq!(tier_for_age(3601).name, "1m");
+        assert_eq!(tier_for_age(7 * 86400).name, "1m");
+        assert_eq!(tier_for_age(7 * 86400 + 1).name, "10m");
+        assert_eq!(tier_for_age(90 * 86400).name, "10m");
+        // Older than anything kept: the coarsest tier still answers with what it has
+        assert_eq!(tier_for_age(365 * 86400).name, "10m");
+    }
+
+    #[test]
+    fn downsamples_polls_into_the_coarser_tiers() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = 1_700_000_400; // A multiple of ten minutes
+        {
+            let store = MetricStore::open_in(dir.path().to_path_buf());
+            for i in 0..120 {
+                let mut values = value("peers", i as f64);
+                values.push(("bad".to_string(), f64::NAN));
+                store.record(Local.timestamp_opt(start + i, 0).unwrap(), values);
+            }
+            // Dropping the store writes out the steps still being averaged
+        }
+        let read = |name: &str| MetricStore::read(dir.path(), tier(name), start, start + 600, |_| true).unwrap();
+
+        let seconds = read("1s");
+        assert_eq!(seconds["peers"].len(), 120);
+        assert_eq!(seconds["peers"][5], [(start + 5) as f64, 5.0]);
+        assert!(!seconds.contains_key("bad"));
+
+        assert_eq!(read("1m")["peers"], vec![[start as f64, 29.5], [(start + 60) as f64, 89.5]]);
+        assert_eq!(read("10m")["peers"], vec![[start as f64, 59.5]]);
+
+        let none = MetricStore::read(dir.path(), tier("1s"), start, start + 600, |name| name == "other").unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn rolls_over_to_a_new_segment_per_span() {
+        let dir = tempfile::tempdir().unwrap();
+        let seconds = tier("1s");
+        let mut writer = TierWriter::new(dir.path().to_path_buf(), seconds);
+        for at in [3598, 3599, 3600, 3601] {
+            writer.add(at, &value("peers", at as f64)).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(segment_starts(dir.path(), seconds), vec![0, 3600]);
+        let points = MetricStore::read(dir.path(), seconds, 0, 7200, |_| true).unwrap();
+        assert_eq!(points["peers"], vec![[3598.0, 3598.0], [3599.0, 3599.0], [3600.0, 3600.0], [3601.0, 3601.0]]);
+
+        // Reading a single span only opens the segment that covers it
+        let later = MetricStore::read(dir.path(), seconds, 3600, 3700, |_| true).unwrap();
+        assert_eq!(later["peers"].len(), 2);
+    }
+
+    #[test]
+    fn resumes_after_a_line_cut_short() {
+        let dir = tempfile::tempdir().unwrap();
+        let seconds = tier("1s");
+        let tier_dir = MetricStore::tier_dir(dir.path(), seconds);
+        std::fs::create_dir_all(&tier_dir).unwrap();
+        std::fs::write(tier_dir.join("0.jsonl"), "{\"t\":1,\"v\":{\"peers\":1.0}}\n{\"t\":2,\"v\":{\"pe").unwrap();
+
+        let mut writer = TierWriter::new(dir.path().to_path_buf(), seconds);
+        writer.add(3, &value("peers", 3.0)).unwrap();
+        writer.flush().unwrap();
+
+        let points = MetricStore::read(dir.path(), seconds, 0, 10, |_| true).unwrap();
+        assert_eq!(points["peers"], vec![[1.0, 1.0], [3.0, 3.0]]);
+    }
+
+    #[test]
+    fn prunes_segments_past_the_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let seconds = tier("1s");
+        let mut writer = TierWriter::new(dir.path().to_path_buf(), seconds);
+        writer.add(10, &value("peers", 1.0)).unwrap();
+        writer.add(3700, &value("peers", 2.0)).unwrap();
+        writer.add(7200, &value("peers", 3.0)).unwrap();
+        writer.flush().unwrap();
+        // The first segment's newest data (3599) is exactly an hour older than 7200
+        assert_eq!(segment_starts(dir.path(), seconds), vec![0, 3600, 7200]);
+
+        // Segments are only pruned when a new span starts
+        writer.add(10_000, &value("peers", 4.0)).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(segment_starts(dir.path(), seconds), vec![0, 3600, 7200]);
+
+        writer.add(10_900, &value("peers", 5.0)).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(segment_starts(dir.path(), seconds), vec![7200, 10_800]);
+        let points = MetricStore::read(dir.path(), seconds, 0, 20_000, |_| true).unwrap();
+        assert_eq!(points["peers"], vec![[7200.0, 3.0], [10_000.0, 4.0], [10_900.0, 5.0]]);
+    }
+
+    #[test]
+    fn merges_points_with_the_same_timestamp() {
+        let mut points = vec![[60.0, 1.0], [120.0, 2.0], [120.0, 4.0], [120.0, 6.0], [180.0, 5.0]];
+        merge_same_time(&mut points);
+        assert_eq!(points, vec![[60.0, 1.0], [120.0, 4.0], [180.0, 5.0]]);
+
+        let mut empty: Vec<[f64; 2]> = Vec::new();
+        merge_same_time(&mut empty);
+        assert!(empty.is_empty());
+    }
+}
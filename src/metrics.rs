@@ -1,22 +1,192 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
-// Removed unused imports
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::metric_store;
+use crate::prometheus::{self, Exposition, MetricType, Sample};
+use crate::sync_stages::SyncStageTracker;
 
 /// Maximum number of data points to keep for each metric
 const MAX_DATA_POINTS: usize = 600; // 600 points = 10 minutes of data at 1 second intervals
 
+/// Window the per-second rate is averaged over
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Quantile lines drawn for summaries and histograms, and their names
+pub const QUANTILES: [(f64, &str); 3] = [(0.5, "p50"), (0.9, "p90"), (0.99, "p99")];
+
+/// Name of the mean line on summary and histogram cards
+pub const MEAN_LINE: &str = "mean";
+
 #[derive(Debug, Clone)]
 pub struct MetricValue {
-    pub timestamp: Instant,
+    pub timestamp: DateTime<Local>,
     pub value: f64,
 }
 
+/// Which series of a metric a card plots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Raw,
+    Rate, // Change per second over the rate window
+    Delta, // Change since the previous poll
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [DisplayMode::Raw, DisplayMode::Rate, DisplayMode::Delta];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisplayMode::Raw => "raw",
+            DisplayMode::Rate => "rate",
+            DisplayMode::Delta => "delta",
+        }
+    }
+    
+    /// This mode's series for stored raw points of unix seconds and values
+    pub fn derive(&self, points: &[[f64; 2]], is_counter: bool) -> Vec<[f64; 2]> {
+        if *self == DisplayMode::Raw {
+            return points.to_vec();
+        }
+        points
+            .windows(2)
+            .map(|pair| {
+                let ([t0, v0], [t1, v1]) = (pair[0], pair[1]);
+                // A counter that dropped started again from zero
+                let delta = if is_counter && v1 < v0 { v1 } else { v1 - v0 };
+                match self {
+                    DisplayMode::Rate if t1 > t0 => [t1, delta / (t1 - t0)],
+                    DisplayMode::Rate => [t1, 0.0],
+                    _ => [t1, delta],
+                }
+            })
+            .collect()
+    }
+}
+
+/// Change of a cumulative value over time. Counters only go up, so a drop means the node
+/// restarted and the counter began again from zero; the new value is then all increase.
+#[derive(Debug, Clone, Default)]
+struct RateWindow {
+    is_counter: bool,
+    last_raw: Option<f64>,
+    offset: f64, // Sum of the values counters had before they reset
+    samples: VecDeque<(Instant, f64)>, // Reset-adjusted values inside the window
+}
+
+impl RateWindow {
+    /// Record a value and return the change since the last one and the rate per second,
+    /// once there is something to compare against
+    fn add(&mut self, at: Instant, raw: f64) -> Option<(f64, f64)> {
+        if self.is_counter && self.last_raw.is_some_and(|last| raw < last) {
+            self.offset += self.last_raw.unwrap_or(0.0);
+        }
+        self.last_raw = Some(raw);
+        let adjusted = raw + self.offset;
+
+        let previous = self.samples.back().map(|(_, value)| *value);
+        self.samples.push_back((at, adjusted));
+        while self.samples.len() > 2 && self.samples.front().is_some_and(|(time, _)| at.duration_since(*time) > RATE_WINDOW) {
+            self.samples.pop_front();
+        }
+
+        let delta = adjusted - previous?;
+        let (first_time, first_value) = self.samples.front()?;
+        let elapsed = at.duration_since(*first_time).as_secs_f64();
+        let rate = if elapsed > 0.0 { (adjusted - first_value) / elapsed } else { 0.0 };
+        Some((delta, rate))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MetricHistory {
     pub name: String,
     pub values: VecDeque<MetricValue>,
     pub unit: String,
+    pub mode: DisplayMode,
+    rates: VecDeque<MetricValue>,
+    deltas: VecDeque<MetricValue>,
+    window: RateWindow,
+    pub groups: BTreeMap<String, MetricHistory>, // One history per label value on grouped cards
+    pub distribution: Option<Distribution>, // Summary or histogram card, whose groups are its quantile and mean lines
+}
+
+/// Observations that fell into each bucket of a histogram between polls
+#[derive(Debug, Clone, Default)]
+pub struct Heatmap {
+    pub bounds: Vec<f64>, // Upper bounds of the buckets in the card's unit, ascending; the last is usually +Inf
+    pub columns: VecDeque<(DateTime<Local>, Vec<f64>)>, // Per poll, observations in each bucket
+    last: Vec<f64>, // Cumulative counts at the previous poll
+}
+
+impl Heatmap {
+    /// Record cumulative bucket counts; resets when the buckets change
+    fn add(&mut self, buckets: &[(f64, f64)], scale: f64) {
+        let bounds: Vec<f64> = buckets.iter().map(|(bound, _)| bound * scale).collect();
+        let counts: Vec<f64> = buckets.iter().map(|(_, count)| *count).collect();
+        if bounds != self.bounds {
+            self.bounds = bounds;
+            self.columns.clear();
+            self.last = counts;
+            return;
+        }
+
+        // Counts that dropped mean the node restarted and began again from zero
+        let restarted = counts.iter().zip(&self.last).any(|(count, last)| count < last);
+        let increase: Vec<f64> = if restarted {
+            counts.clone()
+        } else {
+            counts.iter().zip(&self.last).map(|(count, last)| count - last).collect()
+        };
+        // From cumulative to per bucket
+        let column = increase
+            .iter()
+            .enumerate()
+            .map(|(i, count)| if i == 0 { *count } else { count - increase[i - 1] })
+            .collect();
+        self.columns.push_back((Local::now(), column));
+        while self.columns.len() > MAX_DATA_POINTS {
+            self.columns.pop_front();
+        }
+        self.last = counts;
+    }
+
+    /// Cumulative bucket counts over the columns of the last rate window
+    fn recent(&self) -> Vec<(f64, f64)> {
+        let since = Local::now() - chrono::Duration::from_std(RATE_WINDOW).unwrap_or_default();
+        let mut totals = vec![0.0; self.bounds.len()];
+        for (_, column) in self.columns.iter().rev().take_while(|(at, _)| *at >= since) {
+            for (total, count) in totals.iter_mut().zip(column) {
+                *total += count;
+            }
+        }
+        let mut cumulative = 0.0;
+        self.bounds
+            .iter()
+            .zip(totals)
+            .map(|(bound, count)| {
+                cumulative += count;
+                (*bound, cumulative)
+            })
+            .collect()
+    }
+}
+
+/// Sum and count of a summary or histogram, for the mean of recent observations
+#[derive(Debug, Clone)]
+pub struct Distribution {
+    pub heatmap: Option<Heatmap>, // Histograms only
+    sum: RateWindow,
+    count: RateWindow,
+}
+
+impl Default for Distribution {
+    fn default() -> Self {
+        let counter = RateWindow { is_counter: true, ..RateWindow::default() };
+        Self { heatmap: None, sum: counter.clone(), count: counter }
+    }
 }
 
 impl MetricHistory {
@@ -25,34 +195,126 @@ impl MetricHistory {
             name,
             values: VecDeque::with_capacity(MAX_DATA_POINTS),
             unit,
+            mode: DisplayMode::Raw,
+            rates: VecDeque::new(),
+            deltas: VecDeque::new(),
+            window: RateWindow::default(),
+            groups: BTreeMap::new(),
+            distribution: None,
+        }
+    }
+    
+    /// History of a monotonic counter, whose drops are treated as resets when computing rates
+    pub fn counter(name: String, unit: String) -> Self {
+        let mut history = Self::new(name, unit);
+        history.window.is_counter = true;
+        history
+    }
+    
+    pub fn is_counter(&self) -> bool {
+        self.window.is_counter
+    }
+    
+    pub fn set_counter(&mut self, is_counter: bool) {
+        self.window.is_counter = is_counter;
+        for group in self.groups.values_mut() {
+            group.set_counter(is_counter);
+        }
+    }
+    
+    pub fn set_mode(&mut self, mode: DisplayMode) {
+        self.mode = mode;
+        for group in self.groups.values_mut() {
+            group.mode = mode;
         }
     }
     
     pub fn add_value(&mut self, value: f64) {
-        self.values.push_back(MetricValue {
-            timestamp: Instant::now(),
-            value,
-        });
+        self.record(value);
+    }
+    
+    /// Add a value to the line for one label value, starting the line if it is new
+    pub fn add_group_value(&mut self, group: &str, value: f64) {
+        if !self.groups.contains_key(group) {
+            let mut history = Self::new(group.to_string(), self.unit.clone());
+            history.mode = self.mode;
+            history.window.is_counter = self.window.is_counter;
+            self.groups.insert(group.to_string(), history);
+        }
+        if let Some(history) = self.groups.get_mut(group) {
+            history.add_value(value);
+        }
+    }
+    
+    /// Add a value and return the new per-second rate, if there was an earlier value
+    fn record(&mut self, value: f64) -> Option<f64> {
+        self.record_at(Instant::now(), value)
+    }
+    
+    /// Add a value polled at `at`, which the rate window measures elapsed time from
+    fn record_at(&mut self, at: Instant, value: f64) -> Option<f64> {
+        let timestamp = Local::now();
+        Self::push(&mut self.values, timestamp, value);
+        let (delta, rate) = self.window.add(at, value)?;
+        Self::push(&mut self.deltas, timestamp, delta);
+        Self::push(&mut self.rates, timestamp, rate);
+        Some(rate)
+    }
+    
+    fn push(series: &mut VecDeque<MetricValue>, timestamp: DateTime<Local>, value: f64) {
+        series.push_back(MetricValue { timestamp, value });
         
         // Keep only the last MAX_DATA_POINTS
-        while self.values.len() > MAX_DATA_POINTS {
-            self.values.pop_front();
+        while series.len() > MAX_DATA_POINTS {
+            series.pop_front();
+        }
+    }
+    
+    /// The series shown for the current display mode
+    pub fn series(&self) -> &VecDeque<MetricValue> {
+        match self.mode {
+            DisplayMode::Raw => &self.values,
+            DisplayMode::Rate => &self.rates,
+            DisplayMode::Delta => &self.deltas,
+        }
+    }
+    
+    /// The plotted lines and their names: one per label value on grouped cards,
+    /// otherwise just the shown series
+    pub fn lines(&self) -> Vec<(&str, &VecDeque<MetricValue>)> {
+        if self.groups.is_empty() {
+            vec![(self.name.as_str(), self.series())]
+        } else {
+            self.groups.values().map(|group| (group.name.as_str(), group.series())).collect()
+        }
+    }
+    
+    pub fn has_data(&self) -> bool {
+        self.lines().iter().any(|(_, series)| !series.is_empty())
+    }
+    
+    /// Unit of the shown series, e.g. "MB/s" for the rate of a metric in MB
+    pub fn display_unit(&self) -> String {
+        match self.mode {
+            DisplayMode::Rate if self.unit.is_empty() => "/s".to_string(),
+            DisplayMode::Rate => format!("{}/s", self.unit),
+            _ => self.unit.clone(),
         }
     }
     
     pub fn get_latest(&self) -> Option<f64> {
-        self.values.back().map(|v| v.value)
+        self.series().back().map(|v| v.value)
     }
     
     pub fn get_min_max(&self) -> (f64, f64) {
-        if self.values.is_empty() {
+        if self.series().is_empty() {
             return (0.0, 1.0);
         }
         
         let mut min = f64::MAX;
         let mut max = f64::MIN;
         
-        for value in &self.values {
+        for value in self.series() {
             min = min.min(value.value);
             max = max.max(value.value);
         }
@@ -61,6 +323,128 @@ impl MetricHistory {
     }
 }
 
+/// Restricts a custom metric to the series whose label has this value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelMatcher {
+    pub label: String,
+    pub value: String,
+}
+
+/// A metric card the user added, as saved in the desktop settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomMetric {
+    pub name: String,
+    #[serde(default)]
+    pub mode: DisplayMode,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matchers: Vec<LabelMatcher>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<String>, // Draw one line per value of this label
+}
+
+impl CustomMetric {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), mode: DisplayMode::Raw, matchers: Vec::new(), group_by: None }
+    }
+    
+    /// Identifies the card, e.g. `reth_db_table_size{table="PlainAccountState"}` or
+    /// `reth_database_operation_calls_total by table`; just the name without labels
+    pub fn key(&self) -> String {
+        let mut key = self.name.clone();
+        if !self.matchers.is_empty() {
+            let matchers: Vec<String> = self.matchers.iter().map(|m| format!("{}=\"{}\"", m.label, m.value)).collect();
+            key.push_str(&format!("{{{}}}", matchers.join(",")));
+        }
+        if let Some(label) = &self.group_by {
+            key.push_str(&format!(" by {}", label));
+        }
+        key
+    }
+    
+    /// Whether the card draws a summary or histogram as quantile bands and a mean,
+    /// rather than a single quantile or a grouping
+    pub fn is_distribution(&self, metrics: &Exposition) -> bool {
+        self.group_by.is_none()
+            && !self.matchers.iter().any(|m| m.label == "quantile")
+            && metrics
+                .family(&self.name)
+                .is_some_and(|family| matches!(family.metric_type, MetricType::Summary | MetricType::Histogram))
+    }
+    
+    /// Whether a sample belongs to the series this card plots
+    pub fn matches(&self, sample: &Sample) -> bool {
+        self.matchers.iter().all(|m| sample.label(&m.label) == Some(m.value.as_str()))
+    }
+    
+    /// Card title: the metric's readable name followed by its filters and grouping
+    fn title(&self) -> String {
+        let mut title = display_name(&self.name);
+        if !self.matchers.is_empty() {
+            let matchers: Vec<String> = self.matchers.iter().map(|m| format!("{}={}", m.label, m.value)).collect();
+            title.push_str(&format!(" ({})", matchers.join(", ")));
+        }
+        if let Some(label) = &self.group_by {
+            title.push_str(&format!(" by {}", label));
+        }
+        title
+    }
+}
+
+/// `reth_db_table_size` as "Reth Db Table Size"
+fn display_name(metric_name: &str) -> String {
+    metric_name.replace('_', " ")
+        .split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Either the current format or a bare metric name from older settings files
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCustomMetric {
+    Structured(CustomMetric),
+    Legacy(String),
+}
+
+/// Deserialize custom metric cards, migrating entries saved as plain names
+pub fn deserialize_custom_metrics<'de, D>(deserializer: D) -> Result<Vec<CustomMetric>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let stored = Vec::<StoredCustomMetric>::deserialize(deserializer)?;
+    Ok(stored
+        .into_iter()
+        .map(|entry| match entry {
+            StoredCustomMetric::Structured(metric) => metric,
+            StoredCustomMetric::Legacy(name) => CustomMetric::new(name),
+        })
+        .collect())
+}
+
+/// A sample name offered in the custom metric picker, with its family's metadata
+#[derive(Debug, Clone)]
+pub struct AvailableMetric {
+    pub name: String,
+    pub family: String, // Differs from `name` for the `_sum`/`_count` series of summaries and histograms
+    pub metric_type: MetricType,
+    pub help: Option<String>,
+    pub labels: BTreeMap<String, BTreeSet<String>>, // Label names and the values seen for each
+}
+
+impl AvailableMetric {
+    /// A summary or histogram as a whole, which gets a quantile and mean card
+    pub fn is_distribution(&self) -> bool {
+        matches!(self.metric_type, MetricType::Summary | MetricType::Histogram) && self.name == self.family
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RethMetrics {
     pub sync_progress: MetricHistory,
@@ -72,10 +456,28 @@ pub struct RethMetrics {
     pub cpu_usage: MetricHistory,
     pub disk_io: MetricHistory,
     
-    // Custom metrics dynamically added by user
+    // Rates derived from counters and growing gauges
+    pub blocks_per_second: MetricHistory,
+    pub gas_per_second: MetricHistory,
+    pub rpc_calls_per_second: MetricHistory,
+    pub db_writes_per_second: MetricHistory,
+    
+    // Raw series the derived rates are computed from
+    cpu_seconds: MetricHistory,
+    blocks: MetricHistory,
+    gas_processed: MetricHistory,
+    rpc_calls: MetricHistory,
+    bytes_written: MetricHistory,
+    
+    // Custom metrics dynamically added by user, by `CustomMetric::key`
     pub custom_metrics: HashMap<String, MetricHistory>,
+    custom_selections: Vec<CustomMetric>,
+    
+    // Per-stage progress of the sync pipeline
+    pub sync_stages: SyncStageTracker,
     
     last_poll_time: Option<Instant>,
+    parse_errors: usize, // Malformed lines in the last exposition, reported when the count changes
 }
 
 impl RethMetrics {
@@ -113,13 +515,59 @@ impl RethMetrics {
                 "Active Downloads".to_string(),
                 "blocks".to_string(),
             ),
+            blocks_per_second: MetricHistory::new(
+                "Blocks/s".to_string(),
+                "blocks/s".to_string(),
+            ),
+            gas_per_second: MetricHistory::new(
+                "Gas/s".to_string(),
+                "Mgas/s".to_string(),
+            ),
+            rpc_calls_per_second: MetricHistory::new(
+                "RPC Calls/s".to_string(),
+                "calls/s".to_string(),
+            ),
+            db_writes_per_second: MetricHistory::new(
+                "Disk Writes".to_string(),
+                "MB/s".to_string(),
+            ),
+            cpu_seconds: MetricHistory::counter(String::new(), "s".to_string()),
+            blocks: MetricHistory::new(String::new(), "blocks".to_string()),
+            gas_processed: MetricHistory::counter(String::new(), "gas".to_string()),
+            rpc_calls: MetricHistory::counter(String::new(), "calls".to_string()),
+            bytes_written: MetricHistory::counter(String::new(), "bytes".to_string()),
             custom_metrics: HashMap::new(),
+            custom_selections: Vec::new(),
+            sync_stages: SyncStageTracker::new(),
             last_poll_time: None,
+            parse_errors: 0,
         }
     }
     
-    pub fn add_custom_metric(&mut self, metric_name: String) {
-        if !self.custom_metrics.contains_key(&metric_name) {
+    /// The built-in cards, in display order; derived rates only once their source metrics exist
+    pub fn default_cards(&self) -> Vec<&MetricHistory> {
+        let mut cards = vec![
+            &self.peers_connected,
+            &self.block_height,
+            &self.sync_progress,
+            &self.memory_usage,
+            &self.cpu_usage,
+            &self.disk_io,
+        ];
+        cards.extend(
+            [&self.blocks_per_second, &self.gas_per_second, &self.rpc_calls_per_second, &self.db_writes_per_second]
+                .into_iter()
+                .filter(|metric| !metric.values.is_empty()),
+        );
+        cards
+    }
+    
+    pub fn add_custom_metric(&mut self, custom: &CustomMetric) {
+        let key = custom.key();
+        if let Some(history) = self.custom_metrics.get_mut(&key) {
+            history.set_mode(custom.mode);
+        } else {
+            let metric_name = &custom.name;
             // Try to infer unit from metric name
             let unit = if metric_name.contains("_bytes") {
                 "MB"  // Display as MB in the UI
@@ -133,24 +581,33 @@ impl RethMetrics {
                 ""
             };
             
-            // Create a display name for the metric
-            let display_name = metric_name.replace('_', " ")
-                .split_whitespace()
-                .map(|word| {
-                    let mut chars = word.chars();
-                    match chars.next() {
-                        None => String::new(),
-                        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str()
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join(" ");
-            
-            self.custom_metrics.insert(
-                metric_name.clone(),
-                MetricHistory::new(display_name, unit.to_string())
-            );
+            let mut history = MetricHistory::new(custom.title(), unit.to_string());
+            history.mode = custom.mode;
+            self.custom_metrics.insert(key, history);
+            self.custom_selections.push(custom.clone());
+        }
+    }
+    
+    pub fn remove_custom_metric(&mut self, key: &str) {
+        self.custom_metrics.remove(key);
+        self.custom_selections.retain(|custom| custom.key() != key);
+    }
+    
+    /// Raw values recorded since `since`, by series id, for the metric store
+    pub fn snapshot(&self, since: DateTime<Local>) -> Vec<(String, f64)> {
+        let latest = |history: &MetricHistory| history.values.back().filter(|value| value.timestamp >= since).map(|value| value.value);
+        let mut values: Vec<(String, f64)> = self
+            .default_cards()
+            .into_iter()
+            .filter_map(|card| Some((card.name.clone(), latest(card)?)))
+            .collect();
+        for (key, history) in &self.custom_metrics {
+            values.extend(latest(history).map(|value| (key.clone(), value)));
+            for (group, line) in &history.groups {
+                values.extend(latest(line).map(|value| (metric_store::group_series_id(key, group), value)));
+            }
         }
+        values
     }
     
     pub fn should_poll(&self) -> bool {
@@ -164,55 +621,48 @@ impl RethMetrics {
         self.last_poll_time = Some(Instant::now());
     }
     
-    /// Parse Prometheus-style metrics text and update the metric histories
-    pub fn update_from_prometheus_text(&mut self, text: &str) {
-        let metrics = parse_prometheus_metrics(text);
+    /// Update the metric histories from a parsed scrape of the metrics endpoint
+    pub fn update_from_exposition(&mut self, metrics: &Exposition) {
+        if metrics.errors().len() != self.parse_errors {
+            self.parse_errors = metrics.errors().len();
+            if let Some(first) = metrics.errors().first() {
+                eprintln!("Skipped {} malformed metrics lines, first at {}", self.parse_errors, first);
+            }
+        }
+        self.sync_stages.update_from_metrics(metrics);
         
         // Update connected peers (this metric exists in the endpoint)
-        if let Some(value) = metrics.get("reth_network_connected_peers") {
-            if let Ok(v) = value.parse::<f64>() {
-                self.peers_connected.add_value(v);
-            }
+        if let Some(v) = metrics.value("reth_network_connected_peers") {
+            self.peers_connected.add_value(v);
         }
         
         // Update block height using canonical chain height
-        if let Some(value) = metrics.get("reth_blockchain_tree_canonical_chain_height") {
-            if let Ok(v) = value.parse::<f64>() {
-                self.block_height.add_value(v);
-            }
+        if let Some(v) = metrics.value("reth_blockchain_tree_canonical_chain_height") {
+            self.block_height.add_value(v);
         }
         
         // Update memory usage (convert from bytes to MB) - this metric exists
-        if let Some(value) = metrics.get("reth_process_resident_memory_bytes") {
-            if let Ok(v) = value.parse::<f64>() {
-                self.memory_usage.add_value(v / 1_048_576.0); // Convert to MB
-            }
+        if let Some(v) = metrics.value("reth_process_resident_memory_bytes") {
+            self.memory_usage.add_value(v / 1_048_576.0); // Convert to MB
         }
         
         // Calculate sync progress based on multiple indicators
         let mut is_syncing = false;
         
         // Check gas per second (active sync indicator)
-        if let Some(value) = metrics.get("reth_sync_execution_gas_per_second") {
-            if let Ok(v) = value.parse::<f64>() {
-                if v > 0.0 {
-                    is_syncing = true;
-                }
-            }
+        if metrics.value("reth_sync_execution_gas_per_second").is_some_and(|v| v > 0.0) {
+            is_syncing = true;
         }
         
         // Check active block downloads
-        if let Some(value) = metrics.get("reth_consensus_engine_beacon_active_block_downloads") {
-            if let Ok(v) = value.parse::<f64>() {
-                if v > 0.0 {
-                    is_syncing = true;
-                }
-            }
+        if metrics.value("reth_consensus_engine_beacon_active_block_downloads").is_some_and(|v| v > 0.0) {
+            is_syncing = true;
         }
         
-        // If we have block height, we can show it instead of a percentage
-        // For now, show syncing status rather than a misleading percentage
-        if is_syncing {
+        // Prefer the pipeline's stage-by-stage progress when the node reports it
+        if let Some(progress) = self.sync_stages.overall_progress() {
+            self.sync_progress.add_value(progress);
+        } else if is_syncing {
             // Don't show 100% when syncing, show a value that indicates ongoing sync
             self.sync_progress.add_value(0.0); // Will show as "Syncing"
         } else if self.block_height.get_latest().unwrap_or(0.0) > 0.0 {
@@ -220,22 +670,33 @@ impl RethMetrics {
             self.sync_progress.add_value(100.0);
         }
         
-        // Update CPU usage (using the correct metric name)
-        if let Some(value) = metrics.get("reth_process_cpu_seconds_total") {
-            if let Ok(_v) = value.parse::<f64>() {
-                // This is cumulative, so we'd need to calculate the rate
-                // For now, we'll use a placeholder
-                // TODO: Calculate actual CPU usage rate
-            }
-        }
+        // CPU usage: CPU seconds spent per second of wall time, spread over the cores
+        let now = Instant::now();
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get()) as f64;
+        Self::add_rate(&mut self.cpu_seconds, &mut self.cpu_usage, metrics.value("reth_process_cpu_seconds_total"), 100.0 / cores, now);
+        
+        // Blocks per second from whichever is further ahead: the Execution stage during
+        // pipeline sync, or the canonical chain once following the tip
+        let execution_checkpoint = metrics
+            .samples("reth_sync_checkpoint")
+            .find(|sample| sample.label("stage") == Some("Execution"))
+            .map(|sample| sample.value);
+        let blocks = match (execution_checkpoint, metrics.value("reth_blockchain_tree_canonical_chain_height")) {
+            (Some(checkpoint), Some(height)) => Some(checkpoint.max(height)),
+            (checkpoint, height) => checkpoint.or(height),
+        };
+        Self::add_rate(&mut self.blocks, &mut self.blocks_per_second, blocks, 1.0, now);
+        Self::add_rate(&mut self.gas_processed, &mut self.gas_per_second, metrics.value("reth_sync_execution_gas_processed_total"), 1e-6, now);
+        // Summed over every method
+        Self::add_rate(&mut self.rpc_calls, &mut self.rpc_calls_per_second, metrics.value("reth_rpc_server_calls_started_total"), 1.0, now);
+        // Bytes the node process wrote to storage, nearly all of it database and static files
+        Self::add_rate(&mut self.bytes_written, &mut self.db_writes_per_second, metrics.value("reth_io_write_bytes"), 1.0 / 1_048_576.0, now);
         
         // For transactions per second, we can use a different approach
         // Look at the transaction pool size as an indicator
-        if let Some(value) = metrics.get("reth_transaction_pool_transactions") {
-            if let Ok(v) = value.parse::<f64>() {
-                // This shows current pool size, not TPS, but it's useful
-                self.transactions_per_second.add_value(v);
-            }
+        if let Some(v) = metrics.value("reth_transaction_pool_transactions") {
+            // This shows current pool size, not TPS, but it's useful
+            self.transactions_per_second.add_value(v);
         }
         
         // Update gas price if available (useful for node operators)
@@ -243,66 +704,134 @@ impl RethMetrics {
         // For now, we'll use a placeholder since it's not in the metrics endpoint
         
         // Track active downloads (useful during sync)
-        if let Some(value) = metrics.get("reth_consensus_engine_beacon_active_block_downloads") {
-            if let Ok(v) = value.parse::<f64>() {
-                // Could be used to show sync activity
-                self.disk_io.add_value(v); // Repurpose disk_io for active downloads
-            }
+        if let Some(v) = metrics.value("reth_consensus_engine_beacon_active_block_downloads") {
+            // Could be used to show sync activity
+            self.disk_io.add_value(v); // Repurpose disk_io for active downloads
         }
         
         // Update custom metrics
-        for (metric_name, metric_history) in &mut self.custom_metrics {
-            if let Some(value) = metrics.get(metric_name) {
-                if let Ok(v) = value.parse::<f64>() {
-                    // Convert bytes to MB if it's a bytes metric
-                    let final_value = if metric_history.unit == "MB" && metric_name.contains("_bytes") {
-                        v / 1_048_576.0 // Convert to MB
-                    } else {
-                        v
-                    };
-                    metric_history.add_value(final_value);
+        for custom in &self.custom_selections {
+            let Some(metric_history) = self.custom_metrics.get_mut(&custom.key()) else {
+                continue;
+            };
+            metric_history.set_counter(metrics.is_cumulative(&custom.name));
+            // Convert bytes to MB if it's a bytes metric
+            let scale = if metric_history.unit == "MB" && custom.name.contains("_bytes") { 1.0 / 1_048_576.0 } else { 1.0 };
+            if custom.is_distribution(metrics) {
+                Self::update_distribution(metric_history, metrics, custom, now);
+                continue;
+            }
+            let filter = |sample: &Sample| custom.matches(sample);
+            match &custom.group_by {
+                Some(label) => {
+                    for (group, v) in metrics.values_by(&custom.name, label, filter) {
+                        metric_history.add_group_value(&group, v * scale);
+                    }
+                }
+                None => {
+                    if let Some(v) = metrics.value_where(&custom.name, filter) {
+                        metric_history.add_value(v * scale);
+                    }
                 }
             }
         }
     }
     
-    /// Get all available metric names from the prometheus text
-    pub fn get_available_metrics(text: &str) -> Vec<String> {
-        let metrics = parse_prometheus_metrics(text);
-        let mut metric_names: Vec<String> = metrics.keys().cloned().collect();
-        metric_names.sort();
-        metric_names
-    }
-}
-
-/// Parse Prometheus-style metrics text into a HashMap
-fn parse_prometheus_metrics(text: &str) -> HashMap<String, String> {
-    let mut metrics = HashMap::new();
-    
-    for line in text.lines() {
-        // Skip comments and empty lines
-        if line.starts_with('#') || line.trim().is_empty() {
-            continue;
+    /// Quantile lines, the mean of recent observations and, for histograms, the bucket heatmap.
+    /// Lines hold their last value while nothing is observed.
+    fn update_distribution(history: &mut MetricHistory, metrics: &Exposition, custom: &CustomMetric, now: Instant) {
+        let mut distribution = history.distribution.take().unwrap_or_default();
+        // Latencies read better in milliseconds
+        if history.unit == "s" {
+            history.unit = "ms".to_string();
         }
+        let scale = if history.unit == "ms" { 1000.0 } else { 1.0 };
+        let filter = |sample: &Sample| custom.matches(sample);
+        let held = |history: &MetricHistory, line: &str| history.groups.get(line).and_then(|group| group.values.back()).map(|value| value.value);
         
-        // Parse metric lines (format: metric_name{labels} value)
-        // or simple format: metric_name value
-        if let Some(space_pos) = line.rfind(' ') {
-            let (name_part, value) = line.split_at(space_pos);
-            let value = value.trim();
-            
-            // Extract metric name (before any labels)
-            let metric_name = if let Some(brace_pos) = name_part.find('{') {
-                &name_part[..brace_pos]
-            } else {
-                name_part
-            }.trim();
-            
-            metrics.insert(metric_name.to_string(), value.to_string());
+        let is_histogram = metrics.family(&custom.name).is_some_and(|family| family.metric_type == MetricType::Histogram);
+        let recent_buckets = if is_histogram {
+            let heatmap = distribution.heatmap.get_or_insert_with(Heatmap::default);
+            heatmap.add(&metrics.buckets(&custom.name, filter), scale);
+            Some(heatmap.recent())
+        } else {
+            None
+        };
+        for (quantile, line) in QUANTILES {
+            let value = match &recent_buckets {
+                // The heatmap's bounds are already in the card's unit
+                Some(buckets) => prometheus::bucket_quantile(quantile, buckets),
+                // Quantiles of separate series can't be combined, so show the slowest
+                None => metrics
+                    .samples(&custom.name)
+                    .filter(|sample| filter(sample))
+                    .filter(|sample| sample.label("quantile").and_then(|q| q.parse::<f64>().ok()).is_some_and(|q| (q - quantile).abs() < 1e-9))
+                    .map(|sample| sample.value)
+                    .filter(|value| value.is_finite())
+                    .reduce(f64::max)
+                    .map(|value| value * scale),
+            };
+            if let Some(value) = value.or_else(|| held(history, line)) {
+                history.add_group_value(line, value);
+            }
         }
+        
+        // Mean from how much the sum and count grew over the rate window
+        let sum = metrics.value_where(&format!("{}_sum", custom.name), filter);
+        let count = metrics.value_where(&format!("{}_count", custom.name), filter);
+        if let (Some(sum), Some(count)) = (sum, count) {
+            let sum_rate = distribution.sum.add(now, sum).map(|(_, rate)| rate);
+            let count_rate = distribution.count.add(now, count).map(|(_, rate)| rate);
+            let mean = match (sum_rate, count_rate) {
+                (Some(sum_rate), Some(count_rate)) if count_rate > 0.0 => Some(sum_rate / count_rate * scale),
+                _ => held(history, MEAN_LINE),
+            };
+            if let Some(mean) = mean {
+                history.add_group_value(MEAN_LINE, mean);
+            }
+        }
+        history.distribution = Some(distribution);
     }
     
-    metrics
+    /// Record a source value polled at `at` and plot its per-second rate, multiplied by `scale`
+    fn add_rate(source: &mut MetricHistory, derived: &mut MetricHistory, value: Option<f64>, scale: f64, at: Instant) {
+        let Some(value) = value else {
+            return;
+        };
+        if let Some(rate) = source.record_at(at, value) {
+            derived.add_value(rate * scale);
+        }
+    }
+    
+    /// Every sample name in the exposition, sorted, with its family's type and help text
+    /// and the label values its series have. Histograms are also offered as a whole,
+    /// under their family name.
+    pub fn get_available_metrics(metrics: &Exposition) -> Vec<AvailableMetric> {
+        let mut available: BTreeMap<&str, AvailableMetric> = BTreeMap::new();
+        for family in metrics.families() {
+            for sample in &family.samples {
+                let is_bucket = family.metric_type == MetricType::Histogram && sample.name.ends_with("_bucket");
+                let names = if is_bucket { vec![sample.name.as_str(), family.name.as_str()] } else { vec![sample.name.as_str()] };
+                for name in names {
+                    let metric = available.entry(name).or_insert_with(|| AvailableMetric {
+                        name: name.to_string(),
+                        family: family.name.clone(),
+                        metric_type: family.metric_type,
+                        help: family.help.clone(),
+                        labels: BTreeMap::new(),
+                    });
+                    for (label, value) in &sample.labels {
+                        // The whole histogram is drawn over every bucket
+                        if name == family.name && label == "le" {
+                            continue;
+                        }
+                        metric.labels.entry(label.clone()).or_default().insert(value.clone());
+                    }
+                }
+            }
+        }
+        available.into_values().collect()
+    }
 }
 
 /// Fetch metrics from the Reth metrics endpoint
@@ -310,4 +839,213 @@ pub async fn fetch_metrics(endpoint: &str) -> Result<String, Box<dyn std::error:
     let response = reqwest::get(endpoint).await?;
     let text = response.text().await?;
     Ok(text)
-}
\ No newline at end of file
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter_window() -> RateWindow {
+        RateWindow { is_counter: true, ..RateWindow::default() }
+    }
+
+    fn secs(start: Instant, seconds: u64) -> Instant {
+        start + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn rates_a_monotonic_counter_over_the_window() {
+        let start = Instant::now();
+        let mut window = counter_window();
+        assert_eq!(window.add(start, 0.0), None);
+        assert_eq!(window.add(secs(start, 1), 10.0), Some((10.0, 10.0)));
+        assert_eq!(window.add(secs(start, 2), 30.0), Some((20.0, 15.0)));
+
+        // Samples older than the window stop counting towards the rate
+        assert_eq!(window.add(secs(start, 20), 30.0), Some((0.0, 0.0)));
+        assert_eq!(window.add(secs(start, 22), 50.0), Some((20.0, 10.0)));
+    }
+
+    #[test]
+    fn treats_a_counter_drop_as_a_reset() {
+        let start = Instant::now();
+        let mut window = counter_window();
+        window.add(start, 100.0);
+        window.add(secs(start, 1), 120.0);
+        // The node restarted: the new value is all increase, never a negative rate or a spike
+        assert_eq!(window.add(secs(start, 2), 5.0), Some((5.0, 12.5)));
+        assert_eq!(window.add(secs(start, 3), 15.0), Some((10.0, 35.0 / 3.0)));
+    }
+
+    #[test]
+    fn lets_a_gauge_go_down() {
+        let start = Instant::now();
+        let mut window = RateWindow::default();
+        window.add(start, 100.0);
+        assert_eq!(window.add(secs(start, 2), 60.0), Some((-40.0, -20.0)));
+    }
+
+    #[test]
+    fn needs_two_samples_for_a_rate() {
+        let start = Instant::now();
+        let mut window = counter_window();
+        assert_eq!(window.add(start, 42.0), None);
+        // A second value with no time elapsed has a change but no rate
+        assert_eq!(window.add(start, 50.0), Some((8.0, 0.0)));
+
+        let mut history = MetricHistory::counter("calls".to_string(), String::new());
+        assert_eq!(history.record_at(start, 1.0), None);
+        history.set_mode(DisplayMode::Rate);
+        assert!(!history.has_data());
+        history.set_mode(DisplayMode::Raw);
+        assert_eq!(history.get_latest(), Some(1.0));
+    }
+
+    #[test]
+    fn derives_stored_series_per_mode() {
+        let counter = [[0.0, 100.0], [2.0, 120.0], [4.0, 10.0], [4.0, 12.0]];
+        assert_eq!(DisplayMode::Raw.derive(&counter, true), counter.to_vec());
+        assert_eq!(DisplayMode::Delta.derive(&counter, true), vec![[2.0, 20.0], [4.0, 10.0], [4.0, 2.0]]);
+        assert_eq!(DisplayMode::Rate.derive(&counter, true), vec![[2.0, 10.0], [4.0, 5.0], [4.0, 0.0]]);
+
+        let gauge = [[0.0, 100.0], [2.0, 60.0]];
+        assert_eq!(DisplayMode::Delta.derive(&gauge, false), vec![[2.0, -40.0]]);
+        assert_eq!(DisplayMode::Rate.derive(&gauge, false), vec![[2.0, -20.0]]);
+
+        assert!(DisplayMode::Rate.derive(&[[0.0, 1.0]], true).is_empty());
+        assert!(DisplayMode::Delta.derive(&[], false).is_empty());
+    }
+
+    #[test]
+    fn plots_cpu_usage_as_a_share_of_all_cores() {
+        let start = Instant::now();
+        let mut cpu_seconds = MetricHistory::counter("cpu_seconds".to_string(), "s".to_string());
+        let mut cpu_usage = MetricHistory::new("cpu".to_string(), "%".to_string());
+        let scale = 100.0 / 4.0;
+
+        RethMetrics::add_rate(&mut cpu_seconds, &mut cpu_usage, Some(10.0), scale, start);
+        assert!(cpu_usage.values.is_empty());
+
+        // Two CPU seconds per second on four cores is half the machine
+        RethMetrics::add_rate(&mut cpu_seconds, &mut cpu_usage, Some(14.0), scale, secs(start, 2));
+        assert_eq!(cpu_usage.get_latest(), Some(50.0));
+
+        // A missing sample leaves the line alone; after a restart the two CPU seconds since
+        // zero count as usage, six seconds over the four second window
+        RethMetrics::add_rate(&mut cpu_seconds, &mut cpu_usage, None, scale, secs(start, 3));
+        RethMetrics::add_rate(&mut cpu_seconds, &mut cpu_usage, Some(2.0), scale, secs(start, 4));
+        assert_eq!(cpu_usage.values.len(), 2);
+        assert_eq!(cpu_usage.get_latest(), Some(37.5));
+    }
+
+    fn latency(name: &str) -> CustomMetric {
+        CustomMetric { name: name.to_string(), mode: DisplayMode::Raw, matchers: Vec::new(), group_by: None }
+    }
+
+    /// A histogram scrape with cumulative counts for the bounds 0.1, 0.5, 1 and +Inf
+    fn histogram(counts: [u32; 4], sum: f64) -> Exposition {
+        let bounds = ["0.1", "0.5", "1", "+Inf"];
+        let mut text = "# TYPE rpc_seconds histogram\n".to_string();
+        for (bound, count) in bounds.iter().zip(counts) {
+            text.push_str(&format!("rpc_seconds_bucket{{le=\"{}\"}} {}\n", bound, count));
+        }
+        text.push_str(&format!("rpc_seconds_sum {}\nrpc_seconds_count {}\n", sum, counts[3]));
+        Exposition::parse(&text)
+    }
+
+    fn line(history: &MetricHistory, name: &str) -> Option<f64> {
+        history.groups.get(name).and_then(|group| group.values.back()).map(|value| value.value)
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("line has a value");
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn interpolates_quantiles_across_buckets() {
+        let start = Instant::now();
+        let custom = latency("rpc_seconds");
+        let mut history = MetricHistory::new(custom.name.clone(), String::new());
+
+        // The first scrape only sets the baseline
+        RethMetrics::update_distribution(&mut history, &histogram([0, 0, 0, 0], 0.0), &custom, start);
+        assert!(history.groups.is_empty());
+
+        RethMetrics::update_distribution(&mut history, &histogram([10, 30, 40, 40], 12.0), &custom, secs(start, 1));
+        assert_close(line(&history, "p50"), 0.3);
+        assert_close(line(&history, "p90"), 0.8);
+        assert_close(line(&history, "p99"), 0.98);
+        assert_close(line(&history, MEAN_LINE), 0.3);
+    }
+
+    #[test]
+    fn shows_latencies_in_milliseconds() {
+        let start = Instant::now();
+        let custom = latency("rpc_seconds");
+        let mut history = MetricHistory::new(custom.name.clone(), "s".to_string());
+        RethMetrics::update_distribution(&mut history, &histogram([0, 0, 0, 0], 0.0), &custom, start);
+        RethMetrics::update_distribution(&mut history, &histogram([10, 30, 40, 40], 12.0), &custom, secs(start, 1));
+        assert_eq!(history.unit, "ms");
+        assert_close(line(&history, "p50"), 300.0);
+        assert_close(line(&history, MEAN_LINE), 300.0);
+    }
+
+    #[test]
+    fn draws_nothing_for_an_empty_or_unbounded_histogram() {
+        let start = Instant::now();
+        let custom = latency("rpc_seconds");
+
+        let mut empty = MetricHistory::new(custom.name.clone(), String::new());
+        for i in 0..3 {
+            RethMetrics::update_distribution(&mut empty, &histogram([0, 0, 0, 0], 0.0), &custom, secs(start, i));
+        }
+        assert!(empty.groups.is_empty());
+
+        // Only a +Inf bucket: no bound to place quantiles at, but the mean is still known
+        let unbounded = |count: u32, sum: f64| Exposition::parse(&format!(
+            "# TYPE rpc_seconds histogram\nrpc_seconds_bucket{{le=\"+Inf\"}} {}\nrpc_seconds_sum {}\nrpc_seconds_count {}\n",
+            count, sum, count,
+        ));
+        let mut history = MetricHistory::new(custom.name.clone(), String::new());
+        RethMetrics::update_distribution(&mut history, &unbounded(5, 1.0), &custom, start);
+        RethMetrics::update_distribution(&mut history, &unbounded(9, 3.0), &custom, secs(start, 1));
+        assert_eq!(line(&history, "p50"), None);
+        assert_close(line(&history, MEAN_LINE), 0.5);
+    }
+
+    #[test]
+    fn holds_lines_while_nothing_is_observed() {
+        let start = Instant::now();
+        let custom = latency("rpc_seconds");
+        let mut history = MetricHistory::new(custom.name.clone(), String::new());
+        RethMetrics::update_distribution(&mut history, &histogram([0, 0, 0, 0], 0.0), &custom, start);
+        RethMetrics::update_distribution(&mut history, &histogram([4, 4, 4, 4], 0.2), &custom, secs(start, 1));
+        RethMetrics::update_distribution(&mut history, &histogram([4, 4, 4, 4], 0.2), &custom, secs(start, 20));
+        assert_close(line(&history, MEAN_LINE), 0.05);
+        assert_eq!(history.groups[MEAN_LINE].values.len(), 2);
+        assert!(line(&history, "p50").is_some());
+    }
+
+    #[test]
+    fn counts_observations_per_heatmap_bucket() {
+        let mut heatmap = Heatmap::default();
+        heatmap.add(&[(0.1, 1.0), (1.0, 3.0), (f64::INFINITY, 3.0)], 1000.0);
+        assert_eq!(heatmap.bounds, vec![100.0, 1000.0, f64::INFINITY]);
+        assert!(heatmap.columns.is_empty());
+
+        heatmap.add(&[(0.1, 4.0), (1.0, 10.0), (f64::INFINITY, 12.0)], 1000.0);
+        // The node restarted: counts began again from zero
+        heatmap.add(&[(0.1, 2.0), (1.0, 2.0), (f64::INFINITY, 5.0)], 1000.0);
+        let columns: Vec<&Vec<f64>> = heatmap.columns.iter().map(|(_, column)| column).collect();
+        assert_eq!(columns, vec![&vec![3.0, 4.0, 2.0], &vec![2.0, 0.0, 3.0]]);
+        assert_eq!(heatmap.recent(), vec![(100.0, 5.0), (1000.0, 9.0), (f64::INFINITY, 14.0)]);
+
+        // New bucket bounds start the heatmap over
+        heatmap.add(&[(0.5, 1.0), (f64::INFINITY, 1.0)], 1000.0);
+        assert_eq!(heatmap.bounds, vec![500.0, f64::INFINITY]);
+        assert!(heatmap.columns.is_empty());
+    }
+}
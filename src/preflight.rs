@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use crate::reth_node::CliOption;
+
+/// Flags the desktop adds itself; the option picker leaves them out
+pub const DESKTOP_MANAGED_FLAGS: &[&str] = &[
+    "--full",
+    "--metrics",
+    "--chain",
+    "--datadir",
+    "--log.stdout.format",
+    "--log.file.directory",
+    "--log.file.format",
+    "--log.file.filter",
+    "--log.file.max-size",
+    "--log.file.max-files",
+];
+
+/// Pairs of flag prefixes that reth refuses to combine
+const CONFLICTING_FLAGS: &[(&str, &str)] = &[
+    ("--full", "--prune."),
+];
+
+/// Flag prefixes that only take effect when the server they configure is enabled
+const REQUIRES_FLAG: &[(&str, &str)] = &[
+    ("--http.", "--http"),
+    ("--ws.", "--ws"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+/// A single problem found in the launch arguments
+#[derive(Debug, Clone)]
+pub struct PreflightIssue {
+    pub severity: IssueSeverity,
+    pub flag: Option<String>,
+    pub message: String,
+}
+
+/// Result of checking the final argv before the node is spawned
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    pub issues: Vec<PreflightIssue>,
+}
+
+impl PreflightReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|issue| issue.severity == IssueSeverity::Error)
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == IssueSeverity::Error).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == IssueSeverity::Warning).count()
+    }
+
+    fn error(&mut self, flag: &str, message: String) {
+        self.issues.push(PreflightIssue { severity: IssueSeverity::Error, flag: Some(flag.to_string()), message });
+    }
+
+    fn warning(&mut self, flag: Option<&str>, message: String) {
+        self.issues.push(PreflightIssue { severity: IssueSeverity::Warning, flag: flag.map(|f| f.to_string()), message });
+    }
+}
+
+/// A flag from the argv together with the values that followed it
+struct ParsedFlag {
+    flag: String,
+    values: Vec<String>,
+}
+
+/// Group `node` arguments into flags and their values; `--flag=value` is split
+fn parse_flags(args: &[String]) -> Vec<ParsedFlag> {
+    let mut flags: Vec<ParsedFlag> = Vec::new();
+    for arg in args {
+        if arg.starts_with('-') && arg.len() > 1 && arg.parse::<f64>().is_err() {
+            match arg.split_once('=') {
+                Some((flag, value)) => flags.push(ParsedFlag { flag: flag.to_string(), values: vec![value.to_string()] }),
+                None => flags.push(ParsedFlag { flag: arg.clone(), values: Vec::new() }),
+            }
+        } else if let Some(last) = flags.last_mut() {
+            last.values.push(arg.clone());
+        }
+    }
+    flags
+}
+
+/// Check the final `reth node` arguments (everything after the binary and subcommand)
+/// against the options parsed from `reth node --help`
+pub fn validate_launch_args(args: &[String], options: &[CliOption]) -> PreflightReport {
+    let mut report = PreflightReport::default();
+    let flags = parse_flags(args);
+
+    if options.is_empty() {
+        report.warning(None, "CLI options could not be read from reth, flags were not verified".to_string());
+    }
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let mut ports: HashMap<(u16, bool), &str> = HashMap::new();
+
+    for parsed in &flags {
+        let flag = parsed.flag.as_str();
+        let (option, occurrences) = match find_option(flag, options) {
+            Some((option, occurrences)) => (Some(option), occurrences),
+            None => (None, 1),
+        };
+        let managed = DESKTOP_MANAGED_FLAGS.contains(&flag);
+
+        if option.is_none() && !managed && !options.is_empty() {
+            let message = match suggest_flag(flag, options) {
+                Some(suggestion) => format!("Unknown flag {}, did you mean {}?", flag, suggestion),
+                None => format!("Unknown flag {}", flag),
+            };
+            report.error(flag, message);
+            continue;
+        }
+
+        // Short flags and aliases count towards the option's long name
+        *seen.entry(option.map_or(flag, |option| option.name.as_str())).or_insert(0) += occurrences;
+
+        let Some(option) = option else {
+            continue;
+        };
+
+        // Value presence
+        if option.takes_value && parsed.values.is_empty() {
+            report.error(flag, format!("{} requires a value{}", flag, option.value_name.as_ref().map(|name| format!(" <{}>", name)).unwrap_or_default()));
+            continue;
+        }
+        if !option.takes_value && !parsed.values.is_empty() {
+            report.error(flag, format!("{} does not take a value, got '{}'", flag, parsed.values.join(" ")));
+            continue;
+        }
+
+        // Individual values, splitting comma-joined lists
+        let values: Vec<&str> = parsed.values
+            .iter()
+            .flat_map(|value| {
+                if option.accepts_multiple {
+                    value.split(',').map(str::trim).collect::<Vec<_>>()
+                } else {
+                    vec![value.as_str()]
+                }
+            })
+            .collect();
+
+        if let Some(possible_values) = &option.possible_values {
+            for value in &values {
+                if !possible_values.iter().any(|possible| possible == value) {
+                    report.error(flag, format!("'{}' is not a valid value for {} (expected one of: {})", value, flag, possible_values.join(", ")));
+                }
+            }
+        }
+
+        for value in &values {
+            match value_kind(option) {
+                ValueKind::Port => match value.parse::<u16>() {
+                    Ok(0) | Err(_) => report.error(flag, format!("'{}' is not a valid port for {}", value, flag)),
+                    Ok(port) => {
+                        // Discovery listens on UDP and may share its number with the TCP ports
+                        let udp = flag.contains("discovery");
+                        if let Some(other) = ports.insert((port, udp), flag) {
+                            report.error(flag, format!("Port {} is used by both {} and {}", port, other, flag));
+                        }
+                    }
+                },
+                ValueKind::SocketAddr => {
+                    if value.parse::<SocketAddr>().is_err() && value.parse::<u16>().is_err() {
+                        report.error(flag, format!("'{}' is not a valid socket address for {} (expected e.g. 127.0.0.1:9001)", value, flag));
+                    }
+                }
+                ValueKind::IpAddr => {
+                    if value.parse::<IpAddr>().is_err() {
+                        report.error(flag, format!("'{}' is not a valid IP address for {}", value, flag));
+                    }
+                }
+                ValueKind::Other => {}
+            }
+        }
+    }
+
+    // Duplicates
+    for (flag, count) in &seen {
+        let accepts_multiple = options.iter().any(|option| option.name == *flag && option.accepts_multiple);
+        if *count > 1 && !accepts_multiple {
+            report.error(flag, format!("{} is set {} times", flag, count));
+        }
+    }
+
+    // Conflicts
+    for (first, second_prefix) in CONFLICTING_FLAGS {
+        if seen.contains_key(first) {
+            for flag in seen.keys().filter(|flag| flag.starts_with(second_prefix)) {
+                report.error(flag, format!("{} conflicts with {}", flag, first));
+            }
+        }
+    }
+
+    // Settings without the server they configure
+    for (prefix, required) in REQUIRES_FLAG {
+        if !seen.contains_key(required) {
+            for flag in seen.keys().filter(|flag| flag.starts_with(prefix)) {
+                report.warning(Some(flag), format!("{} has no effect unless {} is set", flag, required));
+            }
+        }
+    }
+
+    // Keep the report stable between frames
+    report.issues.sort_by(|a, b| {
+        let severity = |issue: &PreflightIssue| if issue.severity == IssueSeverity::Error { 0 } else { 1 };
+        severity(a).cmp(&severity(b)).then_with(|| a.flag.cmp(&b.flag))
+    });
+    report
+}
+
+/// Look a flag up by long name, short flag or alias. Repeated short count flags such as
+/// `-vvv` match their option once per repetition; the count is returned alongside it.
+fn find_option<'a>(flag: &str, options: &'a [CliOption]) -> Option<(&'a CliOption, usize)> {
+    let matches = |option: &CliOption, flag: &str| {
+        option.name == flag
+            || option.short.as_deref() == Some(flag)
+            || option.aliases.iter().any(|alias| alias == flag)
+    };
+    if let Some(option) = options.iter().find(|option| matches(option, flag)) {
+        return Some((option, 1));
+    }
+
+    let letters = flag.strip_prefix('-').filter(|letters| !letters.starts_with('-'))?;
+    let first = letters.chars().next()?;
+    if letters.len() < 2 || letters.chars().any(|letter| letter != first) {
+        return None;
+    }
+    let short = format!("-{}", first);
+    options
+        .iter()
+        .find(|option| matches(option, &short) && !option.takes_value && option.accepts_multiple)
+        .map(|option| (option, letters.chars().count()))
+}
+
+enum ValueKind {
+    Port,
+    SocketAddr,
+    IpAddr,
+    Other,
+}
+
+/// Infer the value format from the value placeholder or flag name
+fn value_kind(option: &CliOption) -> ValueKind {
+    let value_name = option.value_name.as_deref().unwrap_or("").to_uppercase();
+    if value_name == "PORT" || value_name.ends_with("_PORT") || option.name.ends_with(".port") {
+        ValueKind::Port
+    } else if value_name.contains("SOCKET") || option.name == "--metrics" {
+        ValueKind::SocketAddr
+    } else if value_name == "ADDR" || value_name.ends_with("_ADDR") || option.name.ends_with(".addr") {
+        ValueKind::IpAddr
+    } else {
+        ValueKind::Other
+    }
+}
+
+/// Closest known flag by edit distance, for typos like --http.prot
+fn suggest_flag(flag: &str, options: &[CliOption]) -> Option<String> {
+    options
+        .iter()
+        .map(|option| (edit_distance(flag, &option.name), &option.name))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name.clone())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_help::parse_help;
+
+    fn check(args: &[&str]) -> PreflightReport {
+        let options = parse_help(include_str!("../node-help.txt"));
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        validate_launch_args(&args, &options)
+    }
+
+    fn messages(report: &PreflightReport, severity: IssueSeverity) -> Vec<&str> {
+        report.issues.iter().filter(|issue| issue.severity == severity).map(|issue| issue.message.as_str()).collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_command_line() {
+        let report = check(&["--http", "--http.port", "8545", "--http.api", "eth,net", "--metrics", "127.0.0.1:9001"]);
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn suggests_the_closest_flag_for_a_typo() {
+        let report = check(&["--http", "--http.prot", "8545", "--no-such-flag-anywhere"]);
+        assert_eq!(messages(&report, IssueSeverity::Error), vec![
+            "Unknown flag --http.prot, did you mean --http.port?",
+            "Unknown flag --no-such-flag-anywhere",
+        ]);
+    }
+
+    #[test]
+    fn rejects_values_outside_the_possible_values() {
+        let report = check(&["--http", "--http.api=eth,bogus", "--log.stdout.format", "xml"]);
+        let errors = messages(&report, IssueSeverity::Error);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].starts_with("'bogus' is not a valid value for --http.api"));
+        assert!(errors[1].starts_with("'xml' is not a valid value for --log.stdout.format"));
+    }
+
+    #[test]
+    fn validates_ports_and_socket_addresses() {
+        let report = check(&["--http", "--http.port", "70000", "--authrpc.port", "0", "--metrics", "localhost"]);
+        assert_eq!(messages(&report, IssueSeverity::Error), vec![
+            "'0' is not a valid port for --authrpc.port",
+            "'70000' is not a valid port for --http.port",
+            "'localhost' is not a valid socket address for --metrics (expected e.g. 127.0.0.1:9001)",
+        ]);
+
+        let report = check(&["--http", "--http.port", "30303", "--port", "30303", "--discovery.port", "30303"]);
+        assert_eq!(messages(&report, IssueSeverity::Error), vec!["Port 30303 is used by both --http.port and --port"]);
+    }
+
+    #[test]
+    fn reports_duplicate_flags() {
+        let report = check(&["--trusted-only", "--trusted-only", "--trusted-peers", "enode://a", "--trusted-peers", "enode://b"]);
+        assert_eq!(messages(&report, IssueSeverity::Error), vec!["--trusted-only is set 2 times"]);
+    }
+
+    #[test]
+    fn reports_pruning_with_full() {
+        let report = check(&["--full", "--prune.senderrecovery.full", "--block-interval", "5"]);
+        assert_eq!(messages(&report, IssueSeverity::Error), vec!["--prune.senderrecovery.full conflicts with --full"]);
+    }
+
+    #[test]
+    fn warns_about_server_settings_without_the_server() {
+        let report = check(&["--http.port", "8545", "--ws.addr", "0.0.0.0"]);
+        assert!(!report.has_errors());
+        assert_eq!(messages(&report, IssueSeverity::Warning), vec![
+            "--http.port has no effect unless --http is set",
+            "--ws.addr has no effect unless --ws is set",
+        ]);
+    }
+
+    #[test]
+    fn resolves_short_flags_and_aliases() {
+        let report = check(&["-d", "-vvv", "--rpc-max-connections", "100", "--http"]);
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+
+        let report = check(&["-q", "--quiet"]);
+        assert_eq!(messages(&report, IssueSeverity::Error), vec!["--quiet is set 2 times"]);
+
+        // -vvv counts three times but verbosity may repeat; a repeated -q may not
+        let report = check(&["-vvv", "-v", "-qq"]);
+        let errors = messages(&report, IssueSeverity::Error);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].starts_with("Unknown flag -qq"));
+    }
+
+    #[test]
+    fn warns_when_the_schema_is_missing() {
+        let report = validate_launch_args(&["--anything".to_string()], &[]);
+        assert!(!report.has_errors());
+        assert_eq!(report.warning_count(), 1);
+    }
+}
@@ -0,0 +1,574 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Metric type from a `# TYPE` line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Summary,
+    Histogram,
+    Untyped,
+}
+
+impl MetricType {
+    fn parse(value: &str) -> Self {
+        match value {
+            "counter" => MetricType::Counter,
+            "gauge" => MetricType::Gauge,
+            "summary" => MetricType::Summary,
+            "histogram" => MetricType::Histogram,
+            _ => MetricType::Untyped,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Summary => "summary",
+            MetricType::Histogram => "histogram",
+            MetricType::Untyped => "untyped",
+        }
+    }
+}
+
+/// Label names and values in the order they were written
+pub type Labels = Vec<(String, String)>;
+
+/// One line of the exposition: a series and its value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub name: String, // Full sample name, e.g. `x_sum` or `x_bucket` for a summary or histogram `x`
+    pub labels: Labels,
+    pub value: f64,
+    pub timestamp_ms: Option<i64>,
+}
+
+impl Sample {
+    pub fn label(&self, name: &str) -> Option<&str> {
+        self.labels.iter().find(|(label, _)| label == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Samples sharing a metric name, with the metadata from its `# HELP` and `# TYPE` lines
+#[derive(Debug, Clone)]
+pub struct MetricFamily {
+    pub name: String,
+    pub help: Option<String>,
+    pub metric_type: MetricType,
+    pub samples: Vec<Sample>,
+}
+
+impl MetricFamily {
+    fn new(name: &str) -> Self {
+        Self { name: name.to_string(), help: None, metric_type: MetricType::Untyped, samples: Vec::new() }
+    }
+}
+
+/// A parsed Prometheus text exposition (format 0.0.4)
+#[derive(Debug, Clone, Default)]
+pub struct Exposition {
+    families: Vec<MetricFamily>, // In the order they first appear
+    index: HashMap<String, usize>, // Family name -> position in `families`
+    errors: Vec<String>, // Lines that couldn't be parsed, skipped
+}
+
+impl Exposition {
+    pub fn parse(text: &str) -> Self {
+        let mut exposition = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                exposition.parse_comment(comment.trim_start());
+                continue;
+            }
+            match parse_sample(line) {
+                Ok(sample) => {
+                    let family = exposition.family_for_sample(&sample.name);
+                    exposition.families[family].samples.push(sample);
+                }
+                Err(e) => exposition.errors.push(format!("line {}: {}", number + 1, e)),
+            }
+        }
+        exposition
+    }
+
+    /// `HELP name text` and `TYPE name type`; other comments are ignored
+    fn parse_comment(&mut self, comment: &str) {
+        let mut parts = comment.splitn(3, [' ', '\t']);
+        let (Some(keyword), Some(name)) = (parts.next(), parts.next()) else {
+            return;
+        };
+        let rest = parts.next().unwrap_or("").trim();
+        match keyword {
+            "HELP" => {
+                let index = self.family_index(name);
+                self.families[index].help = Some(unescape(rest, false));
+            }
+            "TYPE" => {
+                let index = self.family_index(name);
+                self.families[index].metric_type = MetricType::parse(rest);
+            }
+            _ => {}
+        }
+    }
+
+    fn family_index(&mut self, name: &str) -> usize {
+        if let Some(index) = self.index.get(name) {
+            return *index;
+        }
+        self.families.push(MetricFamily::new(name));
+        self.index.insert(name.to_string(), self.families.len() - 1);
+        self.families.len() - 1
+    }
+
+    /// The family a sample belongs to: `x_sum`, `x_count` and `x_bucket` go with a summary or histogram `x`
+    fn family_for_sample(&mut self, sample_name: &str) -> usize {
+        for suffix in ["_sum", "_count", "_bucket"] {
+            if let Some(base) = sample_name.strip_suffix(suffix) {
+                if let Some(index) = self.index.get(base) {
+                    let family_type = self.families[*index].metric_type;
+                    let fits = match suffix {
+                        "_bucket" => family_type == MetricType::Histogram,
+                        _ => matches!(family_type, MetricType::Summary | MetricType::Histogram),
+                    };
+                    if fits {
+                        return *index;
+                    }
+                }
+            }
+        }
+        self.family_index(sample_name)
+    }
+
+    pub fn families(&self) -> &[MetricFamily] {
+        &self.families
+    }
+
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    pub fn family(&self, name: &str) -> Option<&MetricFamily> {
+        self.index.get(name).map(|&position| &self.families[position])
+    }
+
+    /// Cumulative bucket counts of histogram `name` as (upper bound, count), ascending,
+    /// summed over the series `filter` accepts
+    pub fn buckets(&self, name: &str, filter: impl Fn(&Sample) -> bool) -> Vec<(f64, f64)> {
+        let bucket_name = format!("{}_bucket", name);
+        let mut buckets: Vec<(f64, f64)> = Vec::new();
+        for sample in self.samples(&bucket_name).filter(|sample| filter(sample)) {
+            let Some(bound) = sample.label("le").and_then(parse_value) else {
+                continue;
+            };
+            match buckets.iter_mut().find(|(existing, _)| *existing == bound) {
+                Some((_, count)) => *count += sample.value,
+                None => buckets.push((bound, sample.value)),
+            }
+        }
+        buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+        buckets
+    }
+
+    /// Every sample with this exact sample name
+    pub fn samples<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Sample> + 'a {
+        self.families
+            .iter()
+            .flat_map(|family| family.samples.iter())
+            .filter(move |sample| sample.name == name)
+    }
+
+    /// Whether a sample name only ever grows: counters, and the `_sum`, `_count` and
+    /// `_bucket` series of summaries and histograms
+    pub fn is_cumulative(&self, name: &str) -> bool {
+        self.families.iter().any(|family| {
+            family.samples.iter().any(|sample| sample.name == name)
+                && match family.metric_type {
+                    MetricType::Counter => true,
+                    MetricType::Summary | MetricType::Histogram => family.name != name,
+                    _ => false,
+                }
+        })
+    }
+
+    /// A single value for a sample name: the unlabelled series, or for counters and gauges
+    /// with labels the sum across series (e.g. total size over all tables).
+    /// Quantiles and buckets have no meaningful sum and give None.
+    pub fn value(&self, name: &str) -> Option<f64> {
+        self.value_where(name, |_| true)
+    }
+
+    /// Like `value`, over only the series `filter` accepts (e.g. one table)
+    pub fn value_where(&self, name: &str, filter: impl Fn(&Sample) -> bool) -> Option<f64> {
+        combine(&self.samples(name).filter(|sample| filter(sample)).collect::<Vec<_>>())
+    }
+
+    /// One value per value of `label` among the series `filter` accepts, each combined as
+    /// in `value`. Series without the label are left out.
+    pub fn values_by(&self, name: &str, label: &str, filter: impl Fn(&Sample) -> bool) -> BTreeMap<String, f64> {
+        let mut groups: BTreeMap<&str, Vec<&Sample>> = BTreeMap::new();
+        for sample in self.samples(name).filter(|sample| filter(sample)) {
+            if let Some(value) = sample.label(label) {
+                groups.entry(value).or_default().push(sample);
+            }
+        }
+        groups
+            .into_iter()
+            .filter_map(|(value, samples)| Some((value.to_string(), combine(&samples)?)))
+            .collect()
+    }
+}
+
+fn combine(samples: &[&Sample]) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    if let Some(sample) = samples.iter().find(|sample| sample.labels.is_empty()) {
+        return Some(sample.value);
+    }
+    let summable = samples
+        .iter()
+        .all(|sample| sample.label("quantile").is_none() && sample.label("le").is_none());
+    summable.then(|| samples.iter().map(|sample| sample.value).sum())
+}
+
+/// Estimate quantile `q` from cumulative histogram buckets by interpolating linearly inside
+/// the bucket the rank falls in, as Prometheus' `histogram_quantile` does
+pub fn bucket_quantile(q: f64, buckets: &[(f64, f64)]) -> Option<f64> {
+    let total = buckets.last()?.1;
+    if total <= 0.0 {
+        return None;
+    }
+    let rank = q * total;
+    let (mut lower, mut below) = (0.0, 0.0);
+    for (i, &(bound, count)) in buckets.iter().enumerate() {
+        if count >= rank {
+            // Past the highest finite bound all we know is the bound itself, if there is one
+            if bound.is_infinite() {
+                return (i > 0).then_some(lower);
+            }
+            if count <= below {
+                return Some(bound);
+            }
+            return Some(lower + (bound - lower) * (rank - below) / (count - below));
+        }
+        (lower, below) = (bound, count);
+    }
+    None
+}
+
+/// `name{label="value",...} value [timestamp]`
+fn parse_sample(line: &str) -> Result<Sample, String> {
+    let name_end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(line.len());
+    let name = &line[..name_end];
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("invalid metric name in {:?}", line));
+    }
+
+    let mut rest = line[name_end..].trim_start();
+    let mut labels = Vec::new();
+    if let Some(label_text) = rest.strip_prefix('{') {
+        let (parsed, remaining) = parse_labels(label_text)?;
+        labels = parsed;
+        rest = remaining.trim_start();
+    }
+
+    let mut fields = rest.split_whitespace();
+    let value = fields.next().ok_or_else(|| format!("missing value for {}", name))?;
+    let value = parse_value(value).ok_or_else(|| format!("invalid value {:?} for {}", value, name))?;
+    let timestamp_ms = match fields.next() {
+        Some(timestamp) => Some(timestamp.parse::<i64>().map_err(|_| format!("invalid timestamp {:?} for {}", timestamp, name))?),
+        None => None,
+    };
+    if fields.next().is_some() {
+        return Err(format!("unexpected text after the value of {}", name));
+    }
+
+    Ok(Sample { name: name.to_string(), labels, value, timestamp_ms })
+}
+
+/// Labels after the opening brace; returns them and the text after the closing brace
+fn parse_labels(text: &str) -> Result<(Labels, &str), String> {
+    let mut labels = Vec::new();
+    let mut rest = text.trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix('}') {
+            return Ok((labels, after));
+        }
+        let (name, after_name) = rest.split_once('=').ok_or("label without a value")?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("empty label name".to_string());
+        }
+        let quoted = after_name.trim_start().strip_prefix('"').ok_or("label value isn't quoted")?;
+
+        // Find the closing quote, skipping escaped characters
+        let mut end = None;
+        let mut escaped = false;
+        for (index, c) in quoted.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    end = Some(index);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let end = end.ok_or("unterminated label value")?;
+        labels.push((name.to_string(), unescape(&quoted[..end], true)));
+
+        rest = quoted[end + 1..].trim_start();
+        if let Some(after) = rest.strip_prefix(',') {
+            rest = after.trim_start();
+        } else if !rest.starts_with('}') {
+            return Err("expected ',' or '}' after a label".to_string());
+        }
+    }
+}
+
+/// Values may also be `NaN`, `+Inf` and `-Inf`
+fn parse_value(value: &str) -> Option<f64> {
+    match value {
+        "NaN" => Some(f64::NAN),
+        "+Inf" | "Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        _ => value.parse().ok(),
+    }
+}
+
+/// Undo `\\` and `\n` escapes, plus `\"` inside label values
+fn unescape(text: &str, quotes: bool) -> String {
+    if !text.contains('\\') {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('\\') => result.push('\\'),
+            Some('"') if quotes => result.push('"'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../metrics.txt");
+
+    fn find_family<'a>(exposition: &'a Exposition, name: &str) -> Option<&'a MetricFamily> {
+        exposition.families().iter().find(|family| family.name == name)
+    }
+
+    #[test]
+    fn parses_the_fixture_without_errors() {
+        let exposition = Exposition::parse(FIXTURE);
+        assert!(exposition.errors().is_empty(), "{:?}", exposition.errors());
+
+        let sample_lines = FIXTURE.lines().filter(|line| !line.is_empty() && !line.starts_with('#')).count();
+        let samples: usize = exposition.families().iter().map(|family| family.samples.len()).sum();
+        assert_eq!(samples, sample_lines);
+
+        let count = |metric_type| exposition.families().iter().filter(|family| family.metric_type == metric_type).count();
+        assert_eq!(count(MetricType::Counter), 125);
+        assert_eq!(count(MetricType::Gauge), 106);
+        assert_eq!(count(MetricType::Summary), 71);
+        assert_eq!(count(MetricType::Untyped), 0);
+    }
+
+    #[test]
+    fn keeps_help_and_type() {
+        let exposition = Exposition::parse(FIXTURE);
+        let peers = find_family(&exposition, "reth_network_connected_peers").unwrap();
+        assert_eq!(peers.metric_type, MetricType::Gauge);
+        assert_eq!(peers.help.as_deref(), Some("Number of currently connected peers"));
+        assert_eq!(exposition.value("reth_network_connected_peers"), Some(5.0));
+
+        let cpu = find_family(&exposition, "reth_process_cpu_seconds_total").unwrap();
+        assert_eq!(cpu.metric_type, MetricType::Counter);
+        assert_eq!(exposition.value("reth_process_cpu_seconds_total"), Some(17.0));
+    }
+
+    #[test]
+    fn keeps_every_labelled_series() {
+        let exposition = Exposition::parse(FIXTURE);
+        let calls: Vec<&Sample> = exposition.samples("reth_rpc_server_calls_successful_total").collect();
+        assert!(calls.len() > 1);
+        assert!(calls.iter().any(|sample| sample.label("method") == Some("eth_getAccountInfo")));
+        assert!(calls.iter().any(|sample| sample.label("method") == Some("admin_nodeInfo")));
+
+        let mut methods: Vec<&str> = calls.iter().filter_map(|sample| sample.label("method")).collect();
+        methods.sort();
+        methods.dedup();
+        assert_eq!(methods.len(), calls.len(), "each method is its own series");
+    }
+
+    #[test]
+    fn groups_summary_quantiles_sum_and_count() {
+        let exposition = Exposition::parse(FIXTURE);
+        let family = find_family(&exposition, "reth_database_transaction_close_duration_seconds").unwrap();
+        assert_eq!(family.metric_type, MetricType::Summary);
+
+        let abort = |sample: &&Sample| sample.label("mode") == Some("read-write") && sample.label("outcome") == Some("abort");
+        let quantiles: Vec<&str> = family.samples
+            .iter()
+            .filter(abort)
+            .filter_map(|sample| sample.label("quantile"))
+            .collect();
+        assert_eq!(quantiles, ["0", "0.5", "0.9", "0.95", "0.99", "0.999", "1"]);
+        assert!(family.samples.iter().filter(abort).any(|sample| sample.name.ends_with("_sum")));
+        assert!(family.samples.iter().filter(abort).any(|sample| sample.name.ends_with("_count")));
+        assert!(find_family(&exposition, "reth_database_transaction_close_duration_seconds_sum").is_none());
+
+        // Quantiles don't add up to anything meaningful
+        assert_eq!(exposition.value("reth_database_transaction_close_duration_seconds"), None);
+    }
+
+    #[test]
+    fn parses_special_values_and_timestamps() {
+        let text = "\
+# TYPE temperature gauge
+temperature{room=\"a\"} NaN
+temperature{room=\"b\"} +Inf 1700000000000
+temperature{room=\"c\"} -Inf
+temperature{room=\"d\"} 1.5e3 -5
+";
+        let exposition = Exposition::parse(text);
+        assert!(exposition.errors().is_empty(), "{:?}", exposition.errors());
+        let samples: Vec<&Sample> = exposition.samples("temperature").collect();
+        assert!(samples[0].value.is_nan());
+        assert_eq!(samples[1].value, f64::INFINITY);
+        assert_eq!(samples[1].timestamp_ms, Some(1_700_000_000_000));
+        assert_eq!(samples[2].value, f64::NEG_INFINITY);
+        assert_eq!(samples[3].value, 1500.0);
+        assert_eq!(samples[3].timestamp_ms, Some(-5));
+        assert_eq!(samples[0].timestamp_ms, None);
+    }
+
+    #[test]
+    fn parses_histograms() {
+        let text = "\
+# HELP request_seconds Request latency.
+# TYPE request_seconds histogram
+request_seconds_bucket{le=\"0.1\"} 3
+request_seconds_bucket{le=\"1\"} 7
+request_seconds_bucket{le=\"+Inf\"} 9
+request_seconds_sum 4.2
+request_seconds_count 9
+";
+        let exposition = Exposition::parse(text);
+        let family = find_family(&exposition, "request_seconds").unwrap();
+        assert_eq!(family.metric_type, MetricType::Histogram);
+        assert_eq!(family.samples.len(), 5);
+        let buckets: Vec<&str> = exposition.samples("request_seconds_bucket").filter_map(|sample| sample.label("le")).collect();
+        assert_eq!(buckets, ["0.1", "1", "+Inf"]);
+        assert_eq!(exposition.value("request_seconds_bucket"), None);
+        assert_eq!(exposition.value("request_seconds_count"), Some(9.0));
+        assert_eq!(exposition.value("request_seconds_sum"), Some(4.2));
+    }
+
+    #[test]
+    fn estimates_histogram_quantiles() {
+        let text = "\
+# TYPE request_seconds histogram
+request_seconds_bucket{method=\"a\",le=\"0.1\"} 2
+request_seconds_bucket{method=\"a\",le=\"1\"} 6
+request_seconds_bucket{method=\"a\",le=\"+Inf\"} 8
+request_seconds_bucket{method=\"b\",le=\"0.1\"} 2
+request_seconds_bucket{method=\"b\",le=\"1\"} 2
+request_seconds_bucket{method=\"b\",le=\"+Inf\"} 2
+";
+        let exposition = Exposition::parse(text);
+        let buckets = exposition.buckets("request_seconds", |_| true);
+        assert_eq!(buckets, [(0.1, 4.0), (1.0, 8.0), (f64::INFINITY, 10.0)]);
+
+        assert_eq!(bucket_quantile(0.2, &buckets), Some(0.05));
+        assert_eq!(bucket_quantile(0.6, &buckets), Some(0.55));
+        assert_eq!(bucket_quantile(0.99, &buckets), Some(1.0), "the +Inf bucket gives its lower bound");
+
+        let only_b = exposition.buckets("request_seconds", |sample| sample.label("method") == Some("b"));
+        assert_eq!(bucket_quantile(0.5, &only_b), Some(0.05));
+        assert_eq!(bucket_quantile(0.5, &[(1.0, 0.0), (f64::INFINITY, 0.0)]), None);
+        assert_eq!(bucket_quantile(0.5, &[(f64::INFINITY, 5.0)]), None, "no finite bound to estimate from");
+    }
+
+    #[test]
+    fn unescapes_labels_and_help() {
+        let text = "\
+# HELP odd A \\\\ backslash\\nand a newline
+odd{path=\"C:\\\\data\",quote=\"say \\\"hi\\\"\",line=\"a\\nb\",} 1
+";
+        let exposition = Exposition::parse(text);
+        assert!(exposition.errors().is_empty(), "{:?}", exposition.errors());
+        let family = find_family(&exposition, "odd").unwrap();
+        assert_eq!(family.help.as_deref(), Some("A \\ backslash\nand a newline"));
+        assert_eq!(family.metric_type, MetricType::Untyped);
+        let sample = &family.samples[0];
+        assert_eq!(sample.label("path"), Some("C:\\data"));
+        assert_eq!(sample.label("quote"), Some("say \"hi\""));
+        assert_eq!(sample.label("line"), Some("a\nb"));
+    }
+
+    #[test]
+    fn sums_labelled_counters() {
+        let text = "\
+# TYPE table_bytes gauge
+table_bytes{table=\"Headers\"} 10
+table_bytes{table=\"Bodies\"} 32
+";
+        assert_eq!(Exposition::parse(text).value("table_bytes"), Some(42.0));
+    }
+
+    #[test]
+    fn filters_and_groups_by_label() {
+        let exposition = Exposition::parse(FIXTURE);
+        let table = |sample: &Sample| sample.label("table") == Some("PlainAccountState");
+        assert_eq!(exposition.value_where("reth_db_table_size", table), Some(393216.0));
+
+        let sizes = exposition.values_by("reth_db_table_size", "table", |_| true);
+        assert_eq!(sizes.len(), 28);
+        assert_eq!(sizes.get("AccountsHistory"), Some(&638976.0));
+
+        let gets = exposition.values_by("reth_database_operation_calls_total", "table", |sample| sample.label("operation") == Some("get"));
+        assert!(gets.contains_key("PlainAccountState"));
+
+        let latency = exposition.values_by("reth_database_transaction_close_duration_seconds", "mode", |_| true);
+        assert!(latency.is_empty(), "quantiles are not summed");
+    }
+
+    #[test]
+    fn reports_malformed_lines() {
+        let text = "\
+good 1
+bad{label=\"unterminated} 1
+no_value
+worse{x=\"1\"} one
+good_too 2
+";
+        let exposition = Exposition::parse(text);
+        assert_eq!(exposition.errors().len(), 3, "{:?}", exposition.errors());
+        assert!(exposition.errors()[0].starts_with("line 2:"));
+        let names: Vec<&str> = exposition.families().iter().map(|family| family.name.as_str()).collect();
+        assert_eq!(names, ["good", "good_too"]);
+    }
+}
@@ -1,139 +1,109 @@
-use std::collections::VecDeque;
 use std::process::{Command, Stdio, Child};
-use std::io::{BufRead, BufReader, SeekFrom, Seek};
-use std::sync::{Arc, Mutex};
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
 use std::thread;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs::File;
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use crate::settings::DesktopSettings;
+use crate::exit_report::NodeExitReport;
+use crate::run_file::{self, RunFile};
+use crate::discovery::{self, DiscoveredProcess};
+use crate::log_record::{LogLevel, LogRecord, DESKTOP_TARGET};
+use crate::log_tailer::LogTailer;
+use crate::log_ring::{self, LogRing, LogSender, StopFlag};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CliOption {
     pub name: String,
-    pub description: String,
+    pub description: String, // First line of the help text
     pub takes_value: bool,
     pub value_name: Option<String>,
     pub possible_values: Option<Vec<String>>,
     pub accepts_multiple: bool,
+    #[serde(default)]
+    pub short: Option<String>,
+    #[serde(default)]
+    pub default_value: Option<String>,
+    #[serde(default)]
+    pub env: Option<String>,
+    #[serde(default)]
+    pub section: String, // Clap help heading, e.g. "Networking"
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub help: String, // Full multi-line help text
 }
 
-#[derive(Debug, Clone)]
-pub struct LogLine {
-    pub timestamp: String,
-    pub content: String,
-    pub level: LogLevel,
-}
-
-#[derive(Debug, Clone)]
-pub enum LogLevel {
-    Info,
-    Warn,
-    Error,
-    Debug,
-    Trace,
-}
-
-impl LogLevel {
-    fn from_content(content: &str) -> Self {
-        let lower = content.to_lowercase();
-        if lower.contains("error") || lower.contains("err") {
-            LogLevel::Error
-        } else if lower.contains("warn") || lower.contains("warning") {
-            LogLevel::Warn
-        } else if lower.contains("debug") {
-            LogLevel::Debug
-        } else if lower.contains("trace") {
-            LogLevel::Trace
-        } else {
-            LogLevel::Info
-        }
-    }
-}
-
-impl LogLine {
-    /// Remove Reth's timestamp from the log content
-    /// Reth timestamps follow the pattern: 2025-07-03T19:20:27.1514252
-    fn clean_reth_timestamp(content: &str) -> String {
-        // Look for the pattern YYYY-MM-DDTHH:MM:SS.microseconds
-        // We'll find the first occurrence and remove everything up to the first space after it
-        
-        // Find a pattern that looks like: 4 digits, dash, 2 digits, dash, 2 digits, T, etc.
-        let chars: Vec<char> = content.chars().collect();
-        let len = chars.len();
-        
-        // Look for timestamp pattern starting position
-        for i in 0..len.saturating_sub(19) { // minimum timestamp length is about 19 chars
-            // Check if we have YYYY-MM-DDTHH:MM:SS pattern starting at position i
-            if i + 19 < len &&
-               chars[i].is_ascii_digit() && chars[i+1].is_ascii_digit() && 
-               chars[i+2].is_ascii_digit() && chars[i+3].is_ascii_digit() &&
-               chars[i+4] == '-' &&
-               chars[i+5].is_ascii_digit() && chars[i+6].is_ascii_digit() &&
-               chars[i+7] == '-' &&
-               chars[i+8].is_ascii_digit() && chars[i+9].is_ascii_digit() &&
-               chars[i+10] == 'T' &&
-               chars[i+11].is_ascii_digit() && chars[i+12].is_ascii_digit() &&
-               chars[i+13] == ':' &&
-               chars[i+14].is_ascii_digit() && chars[i+15].is_ascii_digit() &&
-               chars[i+16] == ':' &&
-               chars[i+17].is_ascii_digit() && chars[i+18].is_ascii_digit() {
-                
-                // Found timestamp start, now find where it ends (look for space or next non-timestamp char)
-                let mut end_pos = i + 19;
-                while end_pos < len && (chars[end_pos].is_ascii_digit() || chars[end_pos] == '.') {
-                    end_pos += 1;
-                }
-                
-                // Check for timezone indicator (Z or +/-offset)
-                if end_pos < len && (chars[end_pos] == 'Z' || chars[end_pos] == '+' || chars[end_pos] == '-') {
-                    end_pos += 1;
-                    // If it's + or -, skip the offset (e.g., +00:00)
-                    if end_pos > 0 && (chars[end_pos-1] == '+' || chars[end_pos-1] == '-') {
-                        while end_pos < len && (chars[end_pos].is_ascii_digit() || chars[end_pos] == ':') {
-                            end_pos += 1;
-                        }
-                    }
-                }
-                
-                // Skip any trailing spaces
-                while end_pos < len && chars[end_pos].is_whitespace() {
-                    end_pos += 1;
-                }
-                
-                // Return the string with the timestamp portion removed
-                let before = &content[0..i];
-                let after = &content[end_pos..];
-                return format!("{}{}", before, after).trim().to_string();
-            }
-        }
-        
-        // If no timestamp pattern found, return original content
-        content.to_string()
-    }
+/// Where the node's log output is read from
+pub enum LogSource {
+    Pipe, // stdout and stderr of the process we spawned
+    File(PathBuf),
+    Unavailable(String), // Why there is nothing to read, e.g. an external node without file logging
 }
 
 pub struct RethNode {
     process: Option<Child>,
-    log_buffer: Arc<Mutex<VecDeque<LogLine>>>,
-    log_receiver: Option<mpsc::UnboundedReceiver<LogLine>>,
+    log_ring: Arc<LogRing>, // Every line from the node and the desktop, filled by the ingestion thread
+    log_sender: LogSender,
+    tail_stop: Option<StopFlag>, // Stops the thread following the current log file
+    output_readers: Vec<thread::JoinHandle<()>>, // Threads reading the managed process's stdout and stderr
+    run_first_seq: u64, // Ring sequence number of the first line of the current run
     is_running: bool,
     external_log_path: Option<PathBuf>,
     last_external_check: std::time::Instant,
     launch_command: Option<Vec<String>>,
+    stop_requested_at: Option<Instant>,
+    shutdown_timeout: Duration,
+    last_custom_args: Vec<String>,
+    last_exit: Option<NodeExitReport>,
+    started_at: Option<(Instant, chrono::DateTime<chrono::Local>)>,
+    connected_at: Option<Instant>, // When we attached to an external process
+    no_log_reason: Option<String>, // Why an external node's output can't be shown
+    adopted_pid: Option<u32>, // Detached process re-adopted from the PID file
+    detached: bool,
+    external_process: Option<DiscoveredProcess>, // External node found via process discovery
+    original_argv: Option<Vec<String>>, // Full argv when the node was taken over from an external process
+    original_cwd: Option<PathBuf>, // Working directory the taken over node was started in
 }
 
+/// Number of trailing log lines kept with a process exit
+const EXIT_LOG_LINES: usize = 200;
+
+/// How long to wait for the output readers to reach the end of a dead process's pipes
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Lines held in the log ring, matching what the log viewer keeps
+const LOG_RING_LINES: usize = 100_000;
+
 impl RethNode {
     pub fn new() -> Self {
+        let (log_ring, writer) = LogRing::new(LOG_RING_LINES);
+        let log_sender = log_ring::spawn_ingest(log_ring.clone(), writer);
         Self {
             process: None,
-            log_buffer: Arc::new(Mutex::new(VecDeque::new())),
-            log_receiver: None,
+            log_ring,
+            log_sender,
+            tail_stop: None,
+            output_readers: Vec::new(),
+            run_first_seq: 0,
             is_running: false,
             external_log_path: None,
             last_external_check: std::time::Instant::now(),
             launch_command: None,
+            stop_requested_at: None,
+            shutdown_timeout: Duration::from_secs(60),
+            last_custom_args: Vec::new(),
+            last_exit: None,
+            started_at: None,
+            connected_at: None,
+            no_log_reason: None,
+            adopted_pid: None,
+            detached: false,
+            external_process: None,
+            original_argv: None,
+            original_cwd: None,
         }
     }
 
@@ -142,76 +112,94 @@ impl RethNode {
             return Err("Reth node is already running".into());
         }
 
-        // Create channel for log communication
-        let (log_sender, log_receiver) = mpsc::unbounded_channel();
-        self.log_receiver = Some(log_receiver);
-
-        // Determine log directory path based on platform
-        let log_dir = Self::get_default_log_directory();
-        
-        // Ensure log directory exists
-        if let Some(ref log_path) = log_dir {
-            if let Some(parent) = log_path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-        }
-        
-        // Build the command and track it for display
-        let mut command = Command::new(reth_path);
-        let mut command_parts = vec![
-            reth_path.to_string(), 
-            "node".to_string(),
-        ];
+        let args = Self::build_launch_args(custom_args, settings);
+        self.prepare_log_directory(&args)?;
         
-        command.arg("node");
+        self.original_argv = None;
+        self.original_cwd = None;
+        self.spawn_node(reth_path, args, custom_args, None, settings)
+    }
+
+    /// Arguments passed to the reth binary for a desktop launch: the configured defaults followed by the custom arguments
+    pub fn build_launch_args(custom_args: &[String], settings: &DesktopSettings) -> Vec<String> {
+        let mut args = vec!["node".to_string()];
         
         // Add configurable core parameters
         if settings.reth_defaults.enable_full_node {
-            command.arg("--full");
-            command_parts.push("--full".to_string());
+            args.push("--full".to_string());
         }
         
         if settings.reth_defaults.enable_metrics {
-            command.arg("--metrics").arg(&settings.reth_defaults.metrics_address);
-            command_parts.push("--metrics".to_string());
-            command_parts.push(settings.reth_defaults.metrics_address.clone());
+            args.push("--metrics".to_string());
+            args.push(settings.reth_defaults.metrics_address.clone());
         }
         
         // Add chain parameter
-        command.arg("--chain").arg(&settings.reth_defaults.chain);
-        command_parts.push("--chain".to_string());
-        command_parts.push(settings.reth_defaults.chain.clone());
+        args.push("--chain".to_string());
+        args.push(settings.reth_defaults.chain.clone());
         
         // Add datadir parameter
-        command.arg("--datadir").arg(&settings.reth_defaults.datadir);
-        command_parts.push("--datadir".to_string());
-        command_parts.push(settings.reth_defaults.datadir.clone());
+        args.push("--datadir".to_string());
+        args.push(settings.reth_defaults.datadir.clone());
+        
+        // Add stdout logging and file logging configuration
+        Self::append_desktop_log_args(&mut args, settings);
+        
+        // Add custom arguments from settings
+        args.extend(custom_args.iter().cloned());
+        args
+    }
+
+    /// Launch a node as a managed child from a complete argv, e.g. one taken over from an external process.
+    /// The original flags are kept; the desktop's log and metrics flags are added where the argv lacks them.
+    /// The node runs in `cwd` when given, so relative paths in the argv resolve as they did originally.
+    pub fn start_from_argv(&mut self, argv: &[String], cwd: Option<&Path>, settings: &DesktopSettings) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.is_running {
+            return Err("Reth node is already running".into());
+        }
+        let Some((program, original_args)) = argv.split_first() else {
+            return Err("Cannot launch Reth from an empty command line".into());
+        };
+        
+        let mut args = original_args.to_vec();
+        if settings.reth_defaults.enable_metrics && !Self::has_flag(&args, "--metrics") {
+            args.push("--metrics".to_string());
+            args.push(settings.reth_defaults.metrics_address.clone());
+        }
+        Self::append_desktop_log_args(&mut args, settings);
+        self.prepare_log_directory(&args)?;
         
-        // Add stdout logging configuration
-        if settings.reth_defaults.enable_stdout_logging {
-            command.arg("--log.stdout.format").arg(&settings.reth_defaults.stdout_log_format);
-            command_parts.push("--log.stdout.format".to_string());
-            command_parts.push(settings.reth_defaults.stdout_log_format.clone());
+        self.original_argv = Some(argv.to_vec());
+        self.original_cwd = cwd.map(Path::to_path_buf);
+        self.spawn_node(program, args, &[], cwd, settings)
+    }
+
+    /// The argv this node was taken over from, if it was started with `start_from_argv`
+    pub fn original_argv(&self) -> Option<&[String]> {
+        self.original_argv.as_deref()
+    }
+
+    /// The working directory the taken over node runs in
+    pub fn original_cwd(&self) -> Option<&Path> {
+        self.original_cwd.as_deref()
+    }
+
+    /// Whether an argument list already sets a flag, either as `--flag value` or `--flag=value`
+    fn has_flag(args: &[String], flag: &str) -> bool {
+        args.iter().any(|arg| arg == flag || arg.starts_with(&format!("{}=", flag)))
+    }
+
+    /// Add the desktop's stdout and file logging flags unless the arguments already set them
+    fn append_desktop_log_args(args: &mut Vec<String>, settings: &DesktopSettings) {
+        if settings.reth_defaults.enable_stdout_logging && !Self::has_flag(args, "--log.stdout.format") {
+            args.push("--log.stdout.format".to_string());
+            args.push(settings.reth_defaults.stdout_log_format.clone());
         }
         
         // Add file logging configuration if enabled and we have a log directory
-        if settings.reth_defaults.enable_file_logging {
-            if let Some(log_path) = &log_dir {
-                println!("Configuring Reth to log to: {}", log_path.display());
-                command
-                    .arg("--log.file.directory")
-                    .arg(log_path)
-                    .arg("--log.file.format")
-                    .arg(&settings.reth_defaults.file_log_format)
-                    .arg("--log.file.filter")
-                    .arg(&settings.reth_defaults.file_log_level)
-                    .arg("--log.file.max-size")
-                    .arg(&settings.reth_defaults.file_log_max_size)
-                    .arg("--log.file.max-files")
-                    .arg(&settings.reth_defaults.file_log_max_files);
-                
-                // Add to command parts for display
-                command_parts.extend(vec![
+        if settings.reth_defaults.enable_file_logging && !Self::has_flag(args, "--log.file.directory") {
+            if let Some(log_path) = Self::get_default_log_directory() {
+                args.extend(vec![
                     "--log.file.directory".to_string(),
                     log_path.display().to_string(),
                     "--log.file.format".to_string(),
@@ -223,20 +211,50 @@ impl RethNode {
                     "--log.file.max-files".to_string(),
                     settings.reth_defaults.file_log_max_files.clone(),
                 ]);
-                
-                // Store the log directory path - we'll find the actual log file later
-                // Reth creates files with date patterns like reth-2024-01-15-20.log
-                self.external_log_path = Some(log_path.clone());
             }
         }
+    }
+
+    /// Create the desktop log directory and remember it when the arguments log there
+    fn prepare_log_directory(&mut self, args: &[String]) -> std::io::Result<()> {
+        // Determine log directory path based on platform
+        let Some(log_dir) = Self::get_default_log_directory() else {
+            return Ok(());
+        };
+        
+        // Ensure log directory exists
+        if let Some(parent) = log_dir.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
         
-        // Add custom arguments from settings
-        println!("Adding {} custom arguments:", custom_args.len());
-        for arg in custom_args {
-            println!("  Adding custom arg: {}", arg);
-            command.arg(arg);
-            command_parts.push(arg.clone());
+        if args.windows(2).any(|pair| pair[0] == "--log.file.directory" && std::path::Path::new(&pair[1]) == log_dir) {
+            println!("Configuring Reth to log to: {}", log_dir.display());
+            // Store the log directory path - we'll find the actual log file later
+            // Reth creates files with date patterns like reth-2024-01-15-20.log
+            self.external_log_path = Some(log_dir);
+        }
+        
+        Ok(())
+    }
+
+    /// Spawn the node process and wire up its output, either piped or detached to a file
+    fn spawn_node(&mut self, reth_path: &str, args: Vec<String>, custom_args: &[String], cwd: Option<&Path>, settings: &DesktopSettings) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.stop_log_tailing();
+        self.stop_requested_at = None;
+        self.shutdown_timeout = Duration::from_secs(settings.shutdown_timeout_secs);
+        self.last_custom_args = custom_args.to_vec();
+        self.last_exit = None;
+        self.output_readers.clear();
+        self.begin_run();
+        
+        // Build the command and track it for display
+        let mut command = Command::new(reth_path);
+        command.args(&args);
+        if let Some(cwd) = cwd {
+            command.current_dir(cwd);
         }
+        let mut command_parts = vec![reth_path.to_string()];
+        command_parts.extend(args);
         
         // Store the command parts for display
         self.launch_command = Some(command_parts);
@@ -244,88 +262,397 @@ impl RethNode {
         // Print the full command for debugging
         println!("Final command: {:?}", command);
         
+        // When Reth should outlive the app, detach it from our session and send its output to a file
+        let detach = settings.keep_reth_running_in_background;
+        let output_log = RunFile::output_log_path();
+        if detach {
+            std::fs::create_dir_all(RunFile::run_dir())?;
+            let output = File::create(&output_log)?;
+            command
+                .stdin(Stdio::null())
+                .stdout(output.try_clone()?)
+                .stderr(output);
+            
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                // Start a new session so closing the app or its terminal doesn't signal Reth
+                unsafe {
+                    command.pre_exec(|| {
+                        if libc::setsid() == -1 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        Ok(())
+                    });
+                }
+            }
+        } else {
+            command
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+        }
+        
+        // On Windows, run Reth in its own process group so it can receive Ctrl-Break
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+            const DETACHED_PROCESS: u32 = 0x0000_0008;
+            let flags = if detach { CREATE_NEW_PROCESS_GROUP | DETACHED_PROCESS } else { CREATE_NEW_PROCESS_GROUP };
+            command.creation_flags(flags);
+        }
+        
         let mut child = command
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
             .spawn()
             .map_err(|e| {
                 eprintln!("Failed to spawn Reth process: {}", e);
                 e
             })?;
+        
+        if detach {
+            let started_at = chrono::Local::now();
+            let run_file = RunFile {
+                pid: child.id(),
+                launch_command: self.launch_command.clone().unwrap_or_default(),
+                custom_args: custom_args.to_vec(),
+                started_at,
+                output_log: output_log.clone(),
+            };
+            if let Err(e) = run_file.save() {
+                eprintln!("Failed to write PID file: {}", e);
+            }
+            
+            // Follow the output file instead of pipes
+            if let Err(e) = self.start_log_file_monitoring(output_log) {
+                eprintln!("Failed to follow detached Reth output: {}", e);
+            }
+            
+            self.process = Some(child);
+            self.detached = true;
+            self.is_running = true;
+            self.started_at = Some((Instant::now(), started_at));
+            return Ok(());
+        }
 
         // Capture stdout
         if let Some(stdout) = child.stdout.take() {
-            let sender = log_sender.clone();
-            thread::spawn(move || {
+            let sender = self.log_sender.clone();
+            self.output_readers.push(thread::spawn(move || {
                 let reader = BufReader::new(stdout);
                 for line in reader.lines() {
                     if let Ok(line) = line {
-                        let cleaned_content = LogLine::clean_reth_timestamp(&line);
-                        let log_line = LogLine {
-                            timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
-                            content: cleaned_content.clone(),
-                            level: LogLevel::from_content(&cleaned_content),
-                        };
-                        if sender.send(log_line).is_err() {
+                        if !sender.send_line(line, LogLevel::Info) {
                             break;
                         }
                     }
                 }
-            });
+            }));
         }
 
         // Capture stderr
         if let Some(stderr) = child.stderr.take() {
-            let sender = log_sender;
-            thread::spawn(move || {
+            let sender = self.log_sender.clone();
+            self.output_readers.push(thread::spawn(move || {
                 let reader = BufReader::new(stderr);
                 for line in reader.lines() {
                     if let Ok(line) = line {
-                        let cleaned_content = LogLine::clean_reth_timestamp(&line);
-                        let log_line = LogLine {
-                            timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
-                            content: cleaned_content,
-                            level: LogLevel::Error,
-                        };
-                        if sender.send(log_line).is_err() {
+                        // Reth logs to stdout; stderr lines without a level (panic backtraces, CLI
+                        // usage text) are flagged as warnings, leaving Error to lines reth marked so
+                        if !sender.send_line(line, LogLevel::Warn) {
                             break;
                         }
                     }
                 }
-            });
+            }));
         }
 
         self.process = Some(child);
         self.is_running = true;
+        self.started_at = Some((Instant::now(), chrono::Local::now()));
         Ok(())
     }
 
-    pub fn stop(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        if let Some(mut process) = self.process.take() {
-            process.kill()?;
-            process.wait()?;
-            self.is_running = false;
-            // Clear the log path and command for managed processes
-            self.external_log_path = None;
-            self.launch_command = None;
+    /// Ask the node to shut down without blocking the caller.
+    /// Managed processes and discovered external processes receive SIGTERM (Ctrl-Break on Windows)
+    /// and are killed by `check_process_status` if they are still alive once the grace period elapses.
+    pub fn request_stop(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(pid) = self.controllable_pid() {
+            if self.stop_requested_at.is_some() {
+                return Ok(());
+            }
+            
+            if let Err(e) = Self::send_terminate_signal(pid) {
+                eprintln!("Failed to signal Reth process {}: {}", pid, e);
+                if self.process.is_none() {
+                    // Not our child, so a kill would fail the same way (e.g. another user owns it).
+                    // The node is still running, so keep tracking it and report the failure.
+                    let message = format!("Failed to stop Reth process {}: {}", pid, e);
+                    self.push_status_line(message.clone(), LogLevel::Error);
+                    return Err(message.into());
+                }
+                // Our own child can't be signalled, so escalate straight away
+                self.kill_managed_process();
+                return Ok(());
+            }
+            
+            self.stop_requested_at = Some(Instant::now());
+            self.push_status_line(
+                format!("Shutdown requested (pid {}), waiting up to {}s for Reth to exit", pid, self.shutdown_timeout.as_secs()),
+                LogLevel::Warn,
+            );
         } else {
-            // For external processes, just reset the running state
-            self.is_running = false;
-            // Clear the launch command when disconnecting
-            self.launch_command = None;
-            // Keep the log path for external processes in case we reconnect
+            // External processes without a known PID can only be let go of
+            self.disconnect();
+        }
+        
+        Ok(())
+    }
+
+    /// Stop monitoring an external node without signalling it; it keeps running
+    pub fn disconnect(&mut self) {
+        self.stop_log_tailing();
+        self.is_running = false;
+        // Clear the launch command when disconnecting
+        self.launch_command = None;
+        self.external_process = None;
+        self.stop_requested_at = None;
+        self.connected_at = None;
+        self.no_log_reason = None;
+        // Keep the log path for external processes in case we reconnect
+    }
+
+    /// Stop the node and block until it has exited.
+    /// Uses the same graceful sequence as `request_stop`, escalating to a kill after the grace period.
+    pub fn stop(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.request_stop()?;
+        
+        while self.is_stopping() {
+            self.check_process_status();
+            if self.is_stopping() {
+                thread::sleep(Duration::from_millis(100));
+            }
         }
         
         Ok(())
     }
 
+    /// Whether a graceful shutdown is in progress for the managed process
+    pub fn is_stopping(&self) -> bool {
+        self.controllable_pid().is_some() && self.stop_requested_at.is_some()
+    }
+
+    /// Grace period before a stopping node is killed
+    pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+        self.shutdown_timeout = timeout;
+    }
+
+    /// PID we can signal: the managed process, or a discovered external process
+    fn controllable_pid(&self) -> Option<u32> {
+        self.managed_pid().or_else(|| self.external_process.as_ref().map(|external| external.pid))
+    }
+
+    /// Whether the node can be stopped by PID, i.e. it is managed or a discovered external process
+    pub fn can_stop(&self) -> bool {
+        self.is_running && self.controllable_pid().is_some()
+    }
+
+    /// PID of the process we control, whether we spawned it in this session or re-adopted it
+    pub fn managed_pid(&self) -> Option<u32> {
+        self.process.as_ref().map(|process| process.id()).or(self.adopted_pid)
+    }
+
+    /// Whether the managed process runs detached and will survive the app exiting
+    pub fn is_detached(&self) -> bool {
+        self.detached && self.managed_pid().is_some()
+    }
+
+    #[cfg(unix)]
+    fn send_terminate_signal(pid: u32) -> std::io::Result<()> {
+        let result = unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+
+    #[cfg(windows)]
+    fn send_terminate_signal(pid: u32) -> std::io::Result<()> {
+        use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+        let result = unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid) };
+        if result != 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+
+    /// Kill the controlled process immediately and reap it if it is our child
+    fn kill_managed_process(&mut self) {
+        if let Some(process) = &mut self.process {
+            let pid = process.id();
+            if let Err(e) = process.kill() {
+                eprintln!("Failed to kill Reth process {}: {}", pid, e);
+            }
+            let status = process.wait();
+            self.push_status_line(format!("Reth process {} was killed", pid), LogLevel::Error);
+            if let Ok(status) = status {
+                let (exit_code, signal) = Self::exit_status_parts(status);
+                self.record_exit(exit_code, signal);
+            }
+        } else if let Some(pid) = self.adopted_pid {
+            if !run_file::kill_pid(pid) {
+                eprintln!("Failed to kill Reth process {}", pid);
+            }
+            self.push_status_line(format!("Reth process {} was killed", pid), LogLevel::Error);
+            self.record_exit(None, None);
+        } else if let Some(external) = &self.external_process {
+            let pid = external.pid;
+            if !run_file::kill_pid(pid) {
+                eprintln!("Failed to kill external Reth process {}", pid);
+            }
+            self.push_status_line(format!("External Reth process {} was killed", pid), LogLevel::Error);
+        }
+        self.clear_managed_state();
+    }
+
+    /// Split an exit status into its exit code and terminating signal
+    fn exit_status_parts(status: std::process::ExitStatus) -> (Option<i32>, Option<i32>) {
+        #[cfg(unix)]
+        let signal = {
+            use std::os::unix::process::ExitStatusExt;
+            status.signal()
+        };
+        #[cfg(not(unix))]
+        let signal = None;
+        
+        (status.code(), signal)
+    }
+
+    /// Remember how the managed process ended, along with its most recent output
+    /// The exit code and signal are unknown for re-adopted processes, which aren't our children.
+    fn record_exit(&mut self, exit_code: Option<i32>, signal: Option<i32>) {
+        let run_end_seq = self.drain_output();
+        let recent_logs = self.run_logs(run_end_seq, EXIT_LOG_LINES);
+        
+        let now = chrono::Local::now();
+        let (uptime, started_at) = match self.started_at {
+            Some((instant, wall_clock)) => (instant.elapsed(), wall_clock),
+            None => (Duration::ZERO, now),
+        };
+        
+        self.last_exit = Some(NodeExitReport {
+            exit_code,
+            signal,
+            stop_requested: self.stop_requested_at.is_some(),
+            started_at,
+            exited_at: now,
+            uptime,
+            launch_command: self.launch_command.clone().unwrap_or_default(),
+            recent_logs,
+        });
+    }
+
+    /// Mark the start of a run in the log ring, after every line queued so far
+    fn begin_run(&mut self) {
+        self.run_first_seq = self.log_sender.flush().unwrap_or_else(|| self.log_ring.head());
+    }
+
+    /// Ring sequence number of the first line of the current run, or of the last one once it ended
+    pub fn run_first_seq(&self) -> u64 {
+        self.run_first_seq
+    }
+
+    /// Wait for the output readers to reach the end of the exited process's pipes and for the
+    /// ingestion thread to write what they read, so a panic printed just before the exit is in
+    /// the ring. Returns the ring's head once it has caught up.
+    fn drain_output(&mut self) -> u64 {
+        let deadline = Instant::now() + OUTPUT_DRAIN_TIMEOUT;
+        for reader in self.output_readers.drain(..) {
+            // A grandchild holding the pipe open keeps the reader alive, so don't wait forever
+            while !reader.is_finished() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+            if reader.is_finished() {
+                let _ = reader.join();
+            }
+        }
+        self.log_sender.flush().unwrap_or_else(|| self.log_ring.head())
+    }
+
+    /// The newest `count` lines reth printed during the current run, ending before sequence `end`.
+    /// Status lines from the desktop are left out.
+    fn run_logs(&self, end: u64, count: usize) -> Vec<LogRecord> {
+        let mut logs = Vec::new();
+        let mut window_end = end;
+        while logs.len() < count && window_end > self.run_first_seq {
+            let window_start = window_end.saturating_sub(count as u64).max(self.run_first_seq);
+            let slice = self.log_ring.read(window_start, (window_end - window_start) as usize);
+            // Stop at lines that were already overwritten
+            if slice.records.is_empty() || slice.next_seq() != window_end {
+                break;
+            }
+            let reth_lines = slice
+                .records
+                .iter()
+                .filter(|record| record.target != DESKTOP_TARGET)
+                .map(|record| LogRecord::clone(record));
+            logs.splice(0..0, reth_lines);
+            window_end = slice.first_seq;
+        }
+        let skip = logs.len().saturating_sub(count);
+        logs.drain(..skip);
+        logs
+    }
+
+    /// Take the exit information of the last managed process, if it has exited
+    pub fn take_last_exit(&mut self) -> Option<NodeExitReport> {
+        self.last_exit.take()
+    }
+
+    /// Custom arguments the managed process was last started with
+    pub fn last_custom_args(&self) -> &[String] {
+        &self.last_custom_args
+    }
+
+    /// Reset all state tied to the managed process once it has exited
+    fn clear_managed_state(&mut self) {
+        if self.detached {
+            RunFile::remove();
+        }
+        self.is_running = false;
+        self.process = None;
+        self.adopted_pid = None;
+        self.detached = false;
+        self.external_log_path = None;
+        self.launch_command = None;
+        self.stop_requested_at = None;
+        self.started_at = None;
+        self.connected_at = None;
+        self.no_log_reason = None;
+        self.external_process = None;
+    }
+
+    /// Inject a desktop-generated line into the log stream
+    fn push_status_line(&self, content: String, level: LogLevel) {
+        println!("{}", content);
+        self.log_sender.send_record(LogRecord::status(content, level));
+    }
+
+    /// Stop following the current log file, if any
+    fn stop_log_tailing(&mut self) {
+        if let Some(stop) = self.tail_stop.take() {
+            stop.stop();
+        }
+    }
+
     pub fn is_running(&self) -> bool {
         self.is_running
     }
 
     /// Check if we're monitoring an external process (not one we started)
     pub fn is_monitoring_external(&self) -> bool {
-        self.is_running && self.process.is_none()
+        self.is_running && self.managed_pid().is_none()
     }
 
     /// Get the path of the external log file being monitored
@@ -333,54 +660,85 @@ impl RethNode {
         self.external_log_path.as_ref()
     }
     
+    /// Where the running node's output comes from
+    pub fn log_source(&self) -> LogSource {
+        // Managed launches also set a log directory when file logging is on, but we read the pipes
+        if self.process.is_some() && !self.detached {
+            LogSource::Pipe
+        } else if let Some(path) = &self.external_log_path {
+            LogSource::File(path.clone())
+        } else {
+            LogSource::Unavailable(self.no_log_reason.clone().unwrap_or_else(|| "No log output source found".to_string()))
+        }
+    }
+
+    /// When the node was launched, or when we connected to it if it's an external process
+    pub fn running_since(&self) -> Option<Instant> {
+        self.started_at.map(|(instant, _)| instant).or(self.connected_at)
+    }
+    
     /// Get the command used to launch the Reth process
     pub fn get_launch_command(&self) -> Option<&Vec<String>> {
         self.launch_command.as_ref()
     }
 
-    pub fn get_logs(&mut self) -> Vec<LogLine> {
-        let mut logs = Vec::new();
-        
-        // Process any new logs from the receiver
-        if let Some(receiver) = &mut self.log_receiver {
-            while let Ok(log_line) = receiver.try_recv() {
-                logs.push(log_line.clone());
-                
-                // Add to buffer (keep last 1000 lines)
-                let mut buffer = self.log_buffer.lock().unwrap();
-                buffer.push_back(log_line);
-                if buffer.len() > 1000 {
-                    buffer.pop_front();
-                }
-            }
-        }
-        
-        logs
+    /// The ring every log line goes through; readers fetch new lines from it by sequence number
+    pub fn log_ring(&self) -> &Arc<LogRing> {
+        &self.log_ring
     }
 
-    pub fn get_all_logs(&self) -> Vec<LogLine> {
-        let buffer = self.log_buffer.lock().unwrap();
-        buffer.iter().cloned().collect()
+    /// Copies of the newest `count` log lines
+    pub fn recent_logs(&self, count: usize) -> Vec<LogRecord> {
+        self.log_ring.tail(count).iter().map(|record| LogRecord::clone(record)).collect()
     }
 
     pub fn check_process_status(&mut self) {
         if let Some(process) = &mut self.process {
             // Check our own managed process
             match process.try_wait() {
-                Ok(Some(_)) => {
-                    self.is_running = false;
-                    self.process = None;
-                    self.external_log_path = None;
-                    self.launch_command = None;
+                Ok(Some(status)) => {
+                    if self.stop_requested_at.is_some() {
+                        self.push_status_line(format!("Reth shut down gracefully ({})", status), LogLevel::Info);
+                    }
+                    let (exit_code, signal) = Self::exit_status_parts(status);
+                    self.record_exit(exit_code, signal);
+                    self.clear_managed_state();
                 }
                 Ok(None) => {
-                    // Process is still running
+                    // Process is still running - escalate if a graceful stop has timed out
+                    if let Some(requested_at) = self.stop_requested_at {
+                        if requested_at.elapsed() >= self.shutdown_timeout {
+                            self.push_status_line(
+                                format!("Reth did not exit within {}s, killing process", self.shutdown_timeout.as_secs()),
+                                LogLevel::Error,
+                            );
+                            self.kill_managed_process();
+                        }
+                    }
                 }
                 Err(_) => {
-                    self.is_running = false;
-                    self.process = None;
-                    self.external_log_path = None;
-                    self.launch_command = None;
+                    self.clear_managed_state();
+                }
+            }
+        } else if let Some(pid) = self.adopted_pid {
+            // A re-adopted detached process - poll its PID since we can't wait on it
+            let now = std::time::Instant::now();
+            if now.duration_since(self.last_external_check) >= Duration::from_secs(1) {
+                self.last_external_check = now;
+                if !run_file::is_pid_alive(pid) {
+                    if self.stop_requested_at.is_some() {
+                        self.push_status_line("Reth shut down gracefully".to_string(), LogLevel::Info);
+                    }
+                    self.record_exit(None, None);
+                    self.clear_managed_state();
+                } else if let Some(requested_at) = self.stop_requested_at {
+                    if requested_at.elapsed() >= self.shutdown_timeout {
+                        self.push_status_line(
+                            format!("Reth did not exit within {}s, killing process", self.shutdown_timeout.as_secs()),
+                            LogLevel::Error,
+                        );
+                        self.kill_managed_process();
+                    }
                 }
             }
         } else if self.is_running {
@@ -389,18 +747,36 @@ impl RethNode {
             let now = std::time::Instant::now();
             if now.duration_since(self.last_external_check).as_secs() >= 2 {
                 self.last_external_check = now;
-                if !Self::detect_existing_reth_process() {
+                // Follow the exact PID when we know it, otherwise fall back to probing ports
+                let still_running = match &self.external_process {
+                    Some(external) => run_file::is_pid_alive(external.pid),
+                    None => Self::detect_existing_reth_process(),
+                };
+                if !still_running {
+                    if self.stop_requested_at.is_some() {
+                        self.push_status_line("External Reth process shut down gracefully".to_string(), LogLevel::Info);
+                    }
                     self.is_running = false;
                     self.external_log_path = None;
                     self.launch_command = None;
+                    self.external_process = None;
+                    self.stop_requested_at = None;
                     println!("External Reth process has stopped");
+                } else if let Some(requested_at) = self.stop_requested_at {
+                    if requested_at.elapsed() >= self.shutdown_timeout {
+                        self.push_status_line(
+                            format!("External Reth did not exit within {}s, killing process", self.shutdown_timeout.as_secs()),
+                            LogLevel::Error,
+                        );
+                        self.kill_managed_process();
+                    }
                 }
             }
         }
     }
 
     /// Check if any Reth process is currently running on the system
-    /// Uses port checking as a more reliable method than process name matching
+    /// Uses process discovery where available, and port checking elsewhere
     pub fn detect_existing_reth_process() -> bool {
         // Use default ports for backward compatibility when settings aren't available
         Self::detect_existing_reth_process_with_ports(8545, 8546, 8551)
@@ -408,6 +784,15 @@ impl RethNode {
     
     /// Check if any Reth process is currently running using configurable ports
     pub fn detect_existing_reth_process_with_ports(rpc_port: u16, ws_port: u16, engine_port: u16) -> bool {
+        // Anything listening on these ports could be another client, so prefer real process matches
+        if discovery::is_supported() {
+            let processes = discovery::discover_reth_processes();
+            for process in &processes {
+                println!("Detected Reth process {} listening on ports {:?}", process.pid, process.ports);
+            }
+            return !processes.is_empty();
+        }
+        
         let rpc_listening = Self::is_port_listening(rpc_port);
         let ws_listening = Self::is_port_listening(ws_port);
         let engine_listening = Self::is_port_listening(engine_port);
@@ -821,48 +1206,51 @@ impl RethNode {
     /// Start tailing a log file for external process monitoring
     fn start_log_file_monitoring(&mut self, log_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         // Check if log_path is a directory or a file
-        let actual_log_file = if log_path.is_dir() {
+        let (actual_log_file, log_dir) = if log_path.is_dir() {
             // Find the actual log file in the directory
             println!("Log path is a directory, searching for log files in: {}", log_path.display());
             match Self::find_log_files_in_directory(&log_path) {
                 Some(file) => {
                     println!("Found log file in directory: {}", file.display());
-                    file
+                    (file, Some(log_path))
                 }
                 None => {
                     return Err("No log files found in directory".into());
                 }
             }
+        } else if log_path == RunFile::output_log_path() {
+            // Our own capture of a detached node's output is never rotated
+            (log_path, None)
         } else {
-            log_path
+            // Reth rotates files inside its chain-specific log directory
+            let log_dir = log_path.parent().map(|dir| dir.to_path_buf());
+            (log_path, log_dir)
         };
         
         println!("Starting log file monitoring for: {}", actual_log_file.display());
         
-        let (sender, receiver) = mpsc::unbounded_channel::<LogLine>();
-        self.log_receiver = Some(receiver);
+        // Open before stopping the current tailer so a failure leaves it running
+        let (mut tailer, recent_lines) = LogTailer::open(&actual_log_file, log_dir, 50)?;
+        self.stop_log_tailing();
+        let stop = StopFlag::default();
+        self.tail_stop = Some(stop.clone());
         
-        let log_buffer = self.log_buffer.clone();
+        let sender = self.log_sender.clone();
+        println!("Read {} recent log lines", recent_lines.len());
         
-        // Read last 50 lines of the log file to populate initial buffer
-        match Self::read_recent_log_lines(&actual_log_file, 50) {
-            Ok(recent_lines) => {
-                println!("Read {} recent log lines", recent_lines.len());
-                let mut buffer = log_buffer.lock().unwrap();
-                for line in recent_lines {
-                    buffer.push_back(line);
-                }
-            }
-            Err(e) => {
-                println!("Failed to read recent log lines: {}", e);
-            }
-        }
-        
-        let log_file_for_thread = actual_log_file.clone();
         thread::spawn(move || {
-            println!("Log tailing thread started for: {}", log_file_for_thread.display());
-            if let Err(e) = Self::tail_log_file(log_file_for_thread, sender, log_buffer) {
-                eprintln!("Error tailing log file: {}", e);
+            println!("Log tailing thread started for: {}", tailer.path().display());
+            // Start with the last lines of the file for context
+            for line in recent_lines {
+                sender.send_line(line, LogLevel::Info);
+            }
+            while !stop.is_stopped() {
+                for line in tailer.poll() {
+                    if !sender.send_line(line, LogLevel::Info) {
+                        return;
+                    }
+                }
+                tailer.wait();
             }
         });
         
@@ -872,110 +1260,54 @@ impl RethNode {
         Ok(())
     }
 
-    /// Read the last N lines from a log file
-    fn read_recent_log_lines(log_path: &PathBuf, count: usize) -> Result<Vec<LogLine>, Box<dyn std::error::Error>> {
-        println!("Reading recent lines from: {}", log_path.display());
-        let file = File::open(log_path)?;
-        let reader = BufReader::new(file);
-        
-        let mut lines: VecDeque<String> = VecDeque::new();
-        let mut total_lines = 0;
-        
-        // Read all lines and keep only the last N
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                lines.push_back(line);
-                total_lines += 1;
-                if lines.len() > count {
-                    lines.pop_front();
-                }
-            }
-        }
-        
-        println!("Read {} total lines, keeping {} recent lines", total_lines, lines.len());
+    /// Re-adopt a detached Reth process recorded in the PID file by a previous session.
+    /// Returns true if the process is still alive and is now under our control.
+    pub fn adopt_detached_process(&mut self, settings: &DesktopSettings) -> bool {
+        let Some(run_file) = RunFile::load() else {
+            return false;
+        };
         
-        // Convert to LogLine structs
-        let mut log_lines = Vec::new();
-        for line in lines {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                let cleaned_content = LogLine::clean_reth_timestamp(trimmed);
-                log_lines.push(LogLine {
-                    timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
-                    content: cleaned_content.clone(),
-                    level: LogLevel::from_content(&cleaned_content),
-                });
-            }
+        if !run_file.is_process_alive() {
+            println!("Detached Reth process {} is no longer running, removing stale PID file", run_file.pid);
+            RunFile::remove();
+            return false;
         }
         
-        println!("Converted to {} LogLine structs", log_lines.len());
-        Ok(log_lines)
-    }
-
-    /// Tail a log file and send new lines to the channel
-    fn tail_log_file(
-        log_path: PathBuf,
-        sender: mpsc::UnboundedSender<LogLine>,
-        log_buffer: Arc<Mutex<VecDeque<LogLine>>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut file = File::open(&log_path)?;
-        
-        // Seek to end of file to only read new content
-        file.seek(SeekFrom::End(0))?;
+        println!("Re-adopting detached Reth process {}", run_file.pid);
+        self.adopted_pid = Some(run_file.pid);
+        self.detached = true;
+        self.is_running = true;
+        self.stop_requested_at = None;
+        self.shutdown_timeout = Duration::from_secs(settings.shutdown_timeout_secs);
+        self.launch_command = Some(run_file.launch_command.clone());
+        self.last_custom_args = run_file.custom_args.clone();
+        self.last_exit = None;
+        self.begin_run();
         
-        let mut reader = BufReader::new(file);
-        let mut line = String::new();
+        // Reconstruct the monotonic start time from the recorded wall-clock time
+        let running_for = (chrono::Local::now() - run_file.started_at).to_std().unwrap_or_default();
+        let started_instant = Instant::now().checked_sub(running_for).unwrap_or_else(Instant::now);
+        self.started_at = Some((started_instant, run_file.started_at));
         
-        loop {
-            line.clear();
-            match reader.read_line(&mut line) {
-                Ok(0) => {
-                    // No new data, sleep briefly and try again
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-                    continue;
-                }
-                Ok(_) => {
-                    // Process the new line
-                    let trimmed = line.trim();
-                    if !trimmed.is_empty() {
-                        let cleaned_content = LogLine::clean_reth_timestamp(trimmed);
-                        let log_line = LogLine {
-                            timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
-                            content: cleaned_content.clone(),
-                            level: LogLevel::from_content(&cleaned_content),
-                        };
-                        
-                        // Add to buffer
-                        {
-                            let mut buffer = log_buffer.lock().unwrap();
-                            buffer.push_back(log_line.clone());
-                            if buffer.len() > 1000 {
-                                buffer.pop_front();
-                            }
-                        }
-                        
-                        // Send to receiver
-                        if sender.send(log_line).is_err() {
-                            break; // Channel closed
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error reading log file: {}", e);
-                    std::thread::sleep(std::time::Duration::from_millis(1000));
-                }
-            }
+        if let Err(e) = self.start_log_file_monitoring(run_file.output_log.clone()) {
+            eprintln!("Failed to follow detached Reth output {}: {}", run_file.output_log.display(), e);
         }
         
-        Ok(())
+        true
     }
 
     /// Connect to and start monitoring an existing Reth process
     pub fn connect_to_existing_process(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if Self::detect_existing_reth_process() {
+        if let Some(external) = discovery::discover_reth_processes().into_iter().next() {
+            return self.connect_to_discovered_process(external);
+        }
+        
+        if !discovery::is_supported() && Self::detect_existing_reth_process() {
             // Create a dummy "process" state to indicate we're monitoring an external process
             self.is_running = true;
             self.process = None; // We don't own this process
+            self.connected_at = Some(Instant::now());
+            self.begin_run();
             
             // Try to detect the command used to launch the external process
             if let Some(cmd_string) = Self::detect_external_reth_command() {
@@ -1000,8 +1332,8 @@ impl RethNode {
                 println!("Note: Reth may not be configured to write log files.");
                 println!("To enable file logging, restart Reth with: reth node --log.file.directory <path>");
                 
-                // Set a flag to show helpful message in UI
                 self.external_log_path = None;
+                self.no_log_reason = Some(Self::no_log_file_reason(None));
             }
             
             Ok(())
@@ -1009,202 +1341,123 @@ impl RethNode {
             Err("No existing Reth process found".into())
         }
     }
-    
-    /// Parse available CLI options from reth node --help
-    pub fn get_available_cli_options(reth_path: &str) -> Vec<CliOption> {
-        let mut options = Vec::new();
+
+    /// Monitor a discovered external process using the settings from its own command line
+    fn connect_to_discovered_process(&mut self, external: DiscoveredProcess) -> Result<(), Box<dyn std::error::Error>> {
+        println!(
+            "Connecting to Reth process {} (chain: {}, datadir: {}, ports: {:?})",
+            external.pid, external.chain, external.datadir.display(), external.ports
+        );
+        self.is_running = true;
+        self.process = None; // We don't own this process
+        self.connected_at = Some(Instant::now());
+        self.begin_run();
+        self.launch_command = Some(external.argv.clone());
         
-        // Run reth node --help to get available options
-        match Command::new(reth_path)
-            .arg("node")
-            .arg("--help")
-            .output()
-        {
-            Ok(output) => {
-                let help_text = String::from_utf8_lossy(&output.stdout);
-                let lines: Vec<&str> = help_text.lines().collect();
-                
-                let mut i = 0;
-                while i < lines.len() {
-                    let line = lines[i].trim();
-                    
-                    // Look for lines starting with spaces followed by -- (long options)
-                    if line.trim_start().starts_with("--") && !line.trim_start().starts_with("---") {
-                        // Parse the option line
-                        // Format is: --option-name <VALUE>
-                        let trimmed = line.trim();
-                        
-                        // Split by first space to separate option from value placeholder
-                        let parts: Vec<&str> = trimmed.splitn(2, ' ').collect();
-                        if parts.len() >= 1 {
-                            let option_name = parts[0].to_string();
-                            let mut takes_value = false;
-                            let mut value_name = None;
-                            
-                            // Check if there's a value part
-                            if parts.len() > 1 {
-                                let rest = parts[1].trim();
-                                if rest.starts_with('<') && rest.contains('>') {
-                                    // Extract value name
-                                    if let Some(end) = rest.find('>') {
-                                        value_name = Some(rest[1..end].to_string());
-                                        takes_value = true;
-                                    }
-                                }
-                            }
-                            
-                            // Some known flags that don't take values
-                            let flag_patterns = vec![
-                                "--help", "--version", "--full", "--http", "--ws", 
-                                "--disable-", "--enable-", "--with-", "--without-"
-                            ];
-                            
-                            // If it's a known flag pattern and we didn't detect a value, mark as flag
-                            if !takes_value {
-                                for pattern in &flag_patterns {
-                                    if option_name.starts_with(pattern) || option_name == *pattern {
-                                        takes_value = false;
-                                        break;
-                                    }
-                                }
-                            }
-                            
-                            // Get description from the next line and look for possible values
-                            let mut description = String::new();
-                            let mut possible_values = None;
-                            let accepts_multiple;
-                            
-                            // Check next few lines for description and possible values
-                            let mut j = i + 1;
-                            while j < lines.len() {
-                                let line = lines[j].trim();
-                                
-                                // Stop if we hit another option
-                                if line.starts_with("--") {
-                                    break;
-                                }
-                                
-                                // Skip empty lines
-                                if line.is_empty() {
-                                    j += 1;
-                                    continue;
-                                }
-                                
-                                // Check for possible values pattern: [possible values: ...]
-                                if line.contains("[possible values:") {
-                                    // Extract possible values
-                                    if let Some(start) = line.find("[possible values:") {
-                                        let values_part = &line[start + 17..]; // Skip "[possible values:"
-                                        if let Some(end) = values_part.find(']') {
-                                            let values_str = &values_part[..end];
-                                            let values: Vec<String> = values_str
-                                                .split(',')
-                                                .map(|s| s.trim().to_string())
-                                                .filter(|s| !s.is_empty())
-                                                .collect();
-                                            if !values.is_empty() {
-                                                possible_values = Some(values);
-                                            }
-                                        }
-                                    }
-                                } else if description.is_empty() {
-                                    // This is the description line
-                                    description = line.to_string();
-                                }
-                                
-                                j += 1;
-                                
-                                // Only look at a few lines to avoid going too far
-                                if j > i + 5 {
-                                    break;
-                                }
-                            }
-                            
-                            // Check if this parameter accepts multiple values
-                            // Look for indicators in the description or parameter name
-                            let desc_lower = description.to_lowercase();
-                            accepts_multiple = desc_lower.contains("comma-separated") || 
-                                             desc_lower.contains("comma separated") ||
-                                             desc_lower.contains("list of") ||
-                                             desc_lower.contains("multiple") ||
-                                             option_name.contains(".api") ||
-                                             option_name.contains(".namespaces");
-                            
-                            // Some options we want to skip or are already included
-                            let skip_options = vec![
-                                "--help", "--version", "--full", 
-                                "--log.stdout.format", "--log.file.directory",
-                                "--log.file.format", "--log.file.filter",
-                                "--log.file.max-size", "--log.file.max-files"
-                            ];
-                            
-                            if !skip_options.contains(&option_name.as_str()) && !description.is_empty() {
-                                options.push(CliOption {
-                                    name: option_name,
-                                    description,
-                                    takes_value,
-                                    value_name,
-                                    possible_values,
-                                    accepts_multiple,
-                                });
-                            }
-                        }
-                    }
-                    i += 1;
-                }
-            }
-            Err(e) => {
-                println!("Failed to get CLI options: {}", e);
-            }
-        }
+        // Reth writes logs to <log directory>/<chain>/, with the cache directory as the default
+        let log_path = match &external.log_directory {
+            Some(log_directory) => Self::find_log_files_in_directory(&log_directory.join(&external.chain))
+                .or_else(|| Self::find_log_files_in_directory(log_directory)),
+            None => Self::get_default_log_directory()
+                .and_then(|log_directory| Self::find_log_files_in_directory(&log_directory.join(&external.chain)))
+                .or_else(Self::get_reth_log_path),
+        };
         
-        // Add some common options that might be useful
-        if options.is_empty() {
-            // Fallback options if parsing fails
-            options.extend(vec![
-                CliOption {
-                    name: "--datadir".to_string(),
-                    description: "The path to the data directory".to_string(),
-                    takes_value: true,
-                    value_name: Some("PATH".to_string()),
-                    possible_values: None,
-                    accepts_multiple: false,
-                },
-                CliOption {
-                    name: "--port".to_string(),
-                    description: "The port to listen on".to_string(),
-                    takes_value: true,
-                    value_name: Some("PORT".to_string()),
-                    possible_values: None,
-                    accepts_multiple: false,
-                },
-                CliOption {
-                    name: "--http".to_string(),
-                    description: "Enable the HTTP RPC server".to_string(),
-                    takes_value: false,
-                    value_name: None,
-                    possible_values: None,
-                    accepts_multiple: false,
-                },
-                CliOption {
-                    name: "--ws".to_string(),
-                    description: "Enable the WebSocket RPC server".to_string(),
-                    takes_value: false,
-                    value_name: None,
-                    possible_values: None,
-                    accepts_multiple: false,
-                },
-                CliOption {
-                    name: "--authrpc.port".to_string(),
-                    description: "The port to listen on for authenticated RPC".to_string(),
-                    takes_value: true,
-                    value_name: Some("PORT".to_string()),
-                    possible_values: None,
-                    accepts_multiple: false,
-                },
-            ]);
+        let log_directory = external.log_directory.clone();
+        self.external_process = Some(external);
+        
+        if let Some(log_path) = log_path {
+            println!("Found Reth log file: {}", log_path.display());
+            self.start_log_file_monitoring(log_path)?;
+            println!("Started monitoring external Reth process with log tailing");
+        } else {
+            println!("Connected to existing Reth process (no log file found)");
+            println!("To enable file logging, restart Reth with: reth node --log.file.directory <path>");
+            self.external_log_path = None;
+            self.no_log_reason = Some(Self::no_log_file_reason(log_directory.as_ref()));
         }
         
-        options
+        Ok(())
+    }
+
+    /// Explain why an external node's output can't be shown
+    fn no_log_file_reason(log_directory: Option<&PathBuf>) -> String {
+        let searched = match log_directory {
+            Some(dir) => format!("No log files were found in {}, the --log.file.directory this node was started with.", dir.display()),
+            None => match Self::get_default_log_directory() {
+                Some(dir) => format!("No log files were found in reth's default log directory ({}).", dir.display()),
+                None => "No log files were found in reth's default log directory.".to_string(),
+            },
+        };
+        format!(
+            "{} This node was started outside the app and its output goes to its own terminal, so it can only be shown here \
+             if reth writes log files. File logging may be turned off (--log.file.max-files 0); restart reth with \
+             --log.file.directory <path> to see its output.",
+            searched
+        )
+    }
+
+    /// The discovered external process we are monitoring, if any
+    pub fn external_process(&self) -> Option<&DiscoveredProcess> {
+        self.external_process.as_ref()
+    }
+
+    /// Metrics address the running node was launched with, if it exposes metrics
+    pub fn metrics_address(&self) -> Option<String> {
+        if let Some(external) = &self.external_process {
+            return external.metrics_address.clone();
+        }
+        self.launch_command.as_deref().and_then(discovery::metrics_address_from_argv)
+    }
+    
+    /// Minimal option list used when `reth node --help` couldn't be parsed
+    pub fn fallback_cli_options() -> Vec<CliOption> {
+        vec![
+            CliOption {
+                name: "--datadir".to_string(),
+                description: "The path to the data directory".to_string(),
+                takes_value: true,
+                value_name: Some("PATH".to_string()),
+                possible_values: None,
+                accepts_multiple: false,
+                ..Default::default()
+            },
+            CliOption {
+                name: "--port".to_string(),
+                description: "The port to listen on".to_string(),
+                takes_value: true,
+                value_name: Some("PORT".to_string()),
+                possible_values: None,
+                accepts_multiple: false,
+                ..Default::default()
+            },
+            CliOption {
+                name: "--http".to_string(),
+                description: "Enable the HTTP RPC server".to_string(),
+                takes_value: false,
+                value_name: None,
+                possible_values: None,
+                accepts_multiple: false,
+                ..Default::default()
+            },
+            CliOption {
+                name: "--ws".to_string(),
+                description: "Enable the WebSocket RPC server".to_string(),
+                takes_value: false,
+                value_name: None,
+                possible_values: None,
+                accepts_multiple: false,
+                ..Default::default()
+            },
+            CliOption {
+                name: "--authrpc.port".to_string(),
+                description: "The port to listen on for authenticated RPC".to_string(),
+                takes_value: true,
+                value_name: Some("PORT".to_string()),
+                possible_values: None,
+                accepts_multiple: false,
+                ..Default::default()
+            },
+        ]
     }
 }
\ No newline at end of file
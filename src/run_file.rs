@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use sysinfo::{Pid, System};
+
+/// Record of a detached Reth process, written to ~/.reth-desktop/run/ so the
+/// desktop can re-adopt the node after it has been closed and reopened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunFile {
+    pub pid: u32,
+    pub launch_command: Vec<String>,
+    #[serde(default)]
+    pub custom_args: Vec<String>,
+    pub started_at: chrono::DateTime<chrono::Local>,
+    pub output_log: PathBuf,
+}
+
+impl RunFile {
+    /// Directory holding the PID file and the detached process output
+    pub fn run_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".reth-desktop")
+            .join("run")
+    }
+
+    /// Path of the PID file
+    pub fn path() -> PathBuf {
+        Self::run_dir().join("reth-pid.json")
+    }
+
+    /// File that receives stdout and stderr of a detached node
+    pub fn output_log_path() -> PathBuf {
+        Self::run_dir().join("reth-output.log")
+    }
+
+    /// Load the PID file if one exists and can be parsed
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path()).ok()?;
+        match serde_json::from_str::<RunFile>(&content) {
+            Ok(run_file) => Some(run_file),
+            Err(e) => {
+                eprintln!("Failed to parse PID file {}: {}", Self::path().display(), e);
+                None
+            }
+        }
+    }
+
+    /// Write the PID file
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        std::fs::create_dir_all(Self::run_dir())?;
+        std::fs::write(Self::path(), serde_json::to_string_pretty(self)?)?;
+        println!("Wrote PID file: {}", Self::path().display());
+        Ok(())
+    }
+
+    /// Remove the PID file, ignoring a missing file
+    pub fn remove() {
+        let path = Self::path();
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                eprintln!("Failed to remove PID file {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Check that the recorded PID is still the Reth process we started,
+    /// guarding against the PID having been reused by an unrelated process
+    pub fn is_process_alive(&self) -> bool {
+        let pid = Pid::from_u32(self.pid);
+        let mut system = System::new();
+        if !system.refresh_process(pid) {
+            return false;
+        }
+        let Some(process) = system.process(pid) else {
+            return false;
+        };
+
+        if !process.name().to_lowercase().contains("reth") {
+            println!("PID {} is now used by '{}', not Reth", self.pid, process.name());
+            return false;
+        }
+
+        // Allow a little slack between spawn and our own timestamp
+        let recorded_start = self.started_at.timestamp();
+        let actual_start = process.start_time() as i64;
+        if (actual_start - recorded_start).abs() > 10 {
+            println!("PID {} was started at a different time, not adopting it", self.pid);
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Whether a process with the given PID currently exists
+pub fn is_pid_alive(pid: u32) -> bool {
+    let mut system = System::new();
+    system.refresh_process(Pid::from_u32(pid))
+}
+
+/// Forcefully kill a process that is not our child
+pub fn kill_pid(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_process(pid);
+    system.process(pid).map(|process| process.kill()).unwrap_or(false)
+}
@@ -1,16 +1,92 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use crate::launch_args::{self, LaunchArg};
+use crate::log_alerts::{self, AlertRule};
+use crate::metrics::{self, CustomMetric};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DesktopSettings {
+    #[serde(default)]
+    pub version: u32, // Format of the stored settings, bumped when saved values need migrating
     #[serde(default)]
     pub keep_reth_running_in_background: bool,
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64, // Grace period after SIGTERM before the node is killed
+    #[serde(default, deserialize_with = "launch_args::deserialize_launch_args")]
+    pub custom_launch_args: Vec<LaunchArg>,
+    #[serde(default, deserialize_with = "metrics::deserialize_custom_metrics")]
+    pub custom_metrics: Vec<CustomMetric>, // Metric cards added by the user
     #[serde(default)]
-    pub custom_launch_args: Vec<String>,
+    pub reth_defaults: RethDefaults,
     #[serde(default)]
-    pub custom_metrics: Vec<String>, // List of custom metric names to display
+    pub supervisor: SupervisorSettings,
     #[serde(default)]
-    pub reth_defaults: RethDefaults,
+    pub alerts: AlertSettings,
+}
+
+/// Policy for automatically restarting a managed node that crashed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SupervisorSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_initial_backoff_secs")]
+    pub initial_backoff_secs: u64,
+    #[serde(default = "default_max_backoff_secs")]
+    pub max_backoff_secs: u64,
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32, // Restarts allowed inside the window before locking out
+    #[serde(default = "default_restart_window_secs")]
+    pub window_secs: u64,
+    #[serde(default = "default_restart_log_lines")]
+    pub log_lines: usize, // Log lines kept with each restart record
+}
+
+/// Log alert rules: the built-in set plus rules added by the user
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AlertSettings {
+    #[serde(default = "default_true")]
+    pub builtin_rules: bool,
+    #[serde(default)]
+    pub disabled_builtin: Vec<String>, // Names of built-in rules switched off individually
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+}
+
+/// Current settings format; files without a version predate it
+const SETTINGS_VERSION: u32 = 1;
+
+impl DesktopSettings {
+    /// Bring settings saved by an older version up to date; returns whether anything changed
+    pub fn migrate(&mut self) -> bool {
+        if self.version >= SETTINGS_VERSION {
+            return false;
+        }
+        
+        // Version 1: the log viewer, alerts and sync stages read JSON stdout, and older files
+        // stored the previous "terminal" default, which would keep them from working
+        if self.version < 1 && self.reth_defaults.stdout_log_format == "terminal" {
+            self.reth_defaults.stdout_log_format = default_stdout_log_format();
+        }
+        
+        self.version = SETTINGS_VERSION;
+        true
+    }
+}
+
+impl AlertSettings {
+    /// Every rule that should run: enabled built-ins followed by the user's rules
+    pub fn active_rules(&self) -> Vec<AlertRule> {
+        let mut rules: Vec<AlertRule> = if self.builtin_rules {
+            log_alerts::builtin_rules()
+                .into_iter()
+                .filter(|rule| !self.disabled_builtin.contains(&rule.name))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        rules.extend(self.rules.iter().cloned());
+        rules
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -32,8 +108,8 @@ pub struct RethDefaults {
     // Stdout logging parameters
     #[serde(default = "default_true")]
     pub enable_stdout_logging: bool,
-    #[serde(default = "default_log_format")]
-    pub stdout_log_format: String,
+    #[serde(default = "default_stdout_log_format")]
+    pub stdout_log_format: String, // JSON lets the desktop read level, target and fields exactly
     
     // File logging parameters
     #[serde(default = "default_true")]
@@ -58,6 +134,12 @@ pub struct RethDefaults {
 
 // Default value functions
 fn default_true() -> bool { true }
+fn default_shutdown_timeout_secs() -> u64 { 60 }
+fn default_initial_backoff_secs() -> u64 { 5 }
+fn default_max_backoff_secs() -> u64 { 300 }
+fn default_max_restarts() -> u32 { 5 }
+fn default_restart_window_secs() -> u64 { 3600 }
+fn default_restart_log_lines() -> usize { 50 }
 fn default_metrics_address() -> String { "127.0.0.1:9001".to_string() }
 fn default_chain() -> String { "mainnet".to_string() }
 fn default_datadir() -> String {
@@ -69,6 +151,7 @@ fn default_datadir() -> String {
         .to_string()
 }
 fn default_log_format() -> String { "terminal".to_string() }
+fn default_stdout_log_format() -> String { "json".to_string() }
 fn default_log_level() -> String { "info".to_string() }
 fn default_log_max_size() -> String { "50".to_string() }
 fn default_log_max_files() -> String { "3".to_string() }
@@ -79,10 +162,37 @@ fn default_engine_port() -> u16 { 8551 }
 impl Default for DesktopSettings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             keep_reth_running_in_background: false,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
             custom_launch_args: Vec::new(),
             custom_metrics: Vec::new(),
             reth_defaults: RethDefaults::default(),
+            supervisor: SupervisorSettings::default(),
+            alerts: AlertSettings::default(),
+        }
+    }
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            builtin_rules: true,
+            disabled_builtin: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Default for SupervisorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            initial_backoff_secs: default_initial_backoff_secs(),
+            max_backoff_secs: default_max_backoff_secs(),
+            max_restarts: default_max_restarts(),
+            window_secs: default_restart_window_secs(),
+            log_lines: default_restart_log_lines(),
         }
     }
 }
@@ -96,7 +206,7 @@ impl Default for RethDefaults {
             chain: default_chain(),
             datadir: default_datadir(),
             enable_stdout_logging: default_true(),
-            stdout_log_format: default_log_format(),
+            stdout_log_format: default_stdout_log_format(),
             enable_file_logging: default_true(),
             file_log_format: default_log_format(),
             file_log_level: default_log_level(),
@@ -129,8 +239,14 @@ impl DesktopSettingsManager {
         match std::fs::read_to_string(&settings_path) {
             Ok(content) => {
                 match toml::from_str::<DesktopSettings>(&content) {
-                    Ok(settings) => {
+                    Ok(mut settings) => {
                         println!("Loaded desktop settings from: {}", settings_path.display());
+                        if settings.migrate() {
+                            println!("Migrated desktop settings to version {}", settings.version);
+                            if let Err(e) = Self::save_desktop_settings(&settings) {
+                                eprintln!("Failed to save migrated settings.toml: {}", e);
+                            }
+                        }
                         settings
                     }
                     Err(e) => {
@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::exit_report::NodeExitReport;
+use crate::settings::SupervisorSettings;
+
+/// Maximum number of restart records kept for display
+const MAX_RESTART_RECORDS: usize = 50;
+
+/// A single crash handled by the supervisor
+#[derive(Debug, Clone)]
+pub struct RestartRecord {
+    pub exit: NodeExitReport,
+    pub restart_delay: Option<Duration>,
+    pub attempt: u32,
+}
+
+/// What the supervisor decided to do after the node exited
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupervisorDecision {
+    /// Supervision is disabled or the exit was not a crash
+    Ignore,
+    /// Restart once the backoff delay has elapsed
+    RestartIn(Duration),
+    /// Too many restarts inside the window; automatic restarts are suspended
+    LockedOut,
+}
+
+/// Restarts a crashed managed node according to the configured policy
+pub struct NodeSupervisor {
+    history: VecDeque<RestartRecord>,
+    restart_times: VecDeque<Instant>,
+    pending_restart: Option<Instant>,
+    locked_out: bool,
+}
+
+impl NodeSupervisor {
+    pub fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            restart_times: VecDeque::new(),
+            pending_restart: None,
+            locked_out: false,
+        }
+    }
+
+    /// Record a process exit and decide whether it should be restarted
+    pub fn handle_exit(&mut self, exit: NodeExitReport, policy: &SupervisorSettings) -> SupervisorDecision {
+        if !exit.is_crash() {
+            return SupervisorDecision::Ignore;
+        }
+
+        let now = Instant::now();
+        let window = Duration::from_secs(policy.window_secs);
+        while let Some(first) = self.restart_times.front() {
+            if now.duration_since(*first) > window {
+                self.restart_times.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let attempt = self.restart_times.len() as u32 + 1;
+        let decision = if !policy.enabled {
+            SupervisorDecision::Ignore
+        } else if self.locked_out || attempt > policy.max_restarts {
+            self.locked_out = true;
+            SupervisorDecision::LockedOut
+        } else {
+            let delay = Self::backoff_delay(policy, attempt);
+            self.pending_restart = Some(now + delay);
+            SupervisorDecision::RestartIn(delay)
+        };
+
+        match &decision {
+            SupervisorDecision::RestartIn(delay) => {
+                println!("Reth crashed ({}), restarting in {}s (attempt {}/{})", exit.describe(), delay.as_secs(), attempt, policy.max_restarts);
+            }
+            SupervisorDecision::LockedOut => {
+                println!("Reth crashed ({}), crash loop detected - automatic restarts suspended", exit.describe());
+            }
+            SupervisorDecision::Ignore => {
+                println!("Reth crashed ({})", exit.describe());
+            }
+        }
+
+        let mut recent_logs = exit.recent_logs.clone();
+        let skip = recent_logs.len().saturating_sub(policy.log_lines);
+        recent_logs.drain(..skip);
+
+        self.history.push_back(RestartRecord {
+            exit: NodeExitReport { recent_logs, ..exit },
+            restart_delay: match decision {
+                SupervisorDecision::RestartIn(delay) => Some(delay),
+                _ => None,
+            },
+            attempt,
+        });
+        while self.history.len() > MAX_RESTART_RECORDS {
+            self.history.pop_front();
+        }
+
+        decision
+    }
+
+    /// Exponential backoff: initial * 2^(attempt - 1), capped at the maximum
+    fn backoff_delay(policy: &SupervisorSettings, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let secs = policy.initial_backoff_secs.saturating_mul(factor).min(policy.max_backoff_secs);
+        Duration::from_secs(secs)
+    }
+
+    /// Returns true once when a scheduled restart is due
+    pub fn take_due_restart(&mut self) -> bool {
+        match self.pending_restart {
+            Some(at) if Instant::now() >= at => {
+                self.pending_restart = None;
+                self.restart_times.push_back(Instant::now());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Time left until the scheduled restart, if any
+    pub fn time_until_restart(&self) -> Option<Duration> {
+        self.pending_restart.map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn is_locked_out(&self) -> bool {
+        self.locked_out
+    }
+
+    /// Cancel any scheduled restart and clear the lockout after manual intervention
+    pub fn reset(&mut self) {
+        self.pending_restart = None;
+        self.restart_times.clear();
+        self.locked_out = false;
+    }
+
+    pub fn history(&self) -> &VecDeque<RestartRecord> {
+        &self.history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crash() -> NodeExitReport {
+        let now = chrono::Local::now();
+        NodeExitReport {
+            exit_code: Some(1),
+            signal: None,
+            stop_requested: false,
+            started_at: now,
+            exited_at: now,
+            uptime: Duration::ZERO,
+            launch_command: Vec::new(),
+            recent_logs: Vec::new(),
+        }
+    }
+
+    fn policy(initial_backoff_secs: u64, max_backoff_secs: u64, max_restarts: u32) -> SupervisorSettings {
+        SupervisorSettings {
+            enabled: true,
+            initial_backoff_secs,
+            max_backoff_secs,
+            max_restarts,
+            ..SupervisorSettings::default()
+        }
+    }
+
+    /// Crash and let the scheduled restart happen straight away
+    fn crash_and_restart(supervisor: &mut NodeSupervisor, policy: &SupervisorSettings) -> SupervisorDecision {
+        let decision = supervisor.handle_exit(crash(), policy);
+        if let SupervisorDecision::RestartIn(_) = decision {
+            assert!(supervisor.take_due_restart());
+        }
+        decision
+    }
+
+    #[test]
+    fn backoff_doubles_per_attempt() {
+        let policy = policy(5, 3600, 10);
+        let delays: Vec<u64> = (1..=5).map(|attempt| NodeSupervisor::backoff_delay(&policy, attempt).as_secs()).collect();
+        assert_eq!(delays, vec![5, 10, 20, 40, 80]);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = policy(5, 30, 10);
+        assert_eq!(NodeSupervisor::backoff_delay(&policy, 3).as_secs(), 20);
+        assert_eq!(NodeSupervisor::backoff_delay(&policy, 4).as_secs(), 30);
+        assert_eq!(NodeSupervisor::backoff_delay(&policy, 64).as_secs(), 30);
+    }
+
+    #[test]
+    fn locks_out_after_max_restarts() {
+        let policy = policy(0, 0, 3);
+        let mut supervisor = NodeSupervisor::new();
+        for _ in 0..3 {
+            assert_eq!(crash_and_restart(&mut supervisor, &policy), SupervisorDecision::RestartIn(Duration::ZERO));
+        }
+        assert!(!supervisor.is_locked_out());
+
+        assert_eq!(crash_and_restart(&mut supervisor, &policy), SupervisorDecision::LockedOut);
+        assert!(supervisor.is_locked_out());
+        assert_eq!(supervisor.history().len(), 4);
+        assert_eq!(supervisor.history().back().map(|record| record.attempt), Some(4));
+
+        // Stays locked out until reset
+        assert_eq!(crash_and_restart(&mut supervisor, &policy), SupervisorDecision::LockedOut);
+    }
+
+    #[test]
+    fn reset_clears_lockout_and_pending_restart() {
+        let policy = policy(0, 0, 1);
+        let mut supervisor = NodeSupervisor::new();
+        assert_eq!(crash_and_restart(&mut supervisor, &policy), SupervisorDecision::RestartIn(Duration::ZERO));
+        assert_eq!(crash_and_restart(&mut supervisor, &policy), SupervisorDecision::LockedOut);
+
+        supervisor.reset();
+        assert!(!supervisor.is_locked_out());
+        assert_eq!(supervisor.handle_exit(crash(), &policy), SupervisorDecision::RestartIn(Duration::ZERO));
+
+        supervisor.reset();
+        assert_eq!(supervisor.time_until_restart(), None);
+        assert!(!supervisor.take_due_restart());
+    }
+
+    #[test]
+    fn ignores_clean_exits_and_disabled_supervision() {
+        let mut supervisor = NodeSupervisor::new();
+        let clean = NodeExitReport { exit_code: Some(0), ..crash() };
+        assert_eq!(supervisor.handle_exit(clean, &policy(5, 60, 3)), SupervisorDecision::Ignore);
+        assert!(supervisor.history().is_empty());
+
+        let disabled = SupervisorSettings { enabled: false, ..policy(5, 60, 3) };
+        assert_eq!(supervisor.handle_exit(crash(), &disabled), SupervisorDecision::Ignore);
+        assert_eq!(supervisor.time_until_restart(), None);
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::log_record::LogRecord;
+use crate::prometheus::Exposition;
+
+/// Reth's pipeline stages in execution order; stages not listed are shown after these
+pub const STAGE_ORDER: [&str; 15] = [
+    "Era",
+    "Headers",
+    "Bodies",
+    "SenderRecovery",
+    "Execution",
+    "PruneSenderRecovery",
+    "MerkleUnwind",
+    "AccountHashing",
+    "StorageHashing",
+    "MerkleExecute",
+    "TransactionLookup",
+    "IndexStorageHistory",
+    "IndexAccountHistory",
+    "Prune",
+    "Finish",
+];
+
+/// How far back progress samples are used for the ETA
+const RATE_WINDOW: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageStatus {
+    Pending,
+    Running,
+    Finished,
+}
+
+/// Progress of a single pipeline stage
+#[derive(Debug, Clone)]
+pub struct StageProgress {
+    pub name: String,
+    pub checkpoint: Option<u64>, // Block the stage has processed up to
+    pub target: Option<u64>, // Block the stage is syncing to, from the log lines
+    pub entities_processed: Option<u64>,
+    pub entities_total: Option<u64>,
+    pub log_status: Option<StageStatus>, // Status reported by the pipeline's log lines
+    pub log_progress: Option<f64>, // stage_progress field, 0-100
+    pub log_eta: Option<String>, // stage_eta field, as reth formats it
+    samples: VecDeque<(Instant, f64)>, // Processed entity (or block) counts over time
+}
+
+impl StageProgress {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            checkpoint: None,
+            target: None,
+            entities_processed: None,
+            entities_total: None,
+            log_status: None,
+            log_progress: None,
+            log_eta: None,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Done and remaining work, in entities when the metrics report them, otherwise in blocks
+    fn work(&self) -> Option<(f64, f64)> {
+        match (self.entities_processed, self.entities_total) {
+            (Some(processed), Some(total)) if total > 0 => Some((processed as f64, total as f64)),
+            _ => match (self.checkpoint, self.target) {
+                (Some(checkpoint), Some(target)) if target > 0 => Some((checkpoint as f64, target as f64)),
+                _ => None,
+            },
+        }
+    }
+
+    /// Completed fraction between 0 and 1, if known
+    pub fn fraction(&self) -> Option<f64> {
+        match self.work() {
+            Some((done, total)) => Some((done / total).clamp(0.0, 1.0)),
+            None => self.log_progress.map(|percent| (percent / 100.0).clamp(0.0, 1.0)),
+        }
+    }
+
+    /// Processing rate per second over the recent samples
+    pub fn rate(&self) -> Option<f64> {
+        let (first_time, first_value) = self.samples.front()?;
+        let (last_time, last_value) = self.samples.back()?;
+        let elapsed = last_time.duration_since(*first_time).as_secs_f64();
+        let rate = (last_value - first_value) / elapsed;
+        (elapsed > 0.0 && rate > 0.0).then_some(rate)
+    }
+
+    /// Estimated time left, from the measured rate or reth's own estimate
+    pub fn eta(&self) -> Option<String> {
+        if let (Some((done, total)), Some(rate)) = (self.work(), self.rate()) {
+            return Some(format_duration(Duration::from_secs_f64(((total - done).max(0.0)) / rate)));
+        }
+        self.log_eta.clone()
+    }
+
+    fn record_sample(&mut self) {
+        let Some((done, _)) = self.work() else {
+            return;
+        };
+        let now = Instant::now();
+        // Progress went backwards, e.g. a new pipeline run; the old samples no longer apply
+        if self.samples.back().is_some_and(|(_, last)| done < *last) {
+            self.samples.clear();
+        }
+        self.samples.push_back((now, done));
+        while self.samples.front().is_some_and(|(time, _)| now.duration_since(*time) > RATE_WINDOW) {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Follows reth's staged sync pipeline using the `reth_sync_*` stage metrics and the
+/// pipeline's "Executing stage" / "Finished stage" log lines
+#[derive(Debug, Clone, Default)]
+pub struct SyncStageTracker {
+    stages: HashMap<String, StageProgress>,
+    current: Option<String>, // Stage the logs last reported as running
+    pipeline_total: Option<usize>, // Stage count from the pipeline_stages=N/M field
+}
+
+impl SyncStageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    fn stage_mut(&mut self, name: &str) -> &mut StageProgress {
+        self.stages
+            .entry(name.to_string())
+            .or_insert_with(|| StageProgress::new(name))
+    }
+
+    /// Read the per-stage gauges, e.g. `reth_sync_checkpoint{stage="Headers"} 22000000`
+    pub fn update_from_metrics(&mut self, metrics: &Exposition) {
+        let mut updated = Vec::new();
+        for name in ["reth_sync_checkpoint", "reth_sync_entities_processed", "reth_sync_entities_total"] {
+            for sample in metrics.samples(name) {
+                let Some(stage) = sample.label("stage") else {
+                    continue;
+                };
+                let value = Some(sample.value.max(0.0) as u64);
+                let progress = self.stage_mut(stage);
+                match name {
+                    "reth_sync_checkpoint" => progress.checkpoint = value,
+                    "reth_sync_entities_processed" => progress.entities_processed = value,
+                    _ => progress.entities_total = value,
+                }
+                updated.push(stage.to_string());
+            }
+        }
+
+        updated.sort();
+        updated.dedup();
+        for stage in updated {
+            self.stage_mut(&stage).record_sample();
+        }
+    }
+
+    /// Pick up stage transitions and progress from the pipeline's log lines
+    pub fn process_logs(&mut self, records: &[Arc<LogRecord>]) {
+        for record in records {
+            let Some(stage) = record.fields.get("stage").cloned() else {
+                continue;
+            };
+            let status = match record.message.as_str() {
+                "Finished stage" => StageStatus::Finished,
+                "Preparing stage" | "Executing stage" | "Committed stage progress" | "Status" => StageStatus::Running,
+                _ => continue,
+            };
+
+            if let Some(total) = record.fields
+                .get("pipeline_stages")
+                .and_then(|value| value.split_once('/'))
+                .and_then(|(_, total)| total.parse().ok())
+            {
+                self.pipeline_total = Some(total);
+            }
+
+            let progress = self.stage_mut(&stage);
+            progress.log_status = Some(status);
+            if let Some(checkpoint) = record.fields.get("checkpoint").and_then(|value| parse_block(value)) {
+                progress.checkpoint = Some(checkpoint);
+            }
+            if let Some(target) = record.fields.get("target").and_then(|value| parse_block(value)) {
+                progress.target = Some(target);
+            }
+            if let Some(percent) = record.fields.get("stage_progress").and_then(|value| value.trim_end_matches('%').parse().ok()) {
+                progress.log_progress = Some(percent);
+            }
+            progress.log_eta = record.fields.get("stage_eta").cloned();
+            progress.record_sample();
+
+            if status == StageStatus::Running {
+                // Stages before the running one are done; later ones run again in this pass
+                let index = stage_index(&stage);
+                for other in self.stages.values_mut().filter(|other| other.name != stage && other.log_status.is_some()) {
+                    let other_index = stage_index(&other.name);
+                    if other_index < index && other.log_status == Some(StageStatus::Running) {
+                        other.log_status = Some(StageStatus::Finished);
+                    } else if other_index > index {
+                        other.log_status = Some(StageStatus::Pending);
+                    }
+                }
+                self.current = Some(stage);
+            } else if self.current.as_ref() == Some(&stage) {
+                self.current = None;
+            }
+        }
+    }
+
+    /// Stages in pipeline order with their status
+    pub fn stages(&self) -> Vec<(&StageProgress, StageStatus)> {
+        let mut stages: Vec<&StageProgress> = self.stages.values().collect();
+        stages.sort_by_key(|stage| (stage_index(&stage.name), stage.name.clone()));
+
+        // Without log lines, the first stage that hasn't reached its total is the one running
+        let current_index = self.current.as_deref().map(stage_index);
+        let mut running_assigned = current_index.is_some();
+        stages
+            .into_iter()
+            .map(|stage| {
+                let status = match stage.log_status {
+                    Some(status) => status,
+                    None if stage.fraction().is_some_and(|fraction| fraction >= 1.0) => StageStatus::Finished,
+                    None if current_index.is_some_and(|current| stage_index(&stage.name) < current) => StageStatus::Finished,
+                    None if !running_assigned && stage.checkpoint.is_some_and(|checkpoint| checkpoint > 0) => {
+                        running_assigned = true;
+                        StageStatus::Running
+                    }
+                    None => StageStatus::Pending,
+                };
+                (stage, status)
+            })
+            .collect()
+    }
+
+    /// Name of the stage currently running, if any
+    pub fn current_stage(&self) -> Option<String> {
+        self.stages()
+            .into_iter()
+            .find(|(_, status)| *status == StageStatus::Running)
+            .map(|(stage, _)| stage.name.clone())
+    }
+
+    /// Overall pipeline progress in percent: finished stages plus the running stage's fraction
+    pub fn overall_progress(&self) -> Option<f64> {
+        let stages = self.stages();
+        if stages.is_empty() {
+            return None;
+        }
+        let total = self.pipeline_total.unwrap_or(stages.len()).max(stages.len());
+        let done: f64 = stages
+            .iter()
+            .map(|(stage, status)| match status {
+                StageStatus::Finished => 1.0,
+                StageStatus::Running => stage.fraction().unwrap_or(0.0),
+                StageStatus::Pending => 0.0,
+            })
+            .sum();
+        Some(done / total as f64 * 100.0)
+    }
+}
+
+fn stage_index(name: &str) -> usize {
+    STAGE_ORDER.iter().position(|stage| *stage == name).unwrap_or(STAGE_ORDER.len())
+}
+
+/// Block numbers are logged as `123`, `Some(123)` or `None`
+fn parse_block(value: &str) -> Option<u64> {
+    value.trim_start_matches("Some(").trim_end_matches(')').parse().ok()
+}
+
+/// Format a duration as e.g. "2h 05m", "4m 10s" or "12s"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes, seconds) = (secs / 86400, (secs % 86400) / 3600, (secs % 3600) / 60, secs % 60);
+    if days > 0 {
+        format!("{}d {:02}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
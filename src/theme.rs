@@ -83,6 +83,16 @@ impl RethTheme {
             .strong()
     }
     
+    /// Color of a summary or histogram line: p50, p90, p99 or the mean
+    pub fn quantile_color(line: &str) -> egui::Color32 {
+        match line {
+            "p50" => Self::PRIMARY,
+            "p90" => Self::WARNING,
+            "p99" => Self::ERROR,
+            _ => Self::TEXT_SECONDARY,
+        }
+    }
+    
     pub fn body_text(text: &str) -> egui::RichText {
         egui::RichText::new(text)
             .size(14.0)
@@ -0,0 +1,159 @@
+use crate::log_alerts::{self, AlertRule, AlertSeverity};
+use crate::log_record::LogLevel;
+use crate::settings::{DesktopSettings, DesktopSettingsManager};
+use crate::theme::RethTheme;
+
+const LEVELS: [LogLevel; 5] = [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace];
+
+pub struct AlertRulesWindow;
+
+impl AlertRulesWindow {
+    /// Show the alert rule editor. Returns true when the rules changed and were saved.
+    pub fn show_content(ui: &mut egui::Ui, desktop_settings: &mut DesktopSettings, errors: &[String]) -> bool {
+        let mut changed = false;
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.add_space(8.0);
+            ui.label(RethTheme::muted_text("Rules watch the node's log output. Matching lines are recorded in the event timeline; rules with alerts enabled also show a banner until dismissed."));
+            ui.add_space(12.0);
+
+            let alerts = &mut desktop_settings.alerts;
+            ui.horizontal(|ui| {
+                ui.label(RethTheme::text("Built-in rules"));
+                changed |= ui.checkbox(&mut alerts.builtin_rules, "").changed();
+            });
+            ui.add_enabled_ui(alerts.builtin_rules, |ui| {
+                for rule in log_alerts::builtin_rules() {
+                    let mut enabled = !alerts.disabled_builtin.contains(&rule.name);
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut enabled, &rule.name).changed() {
+                            if enabled {
+                                alerts.disabled_builtin.retain(|name| name != &rule.name);
+                            } else {
+                                alerts.disabled_builtin.push(rule.name.clone());
+                            }
+                            changed = true;
+                        }
+                        ui.label(RethTheme::muted_text(&format!("{} - {}", rule.severity.as_str(), rule.summary())));
+                    });
+                }
+            });
+
+            ui.add_space(16.0);
+            ui.separator();
+            ui.add_space(8.0);
+            ui.label(RethTheme::text("Your rules"));
+            if alerts.rules.is_empty() {
+                ui.label(RethTheme::muted_text("No custom rules yet"));
+            }
+
+            let mut remove = None;
+            for (index, rule) in alerts.rules.iter_mut().enumerate() {
+                egui::CollapsingHeader::new(format!("{} ({})", rule.name, rule.summary()))
+                    .id_source(("alert_rule", index))
+                    .show(ui, |ui| {
+                        changed |= Self::show_rule(ui, rule, index);
+                        if ui.button("Remove rule").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+            }
+            if let Some(index) = remove {
+                alerts.rules.remove(index);
+                changed = true;
+            }
+
+            ui.add_space(8.0);
+            if ui.button("Add Rule").clicked() {
+                alerts.rules.push(AlertRule::new(format!("Rule {}", alerts.rules.len() + 1)));
+                changed = true;
+            }
+
+            for error in errors {
+                ui.label(RethTheme::error_text(&format!("Rule skipped - {}", error)));
+            }
+        });
+
+        if changed {
+            if let Err(e) = DesktopSettingsManager::save_desktop_settings(desktop_settings) {
+                eprintln!("Failed to save desktop settings: {}", e);
+            }
+        }
+        changed
+    }
+
+    fn show_rule(ui: &mut egui::Ui, rule: &mut AlertRule, index: usize) -> bool {
+        let mut changed = false;
+        egui::Grid::new(("alert_rule_grid", index))
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("Name:");
+                changed |= ui.text_edit_singleline(&mut rule.name).changed();
+                ui.end_row();
+
+                ui.label("Enabled:");
+                changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                ui.end_row();
+
+                ui.label("Levels:");
+                ui.horizontal(|ui| {
+                    for level in LEVELS {
+                        let selected = rule.levels.contains(&level);
+                        if ui.selectable_label(selected, level.as_str()).clicked() {
+                            if selected {
+                                rule.levels.retain(|l| *l != level);
+                            } else {
+                                rule.levels.push(level);
+                            }
+                            changed = true;
+                        }
+                    }
+                    if rule.levels.is_empty() {
+                        ui.label(RethTheme::muted_text("any"));
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Target contains:");
+                changed |= ui.add(egui::TextEdit::singleline(&mut rule.target).hint_text("e.g. reth::cli")).changed();
+                ui.end_row();
+
+                ui.label("Message regex:");
+                ui.horizontal(|ui| {
+                    changed |= ui.add(egui::TextEdit::singleline(&mut rule.pattern).hint_text("e.g. (?i)timed out")).changed();
+                    if let Err(e) = regex::Regex::new(&rule.pattern) {
+                        ui.label(RethTheme::error_text("Invalid regex")).on_hover_text(e.to_string());
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Fire after:");
+                ui.horizontal(|ui| {
+                    changed |= ui.add(egui::DragValue::new(&mut rule.threshold).clamp_range(1..=10_000).suffix(" matches")).changed();
+                    ui.label("within");
+                    changed |= ui.add(egui::DragValue::new(&mut rule.window_secs).clamp_range(1..=86_400).suffix(" s")).changed();
+                });
+                ui.end_row();
+
+                ui.label("Cooldown:");
+                changed |= ui.add(egui::DragValue::new(&mut rule.cooldown_secs).clamp_range(0..=86_400).suffix(" s")).changed();
+                ui.end_row();
+
+                ui.label("Severity:");
+                egui::ComboBox::from_id_source(("alert_rule_severity", index))
+                    .selected_text(rule.severity.as_str())
+                    .show_ui(ui, |ui| {
+                        for severity in AlertSeverity::ALL {
+                            changed |= ui.selectable_value(&mut rule.severity, severity, severity.as_str()).changed();
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Show alert:");
+                changed |= ui.checkbox(&mut rule.notify, "").on_hover_text("Otherwise events only go to the timeline").changed();
+                ui.end_row();
+            });
+        changed
+    }
+}
@@ -21,7 +21,86 @@ impl DesktopSettingsWindow {
             });
             
             ui.add_space(8.0);
-            ui.label(RethTheme::muted_text("When enabled, Reth will continue running even when the application window is closed."));
+            ui.label(RethTheme::muted_text("When enabled, Reth is started detached and keeps running after the application is closed. It is re-adopted the next time the app opens. Applies to the next start."));
+            
+            ui.add_space(16.0);
+            
+            // Graceful shutdown timeout
+            ui.horizontal(|ui| {
+                ui.label("Shutdown grace period:");
+                if ui.add(egui::DragValue::new(&mut desktop_settings.shutdown_timeout_secs)
+                    .clamp_range(5..=600)
+                    .suffix(" s"))
+                    .changed() {
+                    if let Err(e) = DesktopSettingsManager::save_desktop_settings(desktop_settings) {
+                        eprintln!("Failed to save desktop settings: {}", e);
+                    }
+                }
+            });
+            
+            ui.add_space(8.0);
+            ui.label(RethTheme::muted_text("How long to wait for Reth to exit after asking it to stop before the process is killed."));
+            
+            ui.add_space(16.0);
+            ui.separator();
+            ui.add_space(8.0);
+            
+            // Automatic restart supervisor
+            let mut changed = false;
+            let supervisor = &mut desktop_settings.supervisor;
+            ui.horizontal(|ui| {
+                ui.label("Restart Reth automatically if it crashes:");
+                changed |= ui.checkbox(&mut supervisor.enabled, "").changed();
+            });
+            
+            ui.add_enabled_ui(supervisor.enabled, |ui| {
+                egui::Grid::new("supervisor_settings_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Initial backoff:");
+                        changed |= ui.add(egui::DragValue::new(&mut supervisor.initial_backoff_secs)
+                            .clamp_range(1..=3600)
+                            .suffix(" s"))
+                            .changed();
+                        ui.end_row();
+                        
+                        ui.label("Maximum backoff:");
+                        changed |= ui.add(egui::DragValue::new(&mut supervisor.max_backoff_secs)
+                            .clamp_range(1..=86400)
+                            .suffix(" s"))
+                            .changed();
+                        ui.end_row();
+                        
+                        ui.label("Maximum restarts:");
+                        changed |= ui.add(egui::DragValue::new(&mut supervisor.max_restarts)
+                            .clamp_range(1..=100))
+                            .changed();
+                        ui.end_row();
+                        
+                        ui.label("Restart window:");
+                        changed |= ui.add(egui::DragValue::new(&mut supervisor.window_secs)
+                            .clamp_range(60..=86400)
+                            .suffix(" s"))
+                            .changed();
+                        ui.end_row();
+                        
+                        ui.label("Log lines per crash:");
+                        changed |= ui.add(egui::DragValue::new(&mut supervisor.log_lines)
+                            .clamp_range(0..=1000))
+                            .changed();
+                        ui.end_row();
+                    });
+            });
+            
+            ui.add_space(8.0);
+            ui.label(RethTheme::muted_text("Crashed nodes are restarted with exponential backoff. If the node crashes more often than the maximum within the window, automatic restarts stop until you start it again manually."));
+            
+            if changed {
+                if let Err(e) = DesktopSettingsManager::save_desktop_settings(desktop_settings) {
+                    eprintln!("Failed to save desktop settings: {}", e);
+                }
+            }
         });
     }
 }
\ No newline at end of file
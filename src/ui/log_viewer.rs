@@ -0,0 +1,646 @@
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use chrono::{DateTime, Local};
+use egui::text::{LayoutJob, TextFormat};
+use regex::{Regex, RegexBuilder};
+use crate::launch_args;
+use crate::log_archive::{LogArchive, SessionInfo};
+use crate::log_export::{self, ExportFormat};
+use crate::log_record::{LogLevel, LogRecord};
+use crate::theme::RethTheme;
+
+/// Lines kept in memory for the viewer
+const MAX_LINES: usize = 100_000;
+
+/// Newest lines loaded when opening an archived session
+const MAX_ARCHIVED_LINES: usize = 500_000;
+
+const ROW_FONT_SIZE: f32 = 12.0;
+
+/// Result of reading an archived session: the records and the number of older lines skipped
+type SessionLoad = Result<(Vec<LogRecord>, u64), String>;
+
+/// Result of a background export: the written file or an error message
+type ExportResult = Result<PathBuf, String>;
+
+/// Level toggles, most severe first
+const LEVELS: [LogLevel; 5] = [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace];
+
+/// Searchable, filterable view over the node's log output
+pub struct LogViewer {
+    lines: VecDeque<Arc<LogRecord>>, // Shared with the log ring, so live lines aren't copied
+    first_seq: u64, // Sequence number of lines[0]; stays valid as old lines are dropped
+    filtered: Vec<u64>, // Sequence numbers of lines passing the filters, ascending
+    filters_dirty: bool,
+    shown_levels: HashSet<LogLevel>,
+    targets: BTreeSet<String>, // Every target seen so far, for the target picker
+    target_filter: String,
+    search: String,
+    use_regex: bool,
+    case_sensitive: bool,
+    search_pattern: Option<Regex>,
+    search_error: Option<String>,
+    time_window: Option<(DateTime<Local>, DateTime<Local>)>, // Only lines logged in [from, to)
+    follow: bool,
+    selected: Option<u64>, // Line jumped to with "Next error"
+    scroll_to_row: Option<usize>,
+    sessions: Vec<SessionInfo>, // Archived sessions, refreshed when the picker opens
+    viewing_session: Option<SessionInfo>,
+    live_stash: Option<(VecDeque<Arc<LogRecord>>, u64)>, // Live lines and first_seq while a session is shown
+    session_loader: Option<mpsc::Receiver<SessionLoad>>,
+    skipped_lines: u64, // Older lines of the archived session that weren't loaded
+    export_format: ExportFormat,
+    export_job: Option<mpsc::Receiver<ExportResult>>,
+    export_status: Option<String>, // Result of the last export, shown in the toolbar
+    bundle_requested: bool, // "Diagnostic bundle" was clicked; the app gathers the rest
+    dropped_lines: u64, // Live lines lost before they reached the viewer
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogViewer {
+    pub fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            first_seq: 0,
+            filtered: Vec::new(),
+            filters_dirty: false,
+            shown_levels: LEVELS.iter().copied().collect(),
+            targets: BTreeSet::new(),
+            target_filter: String::new(),
+            search: String::new(),
+            use_regex: false,
+            case_sensitive: false,
+            search_pattern: None,
+            search_error: None,
+            time_window: None,
+            follow: true,
+            selected: None,
+            scroll_to_row: None,
+            sessions: Vec::new(),
+            viewing_session: None,
+            live_stash: None,
+            session_loader: None,
+            skipped_lines: 0,
+            export_format: ExportFormat::Text,
+            export_job: None,
+            export_status: None,
+            bundle_requested: false,
+            dropped_lines: 0,
+        }
+    }
+
+    /// Whether an archived session is shown instead of the live output
+    pub fn is_viewing_session(&self) -> bool {
+        self.viewing_session.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Append new live records, dropping the oldest once the viewer is full
+    pub fn push(&mut self, records: &[Arc<LogRecord>]) {
+        // Live output keeps arriving while an archived session is shown
+        if let Some((lines, first_seq)) = self.live_stash.as_mut() {
+            lines.extend(records.iter().cloned());
+            if lines.len() > MAX_LINES {
+                let excess = lines.len() - MAX_LINES;
+                lines.drain(..excess);
+                *first_seq += excess as u64;
+            }
+            return;
+        }
+
+        for record in records {
+            if !record.target.is_empty() && !self.targets.contains(&record.target) {
+                self.targets.insert(record.target.clone());
+            }
+            let seq = self.first_seq + self.lines.len() as u64;
+            if !self.filters_dirty && self.matches(record) {
+                self.filtered.push(seq);
+            }
+            self.lines.push_back(record.clone());
+        }
+
+        if self.lines.len() > MAX_LINES {
+            let excess = self.lines.len() - MAX_LINES;
+            self.lines.drain(..excess);
+            self.first_seq += excess as u64;
+            let dropped = self.filtered.partition_point(|seq| *seq < self.first_seq);
+            self.filtered.drain(..dropped);
+        }
+    }
+
+    /// Total live lines dropped because the pipeline fell behind, shown in the toolbar
+    pub fn set_dropped_lines(&mut self, dropped: u64) {
+        self.dropped_lines = dropped;
+    }
+
+    pub fn clear(&mut self) {
+        self.first_seq += self.lines.len() as u64;
+        self.lines.clear();
+        self.filtered.clear();
+        self.targets.clear();
+        self.selected = None;
+    }
+
+    /// Show an archived session instead of the live output
+    fn open_session(&mut self, session: SessionInfo) {
+        if self.live_stash.is_none() {
+            self.live_stash = Some((std::mem::take(&mut self.lines), self.first_seq));
+        }
+        self.lines.clear();
+        self.filtered.clear();
+        self.first_seq = 0;
+        self.selected = None;
+        self.skipped_lines = 0;
+
+        let (sender, receiver) = mpsc::channel();
+        let id = session.id.clone();
+        std::thread::spawn(move || {
+            let result = LogArchive::read_session(&id, MAX_ARCHIVED_LINES).map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        });
+        self.session_loader = Some(receiver);
+        self.viewing_session = Some(session);
+    }
+
+    fn back_to_live(&mut self) {
+        if let Some((lines, first_seq)) = self.live_stash.take() {
+            self.lines = lines;
+            self.first_seq = first_seq;
+        }
+        self.viewing_session = None;
+        self.session_loader = None;
+        self.selected = None;
+        self.follow = true;
+        self.rebuild_targets();
+        self.filters_dirty = true;
+    }
+
+    /// Pick up an archived session once the background read has finished
+    fn poll_session_loader(&mut self) {
+        let Some(receiver) = &self.session_loader else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok((records, skipped))) => {
+                self.lines = records.into_iter().map(Arc::new).collect();
+                self.skipped_lines = skipped;
+                self.session_loader = None;
+                self.rebuild_targets();
+                self.filters_dirty = true;
+            }
+            Ok(Err(e)) => {
+                eprintln!("Failed to read archived log session: {}", e);
+                self.session_loader = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.session_loader = None,
+        }
+    }
+
+    /// Show only live lines logged between `from` and `to`, e.g. from a click on the log rate plot
+    pub fn show_time_window(&mut self, from: DateTime<Local>, to: DateTime<Local>) {
+        if self.viewing_session.is_some() {
+            self.back_to_live();
+        }
+        self.time_window = Some((from, to));
+        self.follow = false;
+        self.selected = None;
+        self.scroll_to_row = Some(0);
+        self.filters_dirty = true;
+    }
+
+    /// Run an export on a background thread; the toolbar shows its result
+    pub fn run_export<F>(&mut self, job: F)
+    where
+        F: FnOnce() -> Result<PathBuf, Box<dyn std::error::Error>> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(job().map_err(|e| e.to_string()));
+        });
+        self.export_job = Some(receiver);
+        self.export_status = None;
+    }
+
+    /// Whether the diagnostic bundle was requested since the last call
+    pub fn take_bundle_request(&mut self) -> bool {
+        std::mem::take(&mut self.bundle_requested)
+    }
+
+    fn poll_export_job(&mut self) {
+        let Some(receiver) = &self.export_job else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.export_status = Some(match result {
+                    Ok(path) => format!("Exported to {}", path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                });
+                self.export_job = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.export_job = None,
+        }
+    }
+
+    /// File name stem for exports of the current source
+    fn export_name(&self) -> String {
+        let source = match &self.viewing_session {
+            Some(session) => session.id.clone(),
+            None => "live".to_string(),
+        };
+        format!("reth-logs-{}-{}", source, chrono::Local::now().format("%Y%m%d-%H%M%S"))
+    }
+
+    /// Export the lines that pass the current filters
+    fn export_filtered(&mut self) {
+        let records: Vec<Arc<LogRecord>> = self.filtered
+            .iter()
+            .filter_map(|seq| self.shared_line(*seq).cloned())
+            .collect();
+        let (name, format) = (self.export_name(), self.export_format);
+        self.run_export(move || log_export::export_records(&records, &name, format));
+    }
+
+    /// Export every line of the archived session being viewed, including lines not loaded
+    fn export_session(&mut self) {
+        let Some(session) = &self.viewing_session else {
+            return;
+        };
+        let (id, name, format) = (session.id.clone(), self.export_name(), self.export_format);
+        self.run_export(move || {
+            let (records, _) = LogArchive::read_session(&id, usize::MAX)?;
+            log_export::export_records(&records, &name, format)
+        });
+    }
+
+    fn rebuild_targets(&mut self) {
+        self.targets = self.lines
+            .iter()
+            .filter(|record| !record.target.is_empty())
+            .map(|record| record.target.clone())
+            .collect();
+    }
+
+    fn shared_line(&self, seq: u64) -> Option<&Arc<LogRecord>> {
+        seq.checked_sub(self.first_seq).and_then(|index| self.lines.get(index as usize))
+    }
+
+    fn line(&self, seq: u64) -> Option<&LogRecord> {
+        self.shared_line(seq).map(|record| record.as_ref())
+    }
+
+    /// The text shown for a line, which is also what the search runs against
+    fn line_text(record: &LogRecord) -> String {
+        if record.target.is_empty() {
+            format!("{} {:5} {}", record.time_text(), record.level.as_str(), record.text())
+        } else {
+            format!("{} {:5} {}: {}", record.time_text(), record.level.as_str(), record.target, record.text())
+        }
+    }
+
+    fn matches(&self, record: &LogRecord) -> bool {
+        if !self.shown_levels.contains(&record.level) {
+            return false;
+        }
+
+        if let Some((from, to)) = self.time_window {
+            if record.timestamp < from || record.timestamp >= to {
+                return false;
+            }
+        }
+
+        // Comma separated module paths, e.g. "reth::cli, sync::stages"
+        let target_filter = self.target_filter.trim();
+        if !target_filter.is_empty()
+            && !target_filter
+                .split(',')
+                .map(str::trim)
+                .filter(|term| !term.is_empty())
+                .any(|term| record.target.contains(term))
+        {
+            return false;
+        }
+
+        match &self.search_pattern {
+            Some(pattern) => pattern.is_match(&Self::line_text(record)),
+            None => true,
+        }
+    }
+
+    /// Recompile the search and re-run the filters over every line
+    fn apply_filters(&mut self) {
+        self.search_error = None;
+        self.search_pattern = if self.search.is_empty() {
+            None
+        } else {
+            let source = if self.use_regex { self.search.clone() } else { regex::escape(&self.search) };
+            match RegexBuilder::new(&source).case_insensitive(!self.case_sensitive).build() {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    self.search_error = Some(e.to_string());
+                    None
+                }
+            }
+        };
+
+        self.filtered = self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| self.matches(record))
+            .map(|(index, _)| self.first_seq + index as u64)
+            .collect();
+        self.filters_dirty = false;
+    }
+
+    /// Select the next visible error after the current selection, wrapping around
+    fn jump_to_next_error(&mut self) {
+        let start = match self.selected {
+            Some(selected) => self.filtered.partition_point(|seq| *seq <= selected),
+            None => 0,
+        };
+        let rows = self.filtered.len();
+        let next = (0..rows)
+            .map(|offset| (start + offset) % rows)
+            .find(|row| self.line(self.filtered[*row]).is_some_and(|record| record.level == LogLevel::Error));
+        if let Some(row) = next {
+            self.selected = Some(self.filtered[row]);
+            self.scroll_to_row = Some(row);
+            self.follow = false;
+        }
+    }
+
+    pub fn level_color(level: LogLevel) -> egui::Color32 {
+        match level {
+            LogLevel::Error => egui::Color32::from_rgb(255, 100, 100),
+            LogLevel::Warn => egui::Color32::from_rgb(255, 200, 100),
+            LogLevel::Info => egui::Color32::from_rgb(255, 193, 7),
+            LogLevel::Debug => egui::Color32::from_rgb(150, 150, 255),
+            LogLevel::Trace => egui::Color32::GRAY,
+        }
+    }
+
+    /// Toolbar with the filters, followed by the log lines
+    pub fn show(&mut self, ui: &mut egui::Ui, height: f32) {
+        self.poll_session_loader();
+        self.poll_export_job();
+        self.show_source_picker(ui);
+        self.show_toolbar(ui);
+        if self.filters_dirty {
+            self.apply_filters();
+        }
+        ui.add_space(6.0);
+        self.show_lines(ui, height);
+    }
+
+    /// Choose between live output and archived sessions
+    fn show_source_picker(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Source:");
+            let selected_text = match &self.viewing_session {
+                Some(session) => session.label(),
+                None => "Live".to_string(),
+            };
+            let mut open_session = None;
+            let mut go_live = false;
+            let response = egui::ComboBox::from_id_source("log_viewer_source")
+                .selected_text(selected_text)
+                .width(320.0)
+                .show_ui(ui, |ui| {
+                    if ui.selectable_label(self.viewing_session.is_none(), "Live").clicked() {
+                        go_live = true;
+                    }
+                    for session in &self.sessions {
+                        let selected = self.viewing_session.as_ref().is_some_and(|viewing| viewing.id == session.id);
+                        if ui.selectable_label(selected, session.label()).clicked() {
+                            open_session = Some(session.clone());
+                        }
+                    }
+                });
+            if response.response.clicked() {
+                self.sessions = LogArchive::load_index();
+            }
+
+            if go_live && self.viewing_session.is_some() {
+                self.back_to_live();
+            }
+            if let Some(session) = open_session {
+                self.open_session(session);
+            }
+
+            if self.session_loader.is_some() {
+                ui.spinner();
+                ui.label(RethTheme::muted_text("Loading session..."));
+            } else if let Some(session) = &self.viewing_session {
+                if !session.launch_command.is_empty() {
+                    ui.label(RethTheme::muted_text(&launch_args::format_command(&session.launch_command)))
+                        .on_hover_text("Launch command of this session");
+                }
+                if self.skipped_lines > 0 {
+                    ui.label(RethTheme::warning_text(&format!("Showing the newest {} lines, {} older lines not loaded", self.lines.len(), self.skipped_lines)));
+                }
+            }
+        });
+    }
+
+    fn show_toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            for level in LEVELS {
+                let shown = self.shown_levels.contains(&level);
+                let text = egui::RichText::new(level.as_str())
+                    .monospace()
+                    .color(if shown { Self::level_color(level) } else { RethTheme::TEXT_SECONDARY });
+                if ui.selectable_label(shown, text).clicked() {
+                    if shown {
+                        self.shown_levels.remove(&level);
+                    } else {
+                        self.shown_levels.insert(level);
+                    }
+                    self.filters_dirty = true;
+                }
+            }
+
+            ui.separator();
+            ui.label("Target:");
+            if ui.add(egui::TextEdit::singleline(&mut self.target_filter)
+                .hint_text("e.g. reth::cli, sync::stages")
+                .desired_width(200.0))
+                .changed() {
+                self.filters_dirty = true;
+            }
+            ui.menu_button("▾", |ui| {
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    if self.targets.is_empty() {
+                        ui.label(RethTheme::muted_text("No targets seen yet"));
+                    }
+                    for target in &self.targets {
+                        if ui.button(RethTheme::monospace_text(target)).clicked() {
+                            self.target_filter = target.clone();
+                            self.filters_dirty = true;
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+        });
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Search:");
+            if ui.add(egui::TextEdit::singleline(&mut self.search)
+                .hint_text(if self.use_regex { "regular expression" } else { "text" })
+                .desired_width(260.0))
+                .changed() {
+                self.filters_dirty = true;
+            }
+            if ui.checkbox(&mut self.use_regex, "Regex").changed() {
+                self.filters_dirty = true;
+            }
+            if ui.checkbox(&mut self.case_sensitive, "Match case").changed() {
+                self.filters_dirty = true;
+            }
+            if let Some(error) = &self.search_error {
+                ui.label(RethTheme::error_text("Invalid regex")).on_hover_text(error);
+            }
+            if let Some((from, to)) = self.time_window {
+                ui.label(RethTheme::warning_text(&format!("Time {} – {}", from.format("%H:%M:%S"), to.format("%H:%M:%S"))));
+                if ui.small_button("✕").on_hover_text("Show all times").clicked() {
+                    self.time_window = None;
+                    self.filters_dirty = true;
+                }
+            }
+
+            ui.separator();
+            let follow_label = if self.follow { "⏸ Pause" } else { "▶ Follow" };
+            if ui.button(follow_label).clicked() {
+                self.follow = !self.follow;
+                if self.follow {
+                    self.selected = None;
+                    self.scroll_to_row = Some(self.filtered.len());
+                }
+            }
+            if ui.button("Next error").clicked() {
+                self.jump_to_next_error();
+            }
+            if self.viewing_session.is_none() && ui.button("Clear").clicked() {
+                self.clear();
+            }
+
+            self.show_export_menu(ui);
+
+            ui.label(RethTheme::muted_text(&format!("{} of {} lines", self.filtered.len(), self.lines.len())));
+            if self.dropped_lines > 0 {
+                ui.label(RethTheme::warning_text(&format!("⚠ {} dropped", self.dropped_lines)))
+                    .on_hover_text("Lines lost because the node logged faster than the app could take them in");
+            }
+            if self.export_job.is_some() {
+                ui.spinner();
+            } else if let Some(status) = &self.export_status {
+                ui.label(RethTheme::muted_text(status));
+            }
+        });
+    }
+
+    fn show_export_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("Export ▾", |ui| {
+            ui.label(RethTheme::muted_text("Format"));
+            for format in ExportFormat::ALL {
+                ui.radio_value(&mut self.export_format, format, format.label());
+            }
+            ui.separator();
+
+            let busy = self.export_job.is_some();
+            if ui.add_enabled(!busy, egui::Button::new(format!("Filtered view ({} lines)", self.filtered.len())))
+                .on_hover_text("Lines passing the current level, target and search filters")
+                .clicked() {
+                self.export_filtered();
+                ui.close_menu();
+            }
+            if self.viewing_session.is_some()
+                && ui.add_enabled(!busy && self.session_loader.is_none(), egui::Button::new("Whole session"))
+                    .on_hover_text("Every line of this session, ignoring filters")
+                    .clicked() {
+                self.export_session();
+                ui.close_menu();
+            }
+            ui.separator();
+            if ui.add_enabled(!busy, egui::Button::new("Diagnostic bundle…"))
+                .on_hover_text("Logs, redacted settings, reth.toml, launch command, system check and a metrics sample in one .zip archive")
+                .clicked() {
+                self.bundle_requested = true;
+                ui.close_menu();
+            }
+            ui.label(RethTheme::muted_text("Files are written to your downloads folder"));
+        });
+    }
+
+    fn show_lines(&mut self, ui: &mut egui::Ui, height: f32) {
+        let font_id = egui::FontId::monospace(ROW_FONT_SIZE);
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font_id));
+        let spacing = ui.spacing().item_spacing.y;
+
+        let mut scroll_area = egui::ScrollArea::both()
+            .max_height(height)
+            .auto_shrink([false; 2])
+            .stick_to_bottom(self.follow);
+        if let Some(row) = self.scroll_to_row.take() {
+            // Center the target row in the view
+            let offset = row as f32 * (row_height + spacing) - height / 2.0;
+            scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+        }
+
+        if self.filtered.is_empty() {
+            ui.label(RethTheme::muted_text("No lines match the current filters"));
+            return;
+        }
+
+        scroll_area.show_rows(ui, row_height, self.filtered.len(), |ui, rows| {
+            ui.style_mut().wrap = Some(false);
+            for row in rows {
+                let seq = self.filtered[row];
+                let Some(record) = self.line(seq) else {
+                    continue;
+                };
+                let job = self.highlighted_line(record, &font_id, self.selected == Some(seq));
+                ui.label(job);
+            }
+        });
+    }
+
+    /// Lay out a line in its level color, with search matches highlighted
+    fn highlighted_line(&self, record: &LogRecord, font_id: &egui::FontId, selected: bool) -> LayoutJob {
+        let text = Self::line_text(record);
+        let base = TextFormat {
+            font_id: font_id.clone(),
+            color: Self::level_color(record.level),
+            background: if selected { RethTheme::SURFACE_VARIANT } else { egui::Color32::TRANSPARENT },
+            ..Default::default()
+        };
+        let highlight = TextFormat {
+            color: RethTheme::BACKGROUND,
+            background: RethTheme::PRIMARY,
+            ..base.clone()
+        };
+
+        let mut job = LayoutJob::default();
+        let mut last = 0;
+        if let Some(pattern) = &self.search_pattern {
+            for found in pattern.find_iter(&text).filter(|found| !found.is_empty()) {
+                job.append(&text[last..found.start()], 0.0, base.clone());
+                job.append(found.as_str(), 0.0, highlight.clone());
+                last = found.end();
+            }
+        }
+        job.append(&text[last..], 0.0, base);
+        job
+    }
+}
@@ -0,0 +1,349 @@
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use egui_plot::{Corner, Legend, Line, LineStyle, Plot, PlotPoints};
+use crate::metric_store::{self, MetricStore, StoredRange};
+use crate::metrics::{self, MetricHistory};
+use crate::theme::RethTheme;
+
+/// How often a range ending now is read again from the store
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Format of the custom range inputs
+const CUSTOM_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Result of reading the store: the range read and its points, or an error message
+type RangeLoad = Result<((DateTime<Local>, DateTime<Local>), StoredRange), String>;
+
+/// Time ranges offered in the expanded metric view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeRange {
+    Live, // The in-memory history of this session
+    Minutes15,
+    Hour,
+    Day,
+    Week,
+    Custom,
+}
+
+impl TimeRange {
+    pub const ALL: [TimeRange; 6] = [
+        TimeRange::Live,
+        TimeRange::Minutes15,
+        TimeRange::Hour,
+        TimeRange::Day,
+        TimeRange::Week,
+        TimeRange::Custom,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeRange::Live => "Live",
+            TimeRange::Minutes15 => "15m",
+            TimeRange::Hour => "1h",
+            TimeRange::Day => "24h",
+            TimeRange::Week => "7d",
+            TimeRange::Custom => "Custom",
+        }
+    }
+
+    fn duration(&self) -> Option<chrono::Duration> {
+        match self {
+            TimeRange::Minutes15 => Some(chrono::Duration::minutes(15)),
+            TimeRange::Hour => Some(chrono::Duration::hours(1)),
+            TimeRange::Day => Some(chrono::Duration::hours(24)),
+            TimeRange::Week => Some(chrono::Duration::days(7)),
+            TimeRange::Live | TimeRange::Custom => None,
+        }
+    }
+}
+
+/// Range picker and stored history plot for the expanded metric view
+pub struct MetricRangeView {
+    range: TimeRange,
+    custom_from: String,
+    custom_to: String,
+    custom_error: Option<String>,
+    series: String, // Series id the loaded points belong to
+    bounds: Option<(DateTime<Local>, DateTime<Local>)>, // Range the loaded points were read for
+    loaded: Option<StoredRange>,
+    loader: Option<mpsc::Receiver<RangeLoad>>,
+    load_error: Option<String>,
+    requested_at: Option<Instant>, // None until the current range has been requested
+}
+
+impl Default for MetricRangeView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricRangeView {
+    pub fn new() -> Self {
+        Self {
+            range: TimeRange::Live,
+            custom_from: String::new(),
+            custom_to: String::new(),
+            custom_error: None,
+            series: String::new(),
+            bounds: None,
+            loaded: None,
+            loader: None,
+            load_error: None,
+            requested_at: None,
+        }
+    }
+
+    /// Whether the session's in-memory graph is selected rather than stored history
+    pub fn is_live(&self) -> bool {
+        self.range == TimeRange::Live
+    }
+
+    /// Range buttons, plus from and to inputs for a custom range
+    pub fn show_range_picker(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Range:");
+            for range in TimeRange::ALL {
+                if ui.selectable_label(self.range == range, range.label()).clicked() && self.range != range {
+                    self.range = range;
+                    if range == TimeRange::Custom && self.custom_from.is_empty() {
+                        let now = Local::now();
+                        self.custom_from = (now - chrono::Duration::hours(1)).format(CUSTOM_TIME_FORMAT).to_string();
+                        self.custom_to = now.format(CUSTOM_TIME_FORMAT).to_string();
+                    }
+                    self.reload();
+                }
+            }
+        });
+
+        if self.range == TimeRange::Custom {
+            ui.horizontal(|ui| {
+                ui.label("From");
+                ui.add(egui::TextEdit::singleline(&mut self.custom_from).desired_width(130.0));
+                ui.label("To");
+                ui.add(egui::TextEdit::singleline(&mut self.custom_to).desired_width(130.0));
+                if ui.button("Show").clicked() {
+                    self.reload();
+                }
+                match &self.custom_error {
+                    Some(error) => ui.label(RethTheme::error_text(error)),
+                    None => ui.label(RethTheme::muted_text("YYYY-MM-DD HH:MM")),
+                };
+            });
+        }
+    }
+
+    /// Plot the stored history of a card's series; grouped cards get one line per label value
+    pub fn show_stored(&mut self, ui: &mut egui::Ui, metric: &MetricHistory, series: &str, grouped: bool) {
+        if self.series != series {
+            self.series = series.to_string();
+            self.loaded = None;
+            self.reload();
+        }
+        self.poll_loader();
+        self.request(grouped);
+        if self.loader.is_some() {
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        }
+
+        let (Some(loaded), Some((from, to))) = (&self.loaded, self.bounds) else {
+            ui.horizontal(|ui| match &self.load_error {
+                Some(error) => {
+                    ui.label(RethTheme::error_text(&format!("Failed to read metric history: {}", error)));
+                }
+                None => {
+                    ui.spinner();
+                    ui.label(RethTheme::muted_text("Loading history…"));
+                }
+            });
+            return;
+        };
+
+        let prefix = metric_store::group_series_id(series, "");
+        let lines: Vec<(String, Vec<[f64; 2]>)> = loaded
+            .series
+            .iter()
+            .filter_map(|(id, points)| {
+                let name = if grouped { id.strip_prefix(&prefix)?.to_string() } else { String::new() };
+                let points = metric.mode.derive(points, metric.is_counter());
+                (!points.is_empty()).then_some((name, points))
+            })
+            .collect();
+        if lines.is_empty() {
+            ui.vertical_centered(|ui| {
+                ui.add_space(40.0);
+                ui.label(RethTheme::muted_text("No stored history for this range"));
+            });
+            return;
+        }
+
+        // Don't join points across times the app wasn't recording
+        let max_gap = (loaded.resolution.as_secs_f64() * 5.0).max(5.0);
+        let time_format = if to - from > chrono::Duration::hours(24) { "%m-%d %H:%M" } else { "%H:%M" };
+        let unit = metric.display_unit();
+        let points: usize = lines.iter().map(|(_, points)| points.len()).sum();
+
+        let mut plot = Plot::new(("metric_range_plot", series))
+            .height(ui.available_height() - 30.0)
+            .include_x(from.timestamp() as f64)
+            .include_x(to.timestamp() as f64)
+            .include_y(0.0)
+            .show_grid([false, false])
+            .allow_zoom(false)
+            .allow_drag(false)
+            .allow_boxed_zoom(false)
+            .allow_scroll(false)
+            .show_background(false)
+            .y_axis_width(4)
+            .x_axis_formatter(move |value, _max_chars, _range| format_time(value, time_format))
+            .label_formatter(move |name, value| {
+                let time = format_time(value.x, "%Y-%m-%d %H:%M:%S");
+                if name.is_empty() {
+                    format!("{}\n{:.2} {}", time, value.y, unit)
+                } else {
+                    format!("{}\n{}: {:.2} {}", time, name, value.y, unit)
+                }
+            });
+        if grouped {
+            plot = plot.legend(Legend::default().position(Corner::LeftTop));
+        }
+        plot.show(ui, |plot_ui| {
+            for (index, (name, points)) in lines.into_iter().enumerate() {
+                let color = if metric.distribution.is_some() {
+                    RethTheme::quantile_color(&name)
+                } else if grouped {
+                    line_color(index)
+                } else {
+                    RethTheme::PRIMARY
+                };
+                for run in split_at_gaps(points, max_gap) {
+                    let line = Line::new(PlotPoints::new(run)).color(color).width(2.0);
+                    let line = if metric.distribution.is_some() && name == metrics::MEAN_LINE {
+                        line.name(&name).style(LineStyle::dashed_loose())
+                    } else if metric.distribution.is_some() {
+                        line.name(&name).fill(0.0)
+                    } else if grouped {
+                        line.name(&name)
+                    } else {
+                        line.fill(0.0)
+                    };
+                    plot_ui.line(line);
+                }
+            }
+        });
+
+        ui.label(format!("Showing {} stored points at {} resolution", points, format_resolution(loaded.resolution)));
+    }
+
+    /// Read the current range again on the next frame
+    fn reload(&mut self) {
+        self.requested_at = None;
+        self.custom_error = None;
+        self.load_error = None;
+    }
+
+    /// Start reading the store when the range changed or a range ending now is due a refresh
+    fn request(&mut self, grouped: bool) {
+        if self.range == TimeRange::Live || self.loader.is_some() {
+            return;
+        }
+        let due = match self.requested_at {
+            None => true,
+            Some(at) => self.range != TimeRange::Custom && at.elapsed() >= REFRESH_INTERVAL,
+        };
+        if !due {
+            return;
+        }
+        self.requested_at = Some(Instant::now());
+
+        let bounds = match self.range.duration() {
+            Some(duration) => {
+                let now = Local::now();
+                (now - duration, now)
+            }
+            None => match (parse_time(&self.custom_from), parse_time(&self.custom_to)) {
+                (Some(from), Some(to)) if from < to => (from, to),
+                (Some(_), Some(_)) => {
+                    self.custom_error = Some("From must be before To".to_string());
+                    return;
+                }
+                _ => {
+                    self.custom_error = Some("Use the format YYYY-MM-DD HH:MM".to_string());
+                    return;
+                }
+            },
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let series = self.series.clone();
+        std::thread::spawn(move || {
+            let prefix = metric_store::group_series_id(&series, "");
+            let result = MetricStore::query(bounds.0, bounds.1, |id| id == series || (grouped && id.starts_with(&prefix)))
+                .map(|range| (bounds, range))
+                .map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        });
+        self.loader = Some(receiver);
+    }
+
+    /// Pick up the points once the background read has finished
+    fn poll_loader(&mut self) {
+        let Some(receiver) = &self.loader else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok((bounds, range))) => {
+                self.bounds = Some(bounds);
+                self.loaded = Some(range);
+                self.load_error = None;
+                self.loader = None;
+            }
+            Ok(Err(e)) => {
+                eprintln!("Failed to read metric history: {}", e);
+                self.load_error = Some(e);
+                self.loader = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.loader = None,
+        }
+    }
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(text.trim(), CUSTOM_TIME_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// Format unix seconds as local time
+fn format_time(seconds: f64, format: &str) -> String {
+    Local
+        .timestamp_opt(seconds as i64, 0)
+        .single()
+        .map(|time| time.format(format).to_string())
+        .unwrap_or_default()
+}
+
+fn format_resolution(resolution: Duration) -> String {
+    match resolution.as_secs() {
+        seconds if seconds >= 60 => format!("{}m", seconds / 60),
+        seconds => format!("{}s", seconds),
+    }
+}
+
+/// The color egui_plot picks for the `index`th line, so grouped lines match the live graph
+fn line_color(index: usize) -> egui::Color32 {
+    let golden_ratio = (5.0_f32.sqrt() - 1.0) / 2.0;
+    egui::epaint::Hsva::new(index as f32 * golden_ratio, 0.85, 0.5, 1.0).into()
+}
+
+/// Split points into runs wherever consecutive points are more than `max_gap` seconds apart
+fn split_at_gaps(points: Vec<[f64; 2]>, max_gap: f64) -> Vec<Vec<[f64; 2]>> {
+    let mut runs: Vec<Vec<[f64; 2]>> = Vec::new();
+    for point in points {
+        match runs.last_mut() {
+            Some(run) if run.last().is_some_and(|last| point[0] - last[0] <= max_gap) => run.push(point),
+            _ => runs.push(vec![point]),
+        }
+    }
+    runs
+}
@@ -1,9 +1,15 @@
 //! UI modules for the Reth Desktop application
 
+pub mod alert_rules;
 pub mod desktop_settings;
+pub mod log_viewer;
+pub mod metric_range;
 pub mod node_settings;
 pub mod start_config;
 
+pub use alert_rules::AlertRulesWindow;
 pub use desktop_settings::DesktopSettingsWindow;
+pub use log_viewer::LogViewer;
+pub use metric_range::MetricRangeView;
 pub use node_settings::NodeSettingsWindow;
 pub use start_config::StartConfigWindow;
\ No newline at end of file
@@ -1,6 +1,9 @@
 use crate::theme::RethTheme;
 use crate::reth_node::{RethNode, CliOption};
 use crate::settings::{DesktopSettings, DesktopSettingsManager};
+use crate::launch_args::{self, LaunchArg};
+use crate::preflight::{self, IssueSeverity, PreflightReport};
+use crate::cli_schema::CliSchemaDiff;
 
 pub struct StartConfigWindow;
 
@@ -14,7 +17,8 @@ impl StartConfigWindow {
         selected_cli_option: &mut Option<usize>,
         parameter_value: &mut String,
         selected_values: &mut Vec<String>,
-        pending_launch_args: &mut Vec<String>,
+        pending_launch_args: &mut Vec<LaunchArg>,
+        cli_schema_diff: &mut Option<CliSchemaDiff>,
     ) -> bool {
         let mut restart_requested = false;
         
@@ -24,6 +28,14 @@ impl StartConfigWindow {
             ui.heading("Start Configuration");
             ui.add_space(16.0);
             
+            // Flags that changed since the previously installed reth version
+            if let Some(diff) = cli_schema_diff.as_ref() {
+                if !Self::show_upgrade_diff(ui, diff, desktop_settings) {
+                    *cli_schema_diff = None;
+                }
+                ui.add_space(16.0);
+            }
+            
             // Show reth binary location first
             Self::show_binary_location(ui);
             ui.add_space(16.0);
@@ -50,7 +62,7 @@ impl StartConfigWindow {
         selected_cli_option: &mut Option<usize>,
         parameter_value: &mut String,
         selected_values: &mut Vec<String>,
-        pending_launch_args: &mut Vec<String>,
+        pending_launch_args: &mut Vec<LaunchArg>,
         desktop_settings: &mut DesktopSettings,
         reth_node: &RethNode,
     ) -> bool {
@@ -77,32 +89,37 @@ impl StartConfigWindow {
                         } else {
                             "Select parameter".to_string()
                         };
-                        
-                        egui::ComboBox::from_id_source("cli_option_selector")
-                            .selected_text(selected_option_name)
-                            .show_ui(ui, |ui| {
-                                for (idx, option) in available_cli_options.iter().enumerate() {
-                                    let response = ui.selectable_value(
-                                        selected_cli_option,
-                                        Some(idx),
-                                        &option.name,
-                                    );
-                                    
-                                    if response.clicked() {
-                                        parameter_value.clear();
-                                        selected_values.clear();
-                                    }
-                                }
-                            });
+                        ui.label(RethTheme::monospace_text(&selected_option_name));
                     });
                     
+                    // Search box for the picker, kept in memory between frames
+                    let search_id = egui::Id::new("cli_option_search");
+                    let mut search: String = ui.ctx().memory(|mem| {
+                        mem.data.get_temp::<String>(search_id).unwrap_or_default()
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Search:");
+                        if ui.add(egui::TextEdit::singleline(&mut search)
+                            .hint_text("flag, description or env var")
+                            .desired_width(260.0))
+                            .changed() {
+                            let search = search.clone();
+                            ui.ctx().memory_mut(|mem| mem.data.insert_temp(search_id, search));
+                        }
+                    });
+                    
+                    if Self::show_option_picker(ui, available_cli_options, &search, selected_cli_option) {
+                        parameter_value.clear();
+                        selected_values.clear();
+                    }
+                    
                     // Show description and value input for selected parameter
                     if let Some(idx) = *selected_cli_option {
                         if idx < available_cli_options.len() {
                             let option = &available_cli_options[idx];
                             
                             ui.add_space(4.0);
-                            ui.label(RethTheme::muted_text(&option.description));
+                            Self::show_option_details(ui, option);
                             
                             if option.takes_value {
                                 ui.horizontal(|ui| {
@@ -166,21 +183,18 @@ impl StartConfigWindow {
                                 };
                                 
                                 if ui.add_enabled(can_add, egui::Button::new("Add Parameter")).clicked() {
-                                    let mut new_args = vec![format!("--{}", option.name)];
+                                    let mut values = Vec::new();
                                     
                                     if option.takes_value {
                                         if option.accepts_multiple {
-                                            for value in selected_values.iter() {
-                                                new_args.push(value.clone());
-                                            }
-                                            selected_values.clear();
+                                            values.append(selected_values);
                                         } else {
-                                            new_args.push(parameter_value.clone());
+                                            values.push(parameter_value.trim().to_string());
                                             parameter_value.clear();
                                         }
                                     }
                                     
-                                    pending_launch_args.extend(new_args);
+                                    pending_launch_args.push(LaunchArg::for_option(option, values));
                                     *selected_cli_option = None;
                                 }
                             });
@@ -256,19 +270,9 @@ impl StartConfigWindow {
                         }
                         
                         // Add custom parameters (unless pending deletion)
-                        for custom_param in &desktop_settings.custom_launch_args {
-                            if custom_param.starts_with("--") {
-                                // Parse parameter and value if it has one
-                                let parts: Vec<&str> = custom_param.splitn(2, ' ').collect();
-                                let param_name = parts[0].to_string();
-                                
-                                if !pending_deletions.contains(&param_name) {
-                                    if parts.len() == 2 {
-                                        all_parameters.push((param_name, Some(parts[1].to_string())));
-                                    } else {
-                                        all_parameters.push((custom_param.clone(), None));
-                                    }
-                                }
+                        for custom_arg in &desktop_settings.custom_launch_args {
+                            if !pending_deletions.contains(&custom_arg.flag) {
+                                all_parameters.push((custom_arg.flag.clone(), custom_arg.values_text()));
                             }
                         }
                         
@@ -310,7 +314,7 @@ impl StartConfigWindow {
                                             .on_hover_text("Save changes")
                                             .clicked() {
                                             // Apply the edit
-                                            Self::apply_parameter_edit(param, edit_buffer, desktop_settings);
+                                            Self::apply_parameter_edit(param, edit_buffer, desktop_settings, available_cli_options);
                                             
                                             // Clear editing state
                                             ui.ctx().memory_mut(|mem| {
@@ -411,13 +415,22 @@ impl StartConfigWindow {
             });
             ui.add_space(8.0);
                 
+                // Check the arguments Start would use, including unsaved additions
+                let mut custom_args = desktop_settings.custom_launch_args.clone();
+                custom_args.extend(pending_launch_args.iter().cloned());
+                let custom_argv = launch_args::render_argv(&custom_args, available_cli_options);
+                let launch_argv = RethNode::build_launch_args(&custom_argv, desktop_settings);
+                let preflight = preflight::validate_launch_args(&launch_argv[1..], available_cli_options);
+                Self::show_preflight(ui, &preflight);
+                ui.add_space(8.0);
+                
                 // Show pending parameters
                 if !pending_launch_args.is_empty() {
                     ui.label(RethTheme::warning_text("Pending parameters (not saved):"));
                     let mut to_remove = Vec::new();
                     for (i, arg) in pending_launch_args.iter().enumerate() {
                         ui.horizontal(|ui| {
-                            ui.label(RethTheme::warning_text(arg));
+                            ui.label(RethTheme::warning_text(&arg.to_string()));
                             if ui.small_button("🗑").clicked() {
                                 to_remove.push(i);
                             }
@@ -455,7 +468,7 @@ impl StartConfigWindow {
                                 }
                                 _ => {
                                     // Remove from custom_launch_args
-                                    desktop_settings.custom_launch_args.retain(|arg| !arg.starts_with(&format!("{} ", param_to_delete)) && arg != param_to_delete);
+                                    desktop_settings.custom_launch_args.retain(|arg| &arg.flag != param_to_delete);
                                 }
                             }
                         }
@@ -506,7 +519,7 @@ impl StartConfigWindow {
                     });
                     ui.add_space(4.0);
                     ui.horizontal(|ui| {
-                        if ui.add(egui::Button::new(egui::RichText::new("🔄 Restart Node")
+                        if ui.add_enabled(!preflight.has_errors(), egui::Button::new(egui::RichText::new("🔄 Restart Node")
                             .color(RethTheme::WARNING))
                             .fill(RethTheme::WARNING.gamma_multiply(0.2))
                             .stroke(egui::Stroke::new(1.0, RethTheme::WARNING)))
@@ -527,6 +540,185 @@ impl StartConfigWindow {
         restart_requested
     }
     
+    /// Searchable list of CLI options grouped by their help section.
+    /// Returns true when a different option was selected.
+    fn show_option_picker(
+        ui: &mut egui::Ui,
+        available_cli_options: &[CliOption],
+        search: &str,
+        selected_cli_option: &mut Option<usize>,
+    ) -> bool {
+        let mut changed = false;
+        let query = search.trim().to_lowercase();
+        
+        // Keep sections in the order reth lists them
+        let mut sections: Vec<(&str, Vec<usize>)> = Vec::new();
+        for (idx, option) in available_cli_options.iter().enumerate() {
+            // The desktop sets these itself from the node defaults
+            if preflight::DESKTOP_MANAGED_FLAGS.contains(&option.name.as_str()) {
+                continue;
+            }
+            let matches = query.is_empty()
+                || option.name.to_lowercase().contains(&query)
+                || option.description.to_lowercase().contains(&query)
+                || option.aliases.iter().any(|alias| alias.to_lowercase().contains(&query))
+                || option.env.as_ref().is_some_and(|env| env.to_lowercase().contains(&query));
+            if !matches {
+                continue;
+            }
+            let section = if option.section.is_empty() { "Options" } else { option.section.as_str() };
+            match sections.iter_mut().find(|(name, _)| *name == section) {
+                Some((_, indices)) => indices.push(idx),
+                None => sections.push((section, vec![idx])),
+            }
+        }
+        
+        egui::ScrollArea::vertical()
+            .id_source("cli_option_picker")
+            .max_height(220.0)
+            .show(ui, |ui| {
+                if sections.is_empty() {
+                    ui.label(RethTheme::muted_text("No matching parameters"));
+                }
+                for (section, indices) in &sections {
+                    egui::CollapsingHeader::new(format!("{} ({})", section, indices.len()))
+                        .id_source(("cli_option_section", *section))
+                        .default_open(!query.is_empty())
+                        .open(if query.is_empty() { None } else { Some(true) })
+                        .show(ui, |ui| {
+                            for &idx in indices {
+                                let option = &available_cli_options[idx];
+                                let label = match &option.value_name {
+                                    Some(value_name) => format!("{} <{}>", option.name, value_name),
+                                    None => option.name.clone(),
+                                };
+                                let response = ui.selectable_label(*selected_cli_option == Some(idx), label)
+                                    .on_hover_text(&option.description);
+                                if response.clicked() && *selected_cli_option != Some(idx) {
+                                    *selected_cli_option = Some(idx);
+                                    changed = true;
+                                }
+                            }
+                        });
+                }
+            });
+        
+        changed
+    }
+    
+    fn show_option_details(ui: &mut egui::Ui, option: &CliOption) {
+        ui.label(RethTheme::muted_text(&option.description));
+        
+        let mut details = Vec::new();
+        if let Some(short) = &option.short {
+            details.push(format!("short: {}", short));
+        }
+        if let Some(default_value) = &option.default_value {
+            details.push(format!("default: {}", default_value));
+        }
+        if let Some(env) = &option.env {
+            details.push(format!("env: {}", env));
+        }
+        if !option.aliases.is_empty() {
+            details.push(format!("aliases: {}", option.aliases.join(", ")));
+        }
+        if !details.is_empty() {
+            ui.label(RethTheme::muted_text(&details.join("  ·  ")));
+        }
+        
+        // Full help only when there's more than the summary line
+        if option.help.trim() != option.description.trim() && !option.help.is_empty() {
+            ui.collapsing("Full help", |ui| {
+                ui.label(RethTheme::muted_text(&option.help));
+            });
+        }
+    }
+    
+    fn show_preflight(ui: &mut egui::Ui, report: &PreflightReport) {
+        ui.label(RethTheme::text("Launch Check"));
+        ui.add_space(4.0);
+        
+        if report.issues.is_empty() {
+            ui.label(RethTheme::success_text("✓ All launch parameters look valid"));
+            return;
+        }
+        
+        ui.label(RethTheme::muted_text(&format!("{} error(s), {} warning(s)", report.error_count(), report.warning_count())));
+        for issue in &report.issues {
+            ui.horizontal_wrapped(|ui| {
+                match issue.severity {
+                    IssueSeverity::Error => ui.label(RethTheme::error_text(&format!("✕ {}", issue.message))),
+                    IssueSeverity::Warning => ui.label(RethTheme::warning_text(&format!("⚠ {}", issue.message))),
+                };
+            });
+        }
+        
+        if report.has_errors() {
+            ui.add_space(4.0);
+            ui.label(RethTheme::muted_text("Fix the errors above before starting the node."));
+        }
+    }
+    
+    /// Show which flags changed in the new reth version. Returns false once dismissed.
+    fn show_upgrade_diff(ui: &mut egui::Ui, diff: &CliSchemaDiff, desktop_settings: &mut DesktopSettings) -> bool {
+        let mut keep = true;
+        
+        ui.label(RethTheme::text("Reth CLI Changes"));
+        ui.add_space(4.0);
+        ui.label(RethTheme::muted_text(&format!("Upgraded from {} to {}", diff.from_version, diff.to_version)));
+        ui.label(RethTheme::muted_text(&format!(
+            "{} flag(s) added, {} removed, {} renamed",
+            diff.added.len(), diff.removed.len(), diff.renamed.len()
+        )));
+        
+        if diff.affected_args.is_empty() {
+            ui.label(RethTheme::success_text("✓ Your saved launch parameters are not affected"));
+        } else {
+            ui.add_space(4.0);
+            for flag in &diff.affected_args {
+                ui.horizontal_wrapped(|ui| {
+                    match diff.renamed_to(flag) {
+                        Some(new_name) => ui.label(RethTheme::warning_text(&format!("⚠ {} was renamed to {}", flag, new_name))),
+                        None => ui.label(RethTheme::error_text(&format!("✕ {} no longer exists", flag))),
+                    };
+                });
+            }
+            
+            let has_renames = diff.affected_args.iter().any(|flag| diff.renamed_to(flag).is_some());
+            if has_renames && ui.button("Use New Flag Names").clicked() {
+                for arg in desktop_settings.custom_launch_args.iter_mut() {
+                    if let Some(new_name) = diff.renamed_to(&arg.flag) {
+                        arg.flag = new_name.to_string();
+                    }
+                }
+                if let Err(e) = DesktopSettingsManager::save_desktop_settings(desktop_settings) {
+                    println!("Failed to save settings after renaming flags: {}", e);
+                } else {
+                    println!("Renamed launch parameters to match {}", diff.to_version);
+                }
+            }
+        }
+        
+        if !diff.added.is_empty() || !diff.removed.is_empty() || !diff.renamed.is_empty() {
+            ui.collapsing("All changes", |ui| {
+                for (old_name, new_name) in &diff.renamed {
+                    ui.label(RethTheme::monospace_text(&format!("~ {} → {}", old_name, new_name)));
+                }
+                for flag in &diff.removed {
+                    ui.label(RethTheme::monospace_text(&format!("- {}", flag)));
+                }
+                for flag in &diff.added {
+                    ui.label(RethTheme::monospace_text(&format!("+ {}", flag)));
+                }
+            });
+        }
+        
+        if ui.button("Dismiss").clicked() {
+            keep = false;
+        }
+        keep
+    }
+    
     fn show_binary_location(ui: &mut egui::Ui) {
         let reth_path = dirs::home_dir()
             .unwrap_or_default()
@@ -540,7 +732,7 @@ impl StartConfigWindow {
         });
     }
     
-    fn apply_parameter_edit(param_name: &str, new_value: &str, desktop_settings: &mut DesktopSettings) {
+    fn apply_parameter_edit(param_name: &str, new_value: &str, desktop_settings: &mut DesktopSettings, available_cli_options: &[CliOption]) {
         match param_name {
             "--chain" => {
                 desktop_settings.reth_defaults.chain = new_value.to_string();
@@ -567,11 +759,16 @@ impl StartConfigWindow {
                 desktop_settings.reth_defaults.file_log_max_files = new_value.to_string();
             }
             _ => {
-                // For custom parameters, update in custom_launch_args
-                // First remove any existing version
-                desktop_settings.custom_launch_args.retain(|arg| !arg.starts_with(&format!("{} ", param_name)));
-                // Then add the new version
-                desktop_settings.custom_launch_args.push(format!("{} {}", param_name, new_value));
+                // For custom parameters, update the values in custom_launch_args
+                let option = available_cli_options.iter().find(|option| option.name == param_name);
+                let values = LaunchArg::parse_values(new_value, option);
+                match desktop_settings.custom_launch_args.iter_mut().find(|arg| arg.flag == param_name) {
+                    Some(arg) => arg.set_values(values, option),
+                    None => desktop_settings.custom_launch_args.push(match option {
+                        Some(option) => LaunchArg::for_option(option, values),
+                        None => LaunchArg::new(param_name, values),
+                    }),
+                }
             }
         }
         